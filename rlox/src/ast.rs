@@ -3,6 +3,7 @@ use std::fmt;
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
 pub enum CallableType {
+    Abstract,
     ClassMethod,
     Function,
     Lambda,
@@ -13,6 +14,7 @@ pub enum CallableType {
 impl fmt::Display for CallableType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
+            CallableType::Abstract => write!(f, "abstract"),
             CallableType::ClassMethod => write!(f, "class_method"),
             CallableType::Function => write!(f, "function"),
             CallableType::Lambda => write!(f, "lambda"),
@@ -33,6 +35,10 @@ pub enum Expr {
         operator: Token,
         right: Box<Expr>,
     },
+    Block {
+        statements: Vec<Box<Stmt>>,
+        final_expr: Box<Expr>,
+    },
     Call {
         callee: Box<Expr>,
         paren: Token, // this is to record line number
@@ -49,6 +55,9 @@ pub enum Expr {
         parameters: Vec<Token>,
         body: Vec<Box<Stmt>>,
     },
+    List {
+        elements: Vec<Box<Expr>>,
+    },
     Literal {
         value: crate::scanner::Literal,
     },
@@ -57,6 +66,10 @@ pub enum Expr {
         operator: Token,
         right: Box<Expr>,
     },
+    OptionalGet {
+        object: Box<Expr>,
+        name: Token,
+    },
     Set {
         object: Box<Expr>,
         name: Token,
@@ -95,6 +108,10 @@ impl Expr {
                 operator,
                 right,
             } => visitor.visit_binary_expr(&self, &left, &operator, &right),
+            Expr::Block {
+                statements,
+                final_expr,
+            } => visitor.visit_block_expr(self, statements, final_expr),
             Expr::Call {
                 callee,
                 paren,
@@ -105,12 +122,16 @@ impl Expr {
             Expr::Lambda { parameters, body } => {
                 visitor.visit_lambda_expr(&self, &parameters, &body)
             }
+            Expr::List { elements } => visitor.visit_list_expr(self, elements),
             Expr::Literal { value } => visitor.visit_literal_expr(&self, &value),
             Expr::Logical {
                 left,
                 operator,
                 right,
             } => visitor.visit_logical_expr(&self, &left, &operator, &right),
+            Expr::OptionalGet { object, name } => {
+                visitor.visit_optional_get_expr(self, object, name)
+            }
             Expr::Set {
                 object,
                 name,
@@ -140,6 +161,7 @@ pub trait ExprVisitor<R> {
         operator: &Token,
         right: &Box<Expr>,
     ) -> R;
+    fn visit_block_expr(&mut self, expr: &Expr, statements: &[Box<Stmt>], final_expr: &Expr) -> R;
     fn visit_call_expr(
         &mut self,
         expr: &Expr,
@@ -155,6 +177,7 @@ pub trait ExprVisitor<R> {
         parameters: &Vec<Token>,
         body: &Vec<Box<Stmt>>,
     ) -> R;
+    fn visit_list_expr(&mut self, expr: &Expr, elements: &[Box<Expr>]) -> R;
     fn visit_literal_expr(&mut self, expr: &Expr, literal: &crate::scanner::Literal) -> R;
     fn visit_logical_expr(
         &mut self,
@@ -163,6 +186,7 @@ pub trait ExprVisitor<R> {
         operator: &Token,
         right: &Box<Expr>,
     ) -> R;
+    fn visit_optional_get_expr(&mut self, expr: &Expr, object: &Expr, name: &Token) -> R;
     fn visit_set_expr(
         &mut self,
         expr: &Expr,
@@ -187,6 +211,11 @@ pub trait ExprVisitor<R> {
 
 #[derive(Clone, PartialEq, Eq, Debug, Hash)]
 pub enum Stmt {
+    Assert {
+        condition: Box<Expr>,
+        keyword: Token,
+        message: Option<Box<Expr>>,
+    },
     Block {
         statements: Vec<Box<Stmt>>,
     },
@@ -199,9 +228,19 @@ pub enum Stmt {
         methods: Vec<Box<Stmt>>,
         class_methods: Vec<Box<Stmt>>,
     },
+    Destructure {
+        names: Vec<Token>, // bound to the elements of the list initializer, in order
+        initializer: Box<Expr>,
+    },
     Expression {
         expression: Box<Expr>,
     },
+    For {
+        initializer: Option<Box<Stmt>>,
+        condition: Option<Box<Expr>>,
+        increment: Option<Box<Expr>>,
+        body: Box<Stmt>,
+    },
     Function {
         name: Token,
         parameters: Vec<Token>,
@@ -215,6 +254,8 @@ pub enum Stmt {
     },
     Print {
         expression: Box<Expr>,
+        newline: bool,            // false for `write`
+        precision: Option<usize>, // decimal places for numbers, from `print :N`
     },
     Return {
         keyword: Token,
@@ -224,6 +265,9 @@ pub enum Stmt {
         name: Token,
         initializer: Option<Box<Expr>>,
     },
+    VarList {
+        declarations: Vec<Box<Stmt>>, // Stmt::Var, declared in order
+    },
     While {
         condition: Box<Expr>,
         body: Box<Stmt>,
@@ -236,6 +280,11 @@ impl Stmt {
         T: StmtVisitor<R>,
     {
         match self {
+            Stmt::Assert {
+                condition,
+                keyword,
+                message,
+            } => visitor.visit_assert_stmt(self, condition, keyword, message),
             Stmt::Block { statements } => visitor.visit_block_stmt(&self, statements),
             Stmt::Break { keyword } => visitor.visit_break_stmt(&self, keyword),
             Stmt::Class {
@@ -244,7 +293,16 @@ impl Stmt {
                 methods,
                 class_methods,
             } => visitor.visit_class_stmt(&self, name, super_class, methods, class_methods),
+            Stmt::Destructure { names, initializer } => {
+                visitor.visit_destructure_stmt(self, names, initializer)
+            }
             Stmt::Expression { expression } => visitor.visit_expression_stmt(&self, &expression),
+            Stmt::For {
+                initializer,
+                condition,
+                increment,
+                body,
+            } => visitor.visit_for_stmt(self, initializer, condition, increment, body),
             Stmt::Function {
                 name,
                 parameters,
@@ -256,9 +314,14 @@ impl Stmt {
                 then_branch,
                 else_branch,
             } => visitor.visit_if_stmt(&self, condition, then_branch, else_branch),
-            Stmt::Print { expression } => visitor.visit_print_stmt(&self, &expression),
+            Stmt::Print {
+                expression,
+                newline,
+                precision,
+            } => visitor.visit_print_stmt(self, expression, *newline, *precision),
             Stmt::Return { keyword, value } => visitor.visit_return_stmt(&self, &keyword, &value),
             Stmt::Var { name, initializer } => visitor.visit_var_stmt(&self, &name, &initializer),
+            Stmt::VarList { declarations } => visitor.visit_var_list_stmt(self, declarations),
             Stmt::While { condition, body } => visitor.visit_while_stmt(&self, &condition, &body),
         }
     }
@@ -267,6 +330,13 @@ impl Stmt {
 // -----------------------------------------------------------------------
 
 pub trait StmtVisitor<R> {
+    fn visit_assert_stmt(
+        &mut self,
+        stmt: &Stmt,
+        condition: &Expr,
+        keyword: &Token,
+        message: &Option<Box<Expr>>,
+    ) -> R;
     fn visit_block_stmt(&mut self, stmt: &Stmt, statements: &Vec<Box<Stmt>>) -> R;
     fn visit_break_stmt(&mut self, stmt: &Stmt, keyword: &Token) -> R;
     fn visit_class_stmt(
@@ -277,7 +347,16 @@ pub trait StmtVisitor<R> {
         methods: &Vec<Box<Stmt>>,
         class_methods: &Vec<Box<Stmt>>,
     ) -> R;
+    fn visit_destructure_stmt(&mut self, stmt: &Stmt, names: &[Token], initializer: &Expr) -> R;
     fn visit_expression_stmt(&mut self, stmt: &Stmt, expression: &Box<Expr>) -> R;
+    fn visit_for_stmt(
+        &mut self,
+        stmt: &Stmt,
+        initializer: &Option<Box<Stmt>>,
+        condition: &Option<Box<Expr>>,
+        increment: &Option<Box<Expr>>,
+        body: &Stmt,
+    ) -> R;
     fn visit_function_stmt(
         &mut self,
         stmt: &Stmt,
@@ -293,8 +372,15 @@ pub trait StmtVisitor<R> {
         then_branch: &Box<Stmt>,
         else_branch: &Option<Box<Stmt>>,
     ) -> R;
-    fn visit_print_stmt(&mut self, stmt: &Stmt, expression: &Box<Expr>) -> R;
+    fn visit_print_stmt(
+        &mut self,
+        stmt: &Stmt,
+        expression: &Expr,
+        newline: bool,
+        precision: Option<usize>,
+    ) -> R;
     fn visit_return_stmt(&mut self, stmt: &Stmt, keyword: &Token, value: &Option<Box<Expr>>) -> R;
     fn visit_var_stmt(&mut self, stmt: &Stmt, name: &Token, initializer: &Option<Box<Expr>>) -> R;
+    fn visit_var_list_stmt(&mut self, stmt: &Stmt, declarations: &[Box<Stmt>]) -> R;
     fn visit_while_stmt(&mut self, stmt: &Stmt, condition: &Box<Expr>, body: &Box<Stmt>) -> R;
 }
@@ -21,7 +21,7 @@ impl AstPrinter {
         buffer
     }
 
-    fn parenthesize_exprs(&mut self, name: &str, expressions: &Vec<&Box<Expr>>, newline:bool) -> String {
+    fn parenthesize_exprs(&mut self, name: &str, expressions: &[&Expr], newline: bool) -> String {
         let mut sequence = String::from("(");
         sequence.push_str(name);
 
@@ -38,7 +38,12 @@ impl AstPrinter {
         return sequence;
     }
 
-    fn parenthesize_stmts(&mut self, name: &str, statements: &Vec<Box<Stmt>>, newline: bool) -> String {
+    fn parenthesize_stmts(
+        &mut self,
+        name: &str,
+        statements: &[Box<Stmt>],
+        newline: bool,
+    ) -> String {
         self.depth += 1;
         let mut sequence = String::from("(");
         sequence.push_str(name);
@@ -66,7 +71,7 @@ impl AstPrinter {
 impl ExprVisitor<String> for AstPrinter {
     fn visit_assign_expr(&mut self, __expr: &Expr, name: &Token, value: &Box<Expr>) -> String {
         let name = format!("assign \"{}\"", name.lexeme);
-        self.parenthesize_exprs(&name, &vec![value], false)
+        self.parenthesize_exprs(&name, &[value], false)
     }
 
     fn visit_binary_expr(
@@ -76,7 +81,24 @@ impl ExprVisitor<String> for AstPrinter {
         operator: &Token,
         right: &Box<Expr>,
     ) -> String {
-        self.parenthesize_exprs(&operator.lexeme, &vec![left, right], false)
+        self.parenthesize_exprs(&operator.lexeme, &[left, right], false)
+    }
+
+    fn visit_block_expr(
+        &mut self,
+        _expr: &Expr,
+        statements: &[Box<Stmt>],
+        final_expr: &Expr,
+    ) -> String {
+        let mut sequence = String::from("(block_expr");
+        for stmt in statements {
+            sequence.push(' ');
+            sequence.push_str(stmt.accept(self).trim_end());
+        }
+        sequence.push(' ');
+        sequence.push_str(final_expr.accept(self).as_str());
+        sequence.push(')');
+        sequence
     }
 
     fn visit_call_expr(
@@ -86,7 +108,7 @@ impl ExprVisitor<String> for AstPrinter {
         _paren: &Token,
         arguments: &Vec<Box<Expr>>,
     ) -> String {
-        let mut exprs = vec![];
+        let mut exprs: Vec<&Expr> = vec![];
         for arg in arguments {
             exprs.push(arg);
         }
@@ -95,11 +117,15 @@ impl ExprVisitor<String> for AstPrinter {
     }
 
     fn visit_get_expr(&mut self, _expr: &Expr, object: &Box<Expr>, name: &Token) -> String {
-        self.parenthesize_exprs(&name.lexeme, &vec![object], false)
+        self.parenthesize_exprs(&name.lexeme, &[object], false)
+    }
+
+    fn visit_optional_get_expr(&mut self, _expr: &Expr, object: &Expr, name: &Token) -> String {
+        self.parenthesize_exprs(&format!("?.{}", name.lexeme), &[object], false)
     }
 
     fn visit_grouping_expr(&mut self, _expr: &Expr, content: &Box<Expr>) -> String {
-        self.parenthesize_exprs("group", &vec![content], false)
+        self.parenthesize_exprs("group", &[content], false)
     }
 
     fn visit_lambda_expr(
@@ -111,6 +137,14 @@ impl ExprVisitor<String> for AstPrinter {
         self.parenthesize_stmts("lambda", body, true)
     }
 
+    fn visit_list_expr(&mut self, _expr: &Expr, elements: &[Box<Expr>]) -> String {
+        self.parenthesize_exprs(
+            "list",
+            &elements.iter().map(|e| e.as_ref()).collect::<Vec<_>>(),
+            false,
+        )
+    }
+
     fn visit_literal_expr(&mut self, _expr: &Expr, literal: &crate::scanner::Literal) -> String {
         literal.to_string()
     }
@@ -122,7 +156,7 @@ impl ExprVisitor<String> for AstPrinter {
         operator: &Token,
         right: &Box<Expr>,
     ) -> String {
-        self.parenthesize_exprs(&operator.lexeme, &vec![left, right], false)
+        self.parenthesize_exprs(&operator.lexeme, &[left, right], false)
     }
 
     fn visit_set_expr(
@@ -132,7 +166,11 @@ impl ExprVisitor<String> for AstPrinter {
         name: &Token,
         value: &Box<Expr>,
     ) -> String {
-        self.parenthesize_exprs(&format!("set \"{}\"", &name.lexeme), &vec![object, value], false)
+        self.parenthesize_exprs(
+            &format!("set \"{}\"", &name.lexeme),
+            &[object, value],
+            false,
+        )
     }
 
     fn visit_super_expr(&mut self, _expr: &Expr, _keyword: &Token, method: &Token) -> String {
@@ -146,7 +184,7 @@ impl ExprVisitor<String> for AstPrinter {
         then_value: &Box<Expr>,
         else_value: &Box<Expr>,
     ) -> String {
-        self.parenthesize_exprs("ternary", &vec![condition, then_value, else_value], false)
+        self.parenthesize_exprs("ternary", &[condition, then_value, else_value], false)
     }
 
     fn visit_this_expr(&mut self, _expr: &Expr, _keyword: &Token) -> String {
@@ -154,21 +192,35 @@ impl ExprVisitor<String> for AstPrinter {
     }
 
     fn visit_unary_expr(&mut self, _expr: &Expr, operator: &Token, right: &Box<Expr>) -> String {
-        self.parenthesize_exprs(&operator.lexeme, &vec![right], false)
+        self.parenthesize_exprs(&operator.lexeme, &[right], false)
     }
 
     fn visit_variable_expr(&mut self, _expr: &Expr, name: &Token) -> String {
-        self.parenthesize_exprs(&format!("var_expr \"{}\"", name.lexeme), &vec![], false)
+        self.parenthesize_exprs(&format!("var_expr \"{}\"", name.lexeme), &[], false)
     }
 }
 
 impl StmtVisitor<String> for AstPrinter {
+    fn visit_assert_stmt(
+        &mut self,
+        _stmt: &Stmt,
+        condition: &Expr,
+        _keyword: &Token,
+        message: &Option<Box<Expr>>,
+    ) -> String {
+        if let Some(message) = message {
+            self.parenthesize_exprs("assert", &[condition, message], true)
+        } else {
+            self.parenthesize_exprs("assert", &[condition], true)
+        }
+    }
+
     fn visit_block_stmt(&mut self, _stmt: &Stmt, statements: &Vec<Box<Stmt>>) -> String {
         self.parenthesize_stmts("block", statements, true)
     }
 
     fn visit_break_stmt(&mut self, _stmt: &Stmt, _keyword: &Token) -> String {
-        self.parenthesize_stmts("break", &vec![], false)
+        self.parenthesize_stmts("break", &[], false)
     }
 
     fn visit_class_stmt(
@@ -181,15 +233,53 @@ impl StmtVisitor<String> for AstPrinter {
     ) -> String {
         let all_methods = [&methods[..], &class_methods[..]].concat();
         if let Some(super_class) = super_class {
-            let sc = self.parenthesize_exprs("superclass", &vec![super_class], false);
+            let sc = self.parenthesize_exprs("superclass", &[super_class], false);
             self.parenthesize_stmts(&format!("(class {} < {})", name.lexeme, sc), &all_methods, true)
         } else {
             self.parenthesize_stmts(&format!("(class {})", name.lexeme), &all_methods, true)
         }
     }
 
+    fn visit_destructure_stmt(
+        &mut self,
+        _stmt: &Stmt,
+        names: &[Token],
+        initializer: &Expr,
+    ) -> String {
+        let names: Vec<String> = names
+            .iter()
+            .map(|name| format!("\"{}\"", name.lexeme))
+            .collect();
+        let name = format!("destructure_stmt ({})", names.join(" "));
+        self.parenthesize_exprs(&name, &[initializer], true)
+    }
+
     fn visit_expression_stmt(&mut self, _stmt: &Stmt, expression: &Box<Expr>) -> String {
-        self.parenthesize_exprs("expression", &vec![expression], true)
+        self.parenthesize_exprs("expression", &[expression], true)
+    }
+
+    fn visit_for_stmt(
+        &mut self,
+        _stmt: &Stmt,
+        initializer: &Option<Box<Stmt>>,
+        condition: &Option<Box<Expr>>,
+        increment: &Option<Box<Expr>>,
+        body: &Stmt,
+    ) -> String {
+        let initializer = match initializer {
+            Some(initializer) => initializer.accept(self).trim_end().to_owned(),
+            None => String::from("()"),
+        };
+        let condition = match condition {
+            Some(condition) => condition.accept(self),
+            None => String::from("()"),
+        };
+        let increment = match increment {
+            Some(increment) => increment.accept(self),
+            None => String::from("()"),
+        };
+        let name = format!("(for {} {} {})\n", initializer, condition, increment);
+        self.parenthesize_stmts(&name, &[Box::new(body.clone())], false)
     }
 
     fn visit_function_stmt(
@@ -210,16 +300,55 @@ impl StmtVisitor<String> for AstPrinter {
         then_branch: &Box<Stmt>,
         else_branch: &Option<Box<Stmt>>,
     ) -> String {
-        let mut statements = vec![then_branch.clone()];
-        if let Some(else_branch) = else_branch {
-            statements.push(else_branch.clone())
+        // render `else if` chains flat, as (if ...) (elif ...) (else), rather than
+        // nesting each if inside the previous one's else branch
+        self.depth += 1;
+        let mut sequence = String::from("(");
+        sequence.push_str(&self.parenthesize_exprs("if", &[condition], false));
+        sequence.push(' ');
+        sequence.push_str(&then_branch.accept(self));
+
+        let mut else_branch = else_branch;
+        while let Some(branch) = else_branch {
+            sequence.push(' ');
+            if let Stmt::If {
+                condition,
+                then_branch,
+                else_branch: next,
+            } = &**branch
+            {
+                sequence.push_str(&self.parenthesize_exprs("elif", &[condition], false));
+                sequence.push(' ');
+                sequence.push_str(&then_branch.accept(self));
+                else_branch = next;
+            } else {
+                sequence.push_str("(else) ");
+                sequence.push_str(&branch.accept(self));
+                else_branch = &None;
+            }
+        }
+
+        sequence.push(')');
+        self.depth -= 1;
+        if self.depth == 0 {
+            sequence.push('\n');
         }
-        let name = self.parenthesize_exprs("if", &vec![condition], false);
-        self.parenthesize_stmts(&name, &statements, false)
+        sequence
     }
 
-    fn visit_print_stmt(&mut self, _stmt: &Stmt, expression: &Box<Expr>) -> String {
-        self.parenthesize_exprs("print", &vec![expression], true)
+    fn visit_print_stmt(
+        &mut self,
+        _stmt: &Stmt,
+        expression: &Expr,
+        newline: bool,
+        precision: Option<usize>,
+    ) -> String {
+        let name = if newline { "print" } else { "write" };
+        let name = match precision {
+            Some(precision) => format!("{} :{}", name, precision),
+            None => String::from(name),
+        };
+        self.parenthesize_exprs(&name, &[expression], true)
     }
 
     fn visit_return_stmt(
@@ -229,9 +358,9 @@ impl StmtVisitor<String> for AstPrinter {
         value: &Option<Box<Expr>>,
     ) -> String {
         if let Some(expr) = value {
-            self.parenthesize_exprs("return", &vec![expr], true)
+            self.parenthesize_exprs("return", &[expr], true)
         } else {
-            self.parenthesize_exprs("return", &vec![], true)
+            self.parenthesize_exprs("return", &[], true)
         }
     }
 
@@ -243,19 +372,60 @@ impl StmtVisitor<String> for AstPrinter {
     ) -> String {
         let name = format!("var_stmt \"{}\"", name.lexeme);
         if let Some(expr) = initializer {
-            self.parenthesize_exprs(&name, &vec![expr], true)
+            self.parenthesize_exprs(&name, &[expr], true)
         } else {
-            self.parenthesize_exprs(&name, &vec![], true)
+            // distinguish `var a;` from `var a = nil;`
+            self.parenthesize_exprs(&format!("{} <uninitialized>", name), &[], true)
         }
     }
 
+    fn visit_var_list_stmt(&mut self, _stmt: &Stmt, declarations: &[Box<Stmt>]) -> String {
+        self.parenthesize_stmts("var_list", declarations, true)
+    }
+
     fn visit_while_stmt(
         &mut self,
         _stmt: &Stmt,
         condition: &Box<Expr>,
         body: &Box<Stmt>,
     ) -> String {
-        let name = self.parenthesize_exprs("while", &vec![condition], true);
-        self.parenthesize_stmts(&name, &vec![body.clone()], false)
+        let name = self.parenthesize_exprs("while", &[condition], true);
+        self.parenthesize_stmts(&name, std::slice::from_ref(body), false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::Parser;
+
+    fn generate(program: &str) -> String {
+        let mut scanner = Scanner::new(program);
+        let tokens = scanner.scan_tokens();
+        let mut parser = Parser::new(tokens);
+        let statements = parser.parse().unwrap();
+        AstPrinter::new().generate(&statements)
+    }
+
+    #[test]
+    fn prints_for_loops_as_for_nodes() {
+        let ast = generate("for (var i = 0; i < 3; i = i + 1) print i;");
+        assert!(ast.starts_with("((for (var_stmt \"i\" 0) (< (var_expr \"i\") 3) (assign \"i\" (+ (var_expr \"i\") 1)))"));
+        assert!(!ast.contains("while"));
+    }
+
+    #[test]
+    fn prints_uninitialized_variables_distinctly_from_nil() {
+        assert_eq!(generate("var a;"), "(var_stmt \"a\" <uninitialized>)\n");
+        assert_eq!(generate("var a = nil;"), "(var_stmt \"a\" Nil)\n");
+    }
+
+    #[test]
+    fn prints_else_if_chains_flat() {
+        let ast = generate("if (a) print 1; else if (b) print 2; else print 3;");
+        assert_eq!(
+            ast,
+            "((if (var_expr \"a\")) (print 1)\n  (elif (var_expr \"b\")) (print 2)\n  (else) (print 3)\n )\n"
+        );
     }
 }
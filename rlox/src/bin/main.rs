@@ -9,13 +9,58 @@ struct Options {
     #[structopt(short, long)]
     ast: bool,
 
+    ///Display scanned tokens instead of parsing
+    #[structopt(long)]
+    dump_tokens: bool,
+
+    ///Skip the resolver pass, for debugging it; locals and closures won't work as usual
+    #[structopt(long)]
+    no_resolve: bool,
+
+    ///Stop parsing after this many errors (default 20, 0 for no limit)
+    #[structopt(long)]
+    max_errors: Option<usize>,
+
+    ///Treat resolver warnings, such as shadowing or unread assignments, as errors
+    #[structopt(long)]
+    strict: bool,
+
+    ///Warn when a local variable shadows one in an enclosing scope
+    #[structopt(long)]
+    warn_on_shadowing: bool,
+
+    ///Make division by zero yield Infinity or NaN instead of a runtime error
+    #[structopt(long)]
+    ieee_division: bool,
+
+    ///Allow omitting the semicolon at the end of a line
+    #[structopt(long)]
+    optional_semicolons: bool,
+
+    ///Allow if, while and for conditions without parentheses when the body is a block
+    #[structopt(long)]
+    parenless_conditions: bool,
+
     /// Lox file to run, if none execute REPL
     file: Option<String>,
+
+    /// Arguments passed to the Lox script, available via args()
+    args: Vec<String>,
 }
 
 fn main() {
     let opt = Options::from_args();
-    let mut lox = Lox::new();
+    let mut lox = Lox::with_args(opt.args);
+    if let Some(max_errors) = opt.max_errors {
+        lox.set_max_errors(max_errors);
+    }
+    lox.set_strict(opt.strict);
+    lox.set_warn_on_shadowing(opt.warn_on_shadowing);
+    lox.set_ieee_division(opt.ieee_division);
+    lox.set_optional_semicolons(opt.optional_semicolons);
+    lox.set_allow_parenless_conditions(opt.parenless_conditions);
+    lox.set_dump_tokens(opt.dump_tokens);
+    lox.set_no_resolve(opt.no_resolve);
 
     if let Some(file) = opt.file {
         lox.run_file(&file, opt.ast);
@@ -1,10 +1,16 @@
 use std::fmt;
 
+use crate::function::LoxFunction;
 use crate::interpreter::{InterpretResult, Interpreter};
 use crate::object::LoxObject;
 
 pub trait LoxCallable {
     fn arity(&self) -> usize;
+    // callables accepting optional trailing arguments return the largest
+    // argument count they accept; arity() is the minimum.
+    fn max_arity(&self) -> usize {
+        self.arity()
+    }
     fn call(
         &self,
         interpreter: &mut Interpreter,
@@ -13,6 +19,11 @@ pub trait LoxCallable {
     // returns true if this is a property field, which is invoked
     // simply by evaluating it, no need for a call expression.
     fn is_property(&self) -> bool;
+    // returns true if calling this runs the same code with the same bindings as
+    // `function`, so a tail call from `function` to it may loop instead of recursing.
+    fn is_same_function(&self, _function: &LoxFunction) -> bool {
+        false
+    }
 }
 
 impl fmt::Debug for dyn LoxCallable {
@@ -5,7 +5,7 @@ use std::{cell::RefCell, rc::Rc};
 use crate::callable::LoxCallable;
 use crate::error::RuntimeError;
 use crate::function::LoxFunction;
-use crate::interpreter::{InterpretResult, Interpreter, Result};
+use crate::interpreter::{InterpretResult, InterpretResultStatus, Interpreter, Result};
 use crate::object::LoxObject;
 use crate::scanner::Token;
 
@@ -15,6 +15,7 @@ pub struct ClassData {
     class_fields: HashMap<String, LoxObject>,
     class_methods: HashMap<String, Rc<RefCell<LoxFunction>>>,
     super_class: Option<LoxClass>,
+    abstract_methods: Vec<String>,
 }
 
 impl ClassData {
@@ -27,11 +28,39 @@ impl ClassData {
             None
         }
     }
-    pub fn find_class_method(&self, name: &str) -> Option<Rc<RefCell<LoxFunction>>> {
-        if let Some(function) = self.class_methods.get(name) {
-            Some(function.clone())
+    fn abstract_method_names(&self) -> Vec<String> {
+        let mut names = self.abstract_methods.clone();
+        if let Some(super_class) = &self.super_class {
+            names.extend(super_class.class_data.borrow().abstract_method_names());
+        }
+        names
+    }
+
+    // Returns the names of abstract methods declared here or in a superclass which
+    // haven't been overridden by a concrete method.
+    fn unimplemented_abstract_methods(&self) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .abstract_method_names()
+            .into_iter()
+            .filter(|name| match self.find_method(name) {
+                Some(method) => method.borrow().is_abstract(),
+                None => false,
+            })
+            .collect();
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    // Looks up a class field or class method, searching superclasses if this
+    // class doesn't have one named `name`.
+    pub fn find_class_member(&self, name: &str) -> Option<LoxObject> {
+        if let Some(obj) = self.class_fields.get(name) {
+            Some(obj.clone())
+        } else if let Some(function) = self.class_methods.get(name) {
+            Some(LoxObject::Callable(function.clone()))
         } else if let Some(super_class) = &self.super_class {
-            super_class.class_data.borrow().find_class_method(name)
+            super_class.class_data.borrow().find_class_member(name)
         } else {
             None
         }
@@ -49,6 +78,11 @@ impl LoxClass {
         methods: HashMap<String, Rc<RefCell<LoxFunction>>>,
         class_methods: HashMap<String, Rc<RefCell<LoxFunction>>>,
     ) -> Self {
+        let abstract_methods = methods
+            .iter()
+            .filter(|(_, method)| method.borrow().is_abstract())
+            .map(|(name, _)| name.clone())
+            .collect();
         LoxClass {
             class_data: Rc::new(RefCell::new(ClassData {
                 name: String::from(name),
@@ -56,15 +90,14 @@ impl LoxClass {
                 class_fields: HashMap::new(),
                 class_methods,
                 super_class: super_class,
+                abstract_methods,
             })),
         }
     }
 
     pub fn get(&self, name: &Token) -> Result<LoxObject> {
-        if let Some(obj) = self.class_data.borrow().class_fields.get(&name.lexeme) {
-            Ok(obj.clone())
-        } else if let Some(method) = self.class_data.borrow().find_class_method(&name.lexeme) {
-            Ok(LoxObject::Callable(method))
+        if let Some(obj) = self.class_data.borrow().find_class_member(&name.lexeme) {
+            Ok(obj)
         } else {
             Err(RuntimeError::new(
                 name,
@@ -83,6 +116,11 @@ impl LoxClass {
     pub fn find_method(&self, name: &str) -> Option<Rc<RefCell<LoxFunction>>> {
         self.class_data.borrow().find_method(name)
     }
+
+    /// Returns true if `self` and `other` are handles to the same class.
+    pub fn ptr_eq(&self, other: &LoxClass) -> bool {
+        Rc::ptr_eq(&self.class_data, &other.class_data)
+    }
 }
 
 impl Clone for LoxClass {
@@ -125,6 +163,17 @@ impl LoxCallable for LoxClass {
         interpreter: &mut Interpreter,
         arguments: &Vec<LoxObject>,
     ) -> InterpretResult<Option<LoxObject>> {
+        let unimplemented = self.class_data.borrow().unimplemented_abstract_methods();
+        if !unimplemented.is_empty() {
+            return Err(InterpretResultStatus::Error(RuntimeError::with_message(
+                &format!(
+                    "Cannot instantiate abstract class \"{}\"; unimplemented abstract methods: {}.",
+                    self.class_data.borrow().name,
+                    unimplemented.join(", ")
+                ),
+            )));
+        }
+
         let instance = LoxInstance::new(self.class_data.clone());
         if let Some(initializer) = self.class_data.borrow().find_method("init") {
             let bound = initializer.borrow().bind(&instance);
@@ -140,6 +189,19 @@ impl LoxCallable for LoxClass {
 
 // --------------------------------------------------------------------------------------------------------------------
 
+/// Remembers the method a property access site resolved to, so later accesses
+/// on instances of the same class can skip walking the method tables.
+pub struct CachedMethod {
+    class_data: Rc<RefCell<ClassData>>,
+    name: String,
+    method: Rc<RefCell<LoxFunction>>,
+}
+
+/// Inline method caches, keyed by the id of the accessed property's name token.
+pub type MethodCache = HashMap<usize, CachedMethod>;
+
+// --------------------------------------------------------------------------------------------------------------------
+
 pub struct LoxInstance {
     class_data: Rc<RefCell<ClassData>>,
     fields: Rc<RefCell<HashMap<String, LoxObject>>>,
@@ -153,10 +215,20 @@ impl LoxInstance {
         }
     }
 
-    pub fn get(&self, name: &Token) -> Result<LoxObject> {
+    /// Looks up the field or method `name`. If `cache` is provided, methods are
+    /// looked up through it and it is updated on a miss.
+    pub fn get(&self, name: &Token, cache: Option<&mut MethodCache>) -> Result<LoxObject> {
         if let Some(obj) = self.fields.borrow().get(&name.lexeme) {
-            Ok(obj.clone())
-        } else if let Some(method) = self.class_data.borrow().find_method(&name.lexeme) {
+            return Ok(obj.clone());
+        }
+
+        let method = if let Some(cache) = cache {
+            self.find_cached_method(name, cache)
+        } else {
+            self.class_data.borrow().find_method(&name.lexeme)
+        };
+
+        if let Some(method) = method {
             Ok(LoxObject::Callable(Rc::new(RefCell::new(
                 method.borrow().bind(self),
             ))))
@@ -168,11 +240,73 @@ impl LoxInstance {
         }
     }
 
+    fn find_cached_method(
+        &self,
+        name: &Token,
+        cache: &mut MethodCache,
+    ) -> Option<Rc<RefCell<LoxFunction>>> {
+        if let Some(entry) = cache.get(&name.id) {
+            // token ids restart with each scan, so the name has to match too
+            if Rc::ptr_eq(&entry.class_data, &self.class_data) && entry.name == name.lexeme {
+                return Some(entry.method.clone());
+            }
+        }
+
+        let method = self.class_data.borrow().find_method(&name.lexeme)?;
+        cache.insert(
+            name.id,
+            CachedMethod {
+                class_data: self.class_data.clone(),
+                name: name.lexeme.clone(),
+                method: method.clone(),
+            },
+        );
+        Some(method)
+    }
+
     pub fn set(&self, name: &Token, value: &LoxObject) {
         self.fields
             .borrow_mut()
             .insert(name.lexeme.to_owned(), value.clone());
     }
+
+    /// Returns a snapshot of this instance's fields.
+    pub fn fields(&self) -> HashMap<String, LoxObject> {
+        self.fields.borrow().clone()
+    }
+
+    /// Returns a new instance of the same class holding `fields`.
+    pub fn with_fields(&self, fields: HashMap<String, LoxObject>) -> LoxInstance {
+        LoxInstance {
+            class_data: self.class_data.clone(),
+            fields: Rc::new(RefCell::new(fields)),
+        }
+    }
+
+    /// Returns true if `self` and `other` are handles to the same instance.
+    pub fn ptr_eq(&self, other: &LoxInstance) -> bool {
+        Rc::ptr_eq(&self.fields, &other.fields)
+    }
+
+    /// Returns true if `self` and `other` are instances of exactly the same class.
+    pub fn has_same_class(&self, other: &LoxInstance) -> bool {
+        Rc::ptr_eq(&self.class_data, &other.class_data)
+    }
+
+    /// Returns true if this instance's class is `class`, or inherits from it.
+    pub fn is_instance_of(&self, class: &LoxClass) -> bool {
+        let mut class_data = self.class_data.clone();
+        loop {
+            if Rc::ptr_eq(&class_data, &class.class_data) {
+                return true;
+            }
+            let super_class = match &class_data.borrow().super_class {
+                Some(super_class) => super_class.class_data.clone(),
+                None => return false,
+            };
+            class_data = super_class;
+        }
+    }
 }
 
 impl Clone for LoxInstance {
@@ -29,6 +29,11 @@ impl Environment {
         })))
     }
 
+    /// Returns true if `other` refers to this same environment, rather than an equal one.
+    pub fn is_same(&self, other: &Environment) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+
     pub fn enclosing(&self) -> Option<Environment> {
         self.0.borrow().enclosing.clone()
     }
@@ -40,6 +45,26 @@ impl Environment {
             .insert(name.to_owned(), value.clone());
     }
 
+    /// Removes every value defined directly in this environment; enclosing
+    /// environments are untouched.
+    pub fn clear(&mut self) {
+        self.0.borrow_mut().values.clear();
+    }
+
+    /// Returns the names and values defined directly in this environment, sorted
+    /// by name; enclosing environments are not included.
+    pub fn values(&self) -> Vec<(String, LoxObject)> {
+        let mut values: Vec<(String, LoxObject)> = self
+            .0
+            .borrow()
+            .values
+            .iter()
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect();
+        values.sort_by(|a, b| a.0.cmp(&b.0));
+        values
+    }
+
     pub fn assign(&mut self, name: &Token, value: &LoxObject) -> Result<()> {
         if self.0.borrow().values.contains_key(&name.lexeme) {
             self.0
@@ -177,4 +202,29 @@ mod tests {
 
         assert_eq!(env.get(&name).unwrap(), LoxObject::Boolean(false));
     }
+
+    #[test]
+    fn clear_removes_values() {
+        let mut env = Environment::new();
+        let name = Token::new(TokenType::Identifier, String::from("a"), None, 1, 0);
+        env.define(&name.lexeme, &LoxObject::Number(10.0));
+        env.clear();
+        assert!(env.get(&name).is_err());
+    }
+
+    #[test]
+    fn values_are_sorted_and_exclude_enclosing() {
+        let mut parent = Environment::new();
+        parent.define("z", &LoxObject::Nil);
+        let mut env = Environment::as_child_of(parent);
+        env.define("b", &LoxObject::Number(2.0));
+        env.define("a", &LoxObject::Number(1.0));
+        assert_eq!(
+            env.values(),
+            vec![
+                (String::from("a"), LoxObject::Number(1.0)),
+                (String::from("b"), LoxObject::Number(2.0)),
+            ]
+        );
+    }
 }
@@ -4,6 +4,23 @@ use crate::scanner::Token;
 
 // --------------------------------------------------------------------------------------------------------------------
 
+#[derive(Debug, Clone)]
+pub struct ScanError {
+    pub line: i32,
+    pub message: String,
+}
+
+impl ScanError {
+    pub fn new(line: i32, message: &str) -> Self {
+        Self {
+            line,
+            message: message.to_owned(),
+        }
+    }
+}
+
+// --------------------------------------------------------------------------------------------------------------------
+
 #[derive(Debug, Clone)]
 pub struct ParseError {
     pub token: Token,
@@ -54,10 +71,29 @@ impl fmt::Display for ResolveError {
 
 // --------------------------------------------------------------------------------------------------------------------
 
+/// A call in progress when a runtime error occurred: the name of the function
+/// called, and the line it was called from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StackFrame {
+    pub function: String,
+    pub line: i32,
+}
+
+impl StackFrame {
+    pub fn new(function: &str, line: i32) -> Self {
+        Self {
+            function: function.to_owned(),
+            line,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct RuntimeError {
     pub token: Option<Token>,
     pub message: String,
+    /// The calls in progress when the error occurred, innermost first.
+    pub backtrace: Vec<StackFrame>,
 }
 
 impl RuntimeError {
@@ -65,12 +101,14 @@ impl RuntimeError {
         Self {
             token: Some(token.to_owned()),
             message: message.to_owned(),
+            backtrace: vec![],
         }
     }
     pub fn with_message(message: &str) -> Self {
         Self {
             token: None,
             message: message.to_owned(),
+            backtrace: vec![],
         }
     }
 }
@@ -87,6 +125,61 @@ impl fmt::Display for RuntimeError {
 
 // --------------------------------------------------------------------------------------------------------------------
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// A problem found in Lox source, for hosts which present errors themselves
+/// rather than have them printed. `line` is 0 if the location is unknown, and
+/// `column` is None since tokens don't record their column.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub line: i32,
+    pub column: Option<i32>,
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl Diagnostic {
+    fn new(token: Option<&Token>, severity: Severity, message: &str) -> Self {
+        Diagnostic {
+            line: token.map_or(0, |t| t.line),
+            column: None,
+            severity,
+            message: message.to_owned(),
+        }
+    }
+
+    pub fn resolver_error(e: &ResolveError) -> Self {
+        Diagnostic::new(e.token.as_ref(), Severity::Error, &e.message)
+    }
+
+    pub fn resolver_warning(e: &ResolveError) -> Self {
+        Diagnostic::new(e.token.as_ref(), Severity::Warning, &e.message)
+    }
+}
+
+impl From<&ScanError> for Diagnostic {
+    fn from(e: &ScanError) -> Self {
+        Diagnostic {
+            line: e.line,
+            column: None,
+            severity: Severity::Error,
+            message: e.message.clone(),
+        }
+    }
+}
+
+impl From<&ParseError> for Diagnostic {
+    fn from(e: &ParseError) -> Self {
+        Diagnostic::new(Some(&e.token), Severity::Error, &e.message)
+    }
+}
+
+// --------------------------------------------------------------------------------------------------------------------
+
 pub mod report {
     use super::*;
     use crate::scanner::{Token, TokenType};
@@ -95,11 +188,10 @@ pub mod report {
         eprintln!("[line {}] Error {}: {}", line, context, message);
     }
 
-    pub fn parse_error_at_line(line: i32, message: &str) {
-        error(line, "", message);
+    pub fn scan_error(e: &ScanError) {
+        error(e.line, "", &e.message);
     }
 
-    #[allow(dead_code)]
     pub fn parse_error_at_token(token: &Token, message: &str) {
         match token.token_type {
             TokenType::Eof => error(token.line, " at end", message),
@@ -119,11 +211,22 @@ pub mod report {
         }
     }
 
+    pub fn resolver_warning(e: &ResolveError) {
+        if let Some(token) = &e.token {
+            eprintln!("[line {}] Warning: {}", token.line, e.message,);
+        } else {
+            eprintln!("Warning: {}", e.message);
+        }
+    }
+
     pub fn runtime_error(e: &RuntimeError) {
         if let Some(token) = &e.token {
             eprintln!("{}\n[line {}]", e.message, token.line);
         } else {
             eprintln!("{}", e.message);
         }
+        for frame in &e.backtrace {
+            eprintln!("[line {}] in {}()", frame.line, frame.function);
+        }
     }
 }
@@ -1,9 +1,11 @@
 use std::fmt;
+use std::rc::Rc;
 
 use crate::ast::{CallableType, Stmt};
 use crate::callable::LoxCallable;
 use crate::class::LoxInstance;
 use crate::environment::Environment;
+use crate::error::RuntimeError;
 use crate::interpreter::{InterpretResult, InterpretResultStatus, Interpreter};
 use crate::object::LoxObject;
 use crate::scanner::Token;
@@ -11,7 +13,7 @@ use crate::scanner::Token;
 pub struct LoxFunction {
     name: Option<Token>,
     parameters: Vec<Token>,
-    body: Vec<Box<Stmt>>,
+    body: Rc<Vec<Box<Stmt>>>, // shared with bound copies, identifying the declaration
     closure: Environment,
     is_initializer: bool,
     fn_type: CallableType,
@@ -29,7 +31,7 @@ impl LoxFunction {
         LoxFunction {
             name: Some(name.clone()),
             parameters: parameters.clone(),
-            body: body.clone(),
+            body: Rc::new(body.clone()),
             closure,
             is_initializer,
             fn_type,
@@ -44,57 +46,61 @@ impl LoxFunction {
         LoxFunction {
             name: None,
             parameters: parameters.clone(),
-            body: body.clone(),
+            body: Rc::new(body.clone()),
             closure: closure,
             is_initializer: false,
             fn_type: CallableType::Lambda,
         }
     }
 
+    pub fn is_abstract(&self) -> bool {
+        self.fn_type == CallableType::Abstract
+    }
+
     pub fn bind(&self, instance: &LoxInstance) -> LoxFunction {
         let mut environment = Environment::as_child_of(self.closure.clone());
         environment.define("this", &LoxObject::Instance(instance.clone()));
-        if let Some(name) = &self.name {
-            LoxFunction::new_function(
-                &name,
-                &self.parameters,
-                &self.body,
-                environment,
-                self.is_initializer,
-                self.fn_type,
-            )
+        if self.name.is_some() {
+            LoxFunction {
+                name: self.name.clone(),
+                parameters: self.parameters.clone(),
+                body: self.body.clone(),
+                closure: environment,
+                is_initializer: self.is_initializer,
+                fn_type: self.fn_type,
+            }
         } else {
             panic!("Attempted to call bind() on a lambda.");
         }
     }
-}
 
-impl fmt::Display for LoxFunction {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        if let Some(name) = &self.name {
-            write!(f, "{}", name)
-        } else {
-            write!(f, "lambda")
+    // Returns true if this is a method bound to the same instance as `function`.
+    fn is_bound_like(&self, function: &LoxFunction) -> bool {
+        match (self.closure.enclosing(), function.closure.enclosing()) {
+            (Some(a), Some(b)) if a.is_same(&b) => {
+                match (
+                    self.closure.get_at(0, "this"),
+                    function.closure.get_at(0, "this"),
+                ) {
+                    (Ok(a), Ok(b)) => a.is_identical(&b),
+                    _ => false,
+                }
+            }
+            _ => false,
         }
     }
-}
-
-impl LoxCallable for LoxFunction {
-    fn arity(&self) -> usize {
-        return self.parameters.len();
-    }
 
-    fn call(
-        &self,
-        interpreter: &mut Interpreter,
-        arguments: &Vec<LoxObject>,
-    ) -> InterpretResult<Option<LoxObject>> {
-        let mut env = Environment::as_child_of(self.closure.clone());
-        for i in 0..self.parameters.len() {
-            env.define(&self.parameters[i].lexeme, &arguments[i]);
+    // Returns true if `callee` is this function, so a tail call to it can loop.
+    fn is_tail_call_target(&self, callee: &LoxObject, arguments: &[LoxObject]) -> bool {
+        match callee {
+            LoxObject::Callable(callable) => {
+                arguments.len() == self.arity() && callable.borrow().is_same_function(self)
+            }
+            _ => false,
         }
+    }
 
-        let ret = interpreter.execute_block(&self.body, env);
+    fn finish_call(&self, ret: InterpretResult<()>) -> InterpretResult<Option<LoxObject>> {
         match ret {
             // if function doesn't explicitly call return, we return None for it, unless
             // it's a class instance initializer in which case we return 'this'
@@ -123,6 +129,62 @@ impl LoxCallable for LoxFunction {
             },
         }
     }
+}
+
+impl fmt::Display for LoxFunction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(name) = &self.name {
+            write!(f, "{}", name)
+        } else {
+            write!(f, "lambda")
+        }
+    }
+}
+
+impl LoxCallable for LoxFunction {
+    fn arity(&self) -> usize {
+        return self.parameters.len();
+    }
+
+    fn call(
+        &self,
+        interpreter: &mut Interpreter,
+        arguments: &Vec<LoxObject>,
+    ) -> InterpretResult<Option<LoxObject>> {
+        if self.is_abstract() {
+            if let Some(name) = &self.name {
+                return Err(InterpretResultStatus::Error(RuntimeError::new(
+                    name,
+                    &format!("Cannot call abstract method \"{}\".", name.lexeme),
+                )));
+            }
+        }
+
+        // a `return f(...)` where f is this function reruns the body with the
+        // new arguments, rather than recursing and growing the stack
+        let mut tail_arguments: Option<Vec<LoxObject>> = None;
+        loop {
+            let arguments = tail_arguments.as_ref().unwrap_or(arguments);
+            let mut env = Environment::as_child_of(self.closure.clone());
+            for (parameter, argument) in self.parameters.iter().zip(arguments) {
+                env.define(&parameter.lexeme, argument);
+            }
+
+            match interpreter.execute_block(&self.body, env) {
+                Err(InterpretResultStatus::TailCall(call)) => {
+                    let call = *call;
+                    if self.is_tail_call_target(&call.callee, &call.args) {
+                        tail_arguments = Some(call.args);
+                    } else {
+                        return interpreter
+                            .call_in_frame(&call.function, &call.callee, call.args, &call.paren)
+                            .map(Some);
+                    }
+                }
+                ret => return self.finish_call(ret),
+            }
+        }
+    }
 
     fn is_property(&self) -> bool {
         match self.fn_type {
@@ -130,4 +192,9 @@ impl LoxCallable for LoxFunction {
             _ => false,
         }
     }
+
+    fn is_same_function(&self, function: &LoxFunction) -> bool {
+        Rc::ptr_eq(&self.body, &function.body)
+            && (self.closure.is_same(&function.closure) || self.is_bound_like(function))
+    }
 }
@@ -1,21 +1,30 @@
 use std::collections::HashMap;
+use std::io::{self, BufRead, Write};
 use std::{cell::RefCell, rc::Rc};
 
 use crate::ast::*;
 use crate::callable::LoxCallable;
-use crate::class::LoxClass;
+use crate::class::{LoxClass, MethodCache};
 use crate::environment::Environment;
 use crate::error;
 use crate::function::LoxFunction;
 use crate::natives;
-use crate::object::LoxObject;
-use crate::scanner::{Token, TokenType};
+use crate::object::{format_number, LoxObject};
+use crate::parser::Parser;
+use crate::resolver::Resolver;
+use crate::scanner::{Scanner, Token, TokenType};
 
 //-----------------------------------------------------------------------------
 
-use error::RuntimeError;
+use error::{RuntimeError, StackFrame};
 pub type Result<T> = std::result::Result<T, RuntimeError>;
 
+/// Lox source run by every interpreter before user code, defining globals such as Stopwatch.
+const PRELUDE: &str = include_str!("prelude.lox");
+
+/// Called with a variable's name and new value whenever one is defined or assigned.
+pub type AssignObserver = Box<dyn FnMut(&str, &LoxObject)>;
+
 pub enum InterpretResultStatus {
     // Returned when a runtime error occurs
     Error(RuntimeError),
@@ -25,6 +34,20 @@ pub enum InterpretResultStatus {
 
     // Return statement in a function, carrying optional return value payload.
     Return(Option<LoxObject>),
+
+    // Return statement whose value is a call. The returning function makes the call,
+    // looping rather than recursing when the callee is itself.
+    TailCall(Box<TailCall>),
+}
+
+/// A call made by a return statement, evaluated but not yet made.
+pub struct TailCall {
+    /// The name of the function called, for backtraces.
+    pub function: String,
+    pub callee: LoxObject,
+    pub args: Vec<LoxObject>,
+    /// The call's closing paren, used to report errors.
+    pub paren: Token,
 }
 
 impl std::convert::From<error::RuntimeError> for InterpretResultStatus {
@@ -35,35 +58,284 @@ impl std::convert::From<error::RuntimeError> for InterpretResultStatus {
 
 pub type InterpretResult<T> = std::result::Result<T, InterpretResultStatus>;
 
+/// Compares `left` and `right` as `==` does; both must be numbers or both strings.
+/// Otherwise returns the message of the error to report.
+pub fn equals(left: &LoxObject, right: &LoxObject) -> std::result::Result<bool, &'static str> {
+    match (left, right) {
+        (LoxObject::Number(l), LoxObject::Number(r)) => Ok(l == r),
+        (LoxObject::Number(_), _) => Err("Right operand not a number."),
+        (LoxObject::Str(l), LoxObject::Str(r)) => Ok(l == r),
+        (LoxObject::Str(_), _) => Err("Right operand not a string"),
+        _ => Err("Left operand not a number or string."),
+    }
+}
+
 //-----------------------------------------------------------------------------
 
+// Orders a block's statements for execution, with function declarations first: the
+// resolver hoists them, letting them be called before their declarations are reached.
+fn in_hoisted_order(statements: &[Box<Stmt>]) -> impl Iterator<Item = &Box<Stmt>> {
+    let is_function = |statement: &Stmt| matches!(statement, Stmt::Function { .. });
+    let functions = statements.iter().filter(move |s| is_function(s));
+    functions.chain(statements.iter().filter(move |s| !is_function(s)))
+}
+
+fn define_native<T: LoxCallable + 'static>(globals: &mut Environment, name: &str, native: T) {
+    globals.define(name, &LoxObject::Callable(Rc::new(RefCell::new(native))));
+}
+
 pub struct Interpreter {
     globals: Environment,
     environment: Environment,
     locals: HashMap<Expr, usize>,
+    input: Box<dyn BufRead>,
+    output: Box<dyn Write>,
+    method_cache: Option<MethodCache>,
+    args: Vec<String>,
+    step_limit: Option<usize>,
+    steps: usize,
+    call_stack: Vec<StackFrame>,
+    assign_observer: Option<AssignObserver>,
+    ieee_division: bool,
 }
 
 impl Interpreter {
     pub fn new() -> Self {
         let mut globals = Environment::new();
-        globals.define(
-            "clock",
-            &LoxObject::Callable(Rc::new(RefCell::new(natives::NativeClock::new()))),
-        );
+        Interpreter::define_natives(&mut globals);
 
-        Interpreter {
+        let mut interpreter = Interpreter {
             globals: globals.clone(),
             environment: globals,
             locals: HashMap::new(),
+            input: Box::new(io::BufReader::new(io::stdin())),
+            output: Box::new(io::stdout()),
+            method_cache: Some(MethodCache::new()),
+            args: vec![],
+            step_limit: None,
+            steps: 0,
+            call_stack: vec![],
+            assign_observer: None,
+            ieee_division: false,
+        };
+        interpreter.run_prelude();
+        interpreter
+    }
+
+    // Defines the globals declared in Lox by the prelude.
+    fn run_prelude(&mut self) {
+        let mut scanner = Scanner::new(PRELUDE);
+        let tokens = scanner.scan_tokens();
+        let mut parser = Parser::new(tokens);
+        let statements = parser.parse().expect("Prelude should parse");
+        Resolver::new(self)
+            .resolve(&statements)
+            .expect("Prelude should resolve");
+
+        // the prelude doesn't count toward the step limit
+        let step_limit = self.step_limit.take();
+        self.interpret(&statements)
+            .expect("Prelude should run without error");
+        self.step_limit = step_limit;
+    }
+
+    #[cfg(test)]
+    fn disable_method_cache(&mut self) {
+        self.method_cache = None;
+    }
+
+    fn define_natives(globals: &mut Environment) {
+        define_native(globals, "clock", natives::NativeClock::new());
+        define_native(globals, "clock_diff", natives::NativeClockDiff::new());
+        define_native(globals, "abs_time", natives::NativeAbsTime::new());
+        define_native(globals, "abs", natives::NativeAbs::new());
+        define_native(globals, "abs_diff", natives::NativeAbsDiff::new());
+        define_native(globals, "identical", natives::NativeIdentical::new());
+        define_native(globals, "clock_iso", natives::NativeClockIso::new());
+        define_native(globals, "keys", natives::NativeKeys::new());
+        define_native(globals, "values", natives::NativeValues::new());
+        define_native(globals, "entries", natives::NativeEntries::new());
+        define_native(globals, "set_new", natives::NativeSetNew::new());
+        define_native(globals, "set_add", natives::NativeSetAdd::new());
+        define_native(globals, "set_has", natives::NativeSetHas::new());
+        define_native(globals, "set_remove", natives::NativeSetRemove::new());
+        define_native(globals, "range", natives::NativeRange::new());
+        define_native(globals, "deep_copy", natives::NativeDeepCopy::new());
+        define_native(globals, "apply", natives::NativeApply::new());
+        define_native(globals, "floor_div", natives::NativeFloorDiv::new());
+        define_native(globals, "floor_mod", natives::NativeFloorMod::new());
+        define_native(globals, "read_all", natives::NativeReadAll::new());
+        define_native(globals, "to_radix", natives::NativeToRadix::new());
+        define_native(globals, "env", natives::NativeEnv::new());
+        define_native(globals, "args", natives::NativeArgs::new());
+        define_native(globals, "is_nil", natives::NativeIsNil::new());
+        define_native(globals, "is_number", natives::NativeIsNumber::new());
+        define_native(globals, "is_string", natives::NativeIsString::new());
+        define_native(globals, "is_bool", natives::NativeIsBool::new());
+        define_native(
+            globals,
+            "json_stringify",
+            natives::NativeJsonStringify::new(),
+        );
+        define_native(globals, "json_parse", natives::NativeJsonParse::new());
+        define_native(globals, "hash", natives::NativeHash::new());
+        define_native(globals, "clamp", natives::NativeClamp::new());
+        define_native(globals, "assert_eq", natives::NativeAssertEq::new());
+        define_native(globals, "to_list", natives::NativeToList::new());
+        define_native(
+            globals,
+            "callable_arity",
+            natives::NativeCallableArity::new(),
+        );
+        define_native(globals, "now_nanos", natives::NativeNowNanos::new());
+        define_native(globals, "max_list", natives::NativeMaxList::new());
+        define_native(globals, "min_list", natives::NativeMinList::new());
+        define_native(globals, "repeat", natives::NativeRepeat::new());
+        define_native(globals, "codepoints", natives::NativeCodepoints::new());
+        define_native(globals, "bool", natives::NativeBool::new());
+        define_native(globals, "floor_to", natives::NativeFloorTo::new());
+        define_native(globals, "round_to", natives::NativeRoundTo::new());
+        define_native(globals, "list_push", natives::NativeListPush::new());
+        define_native(globals, "list_pop", natives::NativeListPop::new());
+        define_native(globals, "list_concat", natives::NativeListConcat::new());
+        define_native(globals, "assert_throws", natives::NativeAssertThrows::new());
+        define_native(globals, "sum", natives::NativeSum::new());
+        define_native(globals, "char_at", natives::NativeCharAt::new());
+        define_native(globals, "panic", natives::NativePanic::new());
+        define_native(globals, "zip", natives::NativeZip::new());
+        define_native(globals, "str_reverse", natives::NativeStrReverse::new());
+        define_native(globals, "chars", natives::NativeChars::new());
+        define_native(globals, "min_by", natives::NativeMinBy::new());
+        define_native(globals, "max_by", natives::NativeMaxBy::new());
+        define_native(globals, "sign", natives::NativeSign::new());
+        define_native(globals, "trunc", natives::NativeTrunc::new());
+        define_native(globals, "int", natives::NativeInt::new());
+        define_native(globals, "number_format", natives::NativeNumberFormat::new());
+        define_native(globals, "pad_left", natives::NativePadLeft::new());
+        define_native(globals, "pad_right", natives::NativePadRight::new());
+        define_native(globals, "count", natives::NativeCount::new());
+        define_native(globals, "index_of_list", natives::NativeIndexOfList::new());
+        define_native(globals, "flatten", natives::NativeFlatten::new());
+        define_native(globals, "group_by", natives::NativeGroupBy::new());
+        define_native(globals, "slice", natives::NativeSlice::new());
+        define_native(globals, "enumerate", natives::NativeEnumerate::new());
+        define_native(globals, "reverse", natives::NativeReverse::new());
+        define_native(globals, "sorted", natives::NativeSorted::new());
+        define_native(globals, "merge", natives::NativeMerge::new());
+        define_native(
+            globals,
+            "string_join_map",
+            natives::NativeStringJoinMap::new(),
+        );
+        define_native(globals, "try_call", natives::NativeTryCall::new());
+        define_native(globals, "deep_equals", natives::NativeDeepEquals::new());
+        define_native(
+            globals,
+            "is_instance_of",
+            natives::NativeIsInstanceOf::new(),
+        );
+    }
+
+    /// Forgets all global definitions, leaving only the natives and the prelude. Used
+    /// by the REPL to start over without restarting.
+    pub fn reset_globals(&mut self) {
+        self.globals.clear();
+        Interpreter::define_natives(&mut self.globals);
+        self.environment = self.globals.clone();
+        self.locals.clear();
+        if let Some(method_cache) = &mut self.method_cache {
+            method_cache.clear();
+        }
+        self.run_prelude();
+    }
+
+    /// Bounds the number of statements and expressions executed by each call to
+    /// interpret or evaluate; exceeding the limit is a runtime error. Used to
+    /// sandbox untrusted code.
+    pub fn set_step_limit(&mut self, step_limit: Option<usize>) {
+        self.step_limit = step_limit;
+        self.steps = 0;
+    }
+
+    fn step(&mut self) -> Result<()> {
+        self.steps += 1;
+        match self.step_limit {
+            Some(limit) if self.steps > limit => {
+                Err(RuntimeError::with_message("Execution step limit exceeded."))
+            }
+            _ => Ok(()),
         }
     }
 
+    /// When set, dividing by zero follows IEEE 754, yielding Infinity, -Infinity
+    /// or NaN, rather than being a runtime error.
+    pub fn set_ieee_division(&mut self, ieee_division: bool) {
+        self.ieee_division = ieee_division;
+    }
+
+    /// Sets the command line arguments returned by the args() native.
+    pub fn set_args(&mut self, args: Vec<String>) {
+        self.args = args;
+    }
+
+    pub fn args(&self) -> &Vec<String> {
+        &self.args
+    }
+
+    /// Replaces the reader natives consume input from, which defaults to stdin.
+    #[allow(dead_code)]
+    pub fn set_input(&mut self, input: Box<dyn BufRead>) {
+        self.input = input;
+    }
+
+    pub fn input(&mut self) -> &mut dyn BufRead {
+        self.input.as_mut()
+    }
+
+    /// Sets a callback invoked whenever a variable is defined by a var statement or
+    /// assigned, e.g. to implement a debugger's watch window.
+    pub fn set_assign_observer(&mut self, observer: Option<AssignObserver>) {
+        self.assign_observer = observer;
+    }
+
+    /// Removes and returns the callback set by `set_assign_observer`.
+    pub fn take_assign_observer(&mut self) -> Option<AssignObserver> {
+        self.assign_observer.take()
+    }
+
+    fn observe_assignment(&mut self, name: &Token, value: &LoxObject) {
+        if let Some(observer) = &mut self.assign_observer {
+            // observers see an uninitialized variable as nil, as Lox code does
+            match value {
+                LoxObject::Undefined => observer(&name.lexeme, &LoxObject::Nil),
+                _ => observer(&name.lexeme, value),
+            }
+        }
+    }
+
+    /// Replaces the writer print and write statements output to, which defaults to stdout.
+    #[allow(dead_code)]
+    pub fn set_output(&mut self, output: Box<dyn Write>) {
+        self.output = output;
+    }
+
     #[allow(dead_code)]
     pub fn environment(&self) -> Environment {
         self.environment.clone()
     }
 
+    pub fn globals(&self) -> Environment {
+        self.globals.clone()
+    }
+
     pub fn interpret(&mut self, statements: &Vec<Box<Stmt>>) -> Result<()> {
+        // cache entries are keyed by token ids, which restart with each scan, so
+        // entries from earlier runs are unlikely to hit and would only keep their
+        // classes alive
+        if let Some(method_cache) = &mut self.method_cache {
+            method_cache.clear();
+        }
+        self.steps = 0;
         for statement in statements {
             if let Err(e) = self.execute(statement) {
                 return Err(self._process_error(e));
@@ -73,6 +345,7 @@ impl Interpreter {
     }
 
     pub fn evaluate(&mut self, expr: &Box<Expr>) -> Result<LoxObject> {
+        self.steps = 0;
         match self._evaluate(expr) {
             Ok(result) => Ok(result),
             Err(e) => Err(self._process_error(e)),
@@ -88,30 +361,32 @@ impl Interpreter {
                 // we're in big trouble
                 return RuntimeError::with_message("A \"break\" statement trickled all the way up to root. Something is horribly wrong.");
             }
-            InterpretResultStatus::Return(_) => {
+            InterpretResultStatus::Return(_) | InterpretResultStatus::TailCall(..) => {
                 // we're in big trouble
                 return RuntimeError::with_message("A \"return\" statement trickled all the way up to root. Something is horribly wrong.");
             }
         }
     }
 
-    fn _evaluate(&mut self, expr: &Box<Expr>) -> InterpretResult<LoxObject> {
+    fn _evaluate(&mut self, expr: &Expr) -> InterpretResult<LoxObject> {
+        self.step()?;
         expr.accept(self)
     }
 
-    fn execute(&mut self, stmt: &Box<Stmt>) -> InterpretResult<()> {
+    fn execute(&mut self, stmt: &Stmt) -> InterpretResult<()> {
+        self.step()?;
         stmt.accept(self)
     }
 
     pub fn execute_block(
         &mut self,
-        statements: &Vec<Box<Stmt>>,
+        statements: &[Box<Stmt>],
         env: Environment,
     ) -> InterpretResult<()> {
         let previous_env = self.environment.clone();
         self.environment = env;
 
-        for statement in statements {
+        for statement in in_hoisted_order(statements) {
             if let Err(e) = self.execute(statement) {
                 // had an error, restore parent env and bail
                 self.environment = previous_env;
@@ -124,6 +399,260 @@ impl Interpreter {
         Ok(())
     }
 
+    fn execute_for_loop(
+        &mut self,
+        initializer: &Option<Box<Stmt>>,
+        condition: &Option<Box<Expr>>,
+        increment: &Option<Box<Expr>>,
+        body: &Stmt,
+    ) -> InterpretResult<()> {
+        if let Some(initializer) = initializer {
+            self.execute(initializer)?;
+        }
+
+        let loop_variables: Vec<&Token> = match initializer.as_deref() {
+            Some(Stmt::Var { name, .. }) => vec![name],
+            Some(Stmt::VarList { declarations }) => declarations
+                .iter()
+                .filter_map(|declaration| match &**declaration {
+                    Stmt::Var { name, .. } => Some(name),
+                    _ => None,
+                })
+                .collect(),
+            _ => vec![],
+        };
+
+        loop {
+            if let Some(condition) = condition {
+                if !self._evaluate(condition)?.is_truthy() {
+                    break;
+                }
+            }
+
+            match self.execute(body) {
+                Ok(_) => (),
+                Err(status) => match status {
+                    InterpretResultStatus::Break => {
+                        // time to break from loop.
+                        break;
+                    }
+                    _ => return Err(status),
+                },
+            }
+
+            // Rebind the loop variables in a fresh environment for the next iteration,
+            // so closures created by the body keep the values from their own iteration.
+            if !loop_variables.is_empty() {
+                if let Some(enclosing) = self.environment.enclosing() {
+                    let mut env = Environment::as_child_of(enclosing);
+                    for name in &loop_variables {
+                        let value = self.environment.get_at(0, &name.lexeme)?;
+                        env.define(&name.lexeme, &value);
+                    }
+                    self.environment = env;
+                }
+            }
+
+            if let Some(increment) = increment {
+                self._evaluate(increment)?;
+            }
+        }
+        Ok(())
+    }
+
+    // Looks up the property `name` on `object`, invoking it if it's a property accessor.
+    fn get_property(&mut self, object: LoxObject, name: &Token) -> InterpretResult<LoxObject> {
+        match object {
+            LoxObject::Instance(lox_instance) => {
+                match lox_instance.get(name, self.method_cache.as_mut()) {
+                    Ok(obj) => {
+                        if let LoxObject::Callable(callable) = &obj {
+                            if callable.borrow().is_property() {
+                                // this is a property field on a class instance, invoke it.
+                                if let Some(r) = callable.borrow().call(self, &vec![])? {
+                                    Ok(r)
+                                } else {
+                                    // Property didn't explicitly return anything - which is weird, but let's
+                                    // allow it because it could be desired that the property invocation causes a
+                                    // desired side-effect.
+                                    Ok(LoxObject::Nil)
+                                }
+                            } else {
+                                Ok(obj)
+                            }
+                        } else {
+                            Ok(obj)
+                        }
+                    }
+                    Err(e) => Err(InterpretResultStatus::Error(e)),
+                }
+            }
+            LoxObject::Class(lox_class) => match lox_class.get(name) {
+                Ok(obj) => Ok(obj),
+                Err(e) => Err(InterpretResultStatus::Error(e)),
+            },
+            _ => Err(InterpretResultStatus::Error(RuntimeError::new(
+                name,
+                "Only instances have properties.",
+            ))),
+        }
+    }
+
+    // Evaluates `object.name` (or `object?.name`) as the callee of a call expression.
+    // Properties are invoked when accessed, so `instance.property()` would call the
+    // property's value; that's reported as an error instead. `(instance.property)()`
+    // still calls a property returning a function. The lookup is part of the call
+    // expression, so only `object` counts as a step, as every evaluated node does.
+    fn evaluate_method(
+        &mut self,
+        object: &Expr,
+        name: &Token,
+        is_optional: bool,
+    ) -> InterpretResult<LoxObject> {
+        match self._evaluate(object)? {
+            LoxObject::Nil if is_optional => Ok(LoxObject::Nil),
+            LoxObject::Instance(lox_instance) => {
+                match lox_instance.get(name, self.method_cache.as_mut()) {
+                    Ok(LoxObject::Callable(callable)) if callable.borrow().is_property() => {
+                        // invoke the property; its value may be called if it's a function
+                        match callable.borrow().call(self, &vec![])? {
+                            Some(value @ LoxObject::Callable(_)) => Ok(value),
+                            _ => Err(InterpretResultStatus::Error(RuntimeError::new(
+                                name,
+                                &format!("\"{}\" is a property and cannot be called.", name.lexeme),
+                            ))),
+                        }
+                    }
+                    Ok(member) => Ok(member),
+                    Err(e) => Err(InterpretResultStatus::Error(e)),
+                }
+            }
+            object => self.get_property(object, name),
+        }
+    }
+
+    /// Evaluates the callee and arguments of a call expression, returning them along
+    /// with the name of the function called, for backtraces.
+    fn evaluate_call(
+        &mut self,
+        callee: &Expr,
+        arguments: &[Box<Expr>],
+    ) -> InterpretResult<(String, LoxObject, Vec<LoxObject>)> {
+        let function = match callee {
+            Expr::Variable { name } => name.lexeme.as_str(),
+            Expr::Get { name, .. } | Expr::OptionalGet { name, .. } => name.lexeme.as_str(),
+            Expr::Super { method, .. } => method.lexeme.as_str(),
+            _ => "<anonymous>",
+        };
+        let callee_value = match callee {
+            Expr::Get { object, name } => self.evaluate_method(object, name, false)?,
+            Expr::OptionalGet { object, name } => self.evaluate_method(object, name, true)?,
+            _ => self._evaluate(callee)?,
+        };
+        let mut args = vec![];
+        for arg in arguments {
+            args.push(self._evaluate(arg)?);
+        }
+        Ok((function.to_owned(), callee_value, args))
+    }
+
+    /// As `call`, but records a frame for `function` on the call stack while the
+    /// call runs, so errors raised by it carry a backtrace.
+    pub fn call_in_frame(
+        &mut self,
+        function: &str,
+        callee: &LoxObject,
+        args: Vec<LoxObject>,
+        paren: &Token,
+    ) -> InterpretResult<LoxObject> {
+        self.call_stack.push(StackFrame::new(function, paren.line));
+        let mut result = self.call(callee, &args, Some(paren));
+        if let Err(InterpretResultStatus::Error(e)) = &mut result {
+            // the innermost call records the whole stack
+            if e.backtrace.is_empty() {
+                e.backtrace = self.call_stack.iter().rev().cloned().collect();
+            }
+        }
+        self.call_stack.pop();
+        result
+    }
+
+    /// Calls `callee` with `args`, checking its arity first. `paren` is the closing
+    /// paren of the call expression, if there is one, and is used to report errors.
+    pub fn call(
+        &mut self,
+        callee: &LoxObject,
+        args: &Vec<LoxObject>,
+        paren: Option<&Token>,
+    ) -> InterpretResult<LoxObject> {
+        let error = |message: &str| {
+            InterpretResultStatus::Error(if let Some(paren) = paren {
+                RuntimeError::new(paren, message)
+            } else {
+                RuntimeError::with_message(message)
+            })
+        };
+
+        let result = match callee {
+            LoxObject::Callable(callable) => {
+                let arity = callable.borrow().arity();
+                let max_arity = callable.borrow().max_arity();
+                if args.len() < arity || args.len() > max_arity {
+                    let expected = if arity == max_arity {
+                        format!("{}", arity)
+                    } else {
+                        format!("{} to {}", arity, max_arity)
+                    };
+                    return Err(error(
+                        format!("Expected {} arguments but got {}", expected, args.len()).as_str(),
+                    ));
+                }
+
+                callable.borrow().call(self, args)
+            }
+
+            LoxObject::Class(class) => {
+                if args.len() != class.arity() {
+                    return Err(error(
+                        format!(
+                            "Expected {} arguments but got {}",
+                            class.arity(),
+                            args.len()
+                        )
+                        .as_str(),
+                    ));
+                }
+
+                class.call(self, args)
+            }
+
+            _ => {
+                return Err(error(
+                    "Callee is not a callable expression (function, method, or class ctor).",
+                ))
+            }
+        };
+
+        match result {
+            // natives and class constructors may have no token to report, so errors
+            // they raise get the call site
+            Err(InterpretResultStatus::Error(mut e)) if e.token.is_none() => {
+                e.token = paren.cloned();
+                Err(InterpretResultStatus::Error(e))
+            }
+            result => Ok(result?.unwrap_or(LoxObject::Nil)),
+        }
+    }
+
+    // Returns true if the variable `name` was declared without a value and hasn't
+    // been assigned one since.
+    fn is_uninitialized(&self, name: &Token, expr: &Expr) -> InterpretResult<bool> {
+        Ok(matches!(
+            self.look_up_variable(name, expr)?,
+            LoxObject::Undefined
+        ))
+    }
+
     pub fn resolve_local(&mut self, variable: &Expr, distance: usize) {
         self.locals.insert(variable.clone(), distance);
     }
@@ -146,13 +675,14 @@ impl ExprVisitor<InterpretResult<LoxObject>> for Interpreter {
         name: &Token,
         value: &Box<Expr>,
     ) -> InterpretResult<LoxObject> {
-        let value = self.evaluate(value)?;
+        let value = self._evaluate(value)?;
 
         if let Some(distance) = self.locals.get(expr) {
             self.environment.assign_at(*distance, name, &value)?;
         } else {
             self.globals.assign(name, &value)?;
         }
+        self.observe_assignment(name, &value);
 
         Ok(value)
     }
@@ -164,8 +694,8 @@ impl ExprVisitor<InterpretResult<LoxObject>> for Interpreter {
         operator: &Token,
         right: &Box<Expr>,
     ) -> InterpretResult<LoxObject> {
-        let left = self.evaluate(left)?;
-        let right = self.evaluate(right)?;
+        let left = self._evaluate(left)?;
+        let right = self._evaluate(right)?;
         match operator.token_type {
             TokenType::Minus => {
                 if let LoxObject::Number(l) = left {
@@ -177,17 +707,29 @@ impl ExprVisitor<InterpretResult<LoxObject>> for Interpreter {
                             "Right operand not a number.",
                         )))
                     }
+                } else if let LoxObject::Str(l) = left {
+                    // subtracting a string removes it from the end of the left operand, if present
+                    if let LoxObject::Str(r) = right {
+                        Ok(LoxObject::Str(String::from(
+                            l.strip_suffix(r.as_str()).unwrap_or(&l),
+                        )))
+                    } else {
+                        Err(InterpretResultStatus::Error(RuntimeError::new(
+                            operator,
+                            "Right operand not a string.",
+                        )))
+                    }
                 } else {
                     Err(InterpretResultStatus::Error(RuntimeError::new(
                         operator,
-                        "Left operand not a number.",
+                        "Left operand not a number or string.",
                     )))
                 }
             }
             TokenType::Slash => {
                 if let LoxObject::Number(l) = left {
                     if let LoxObject::Number(r) = right {
-                        if r.abs() > 1e-8 {
+                        if r != 0.0 || self.ieee_division {
                             Ok(LoxObject::Number(l / r))
                         } else {
                             Err(InterpretResultStatus::Error(RuntimeError::new(
@@ -239,7 +781,7 @@ impl ExprVisitor<InterpretResult<LoxObject>> for Interpreter {
                     if let LoxObject::Str(r) = right {
                         Ok(LoxObject::Str(format!("{}{}", l, r)))
                     } else if let LoxObject::Number(r) = right {
-                        Ok(LoxObject::Str(format!("{}{}", l, r)))
+                        Ok(LoxObject::Str(format!("{}{}", l, format_number(r))))
                     } else if let LoxObject::Boolean(r) = right {
                         Ok(LoxObject::Str(format!("{}{}", l, r)))
                     } else if let LoxObject::Nil = right {
@@ -258,32 +800,19 @@ impl ExprVisitor<InterpretResult<LoxObject>> for Interpreter {
                 }
             }
 
-            TokenType::EqualEqual => {
-                if let LoxObject::Number(l) = left {
-                    if let LoxObject::Number(r) = right {
-                        Ok(LoxObject::Boolean(l == r))
-                    } else {
-                        Err(InterpretResultStatus::Error(RuntimeError::new(
-                            operator,
-                            "Right operand not a number.",
-                        )))
-                    }
-                } else if let LoxObject::Str(l) = left {
-                    if let LoxObject::Str(r) = right {
-                        Ok(LoxObject::Boolean(l == r))
-                    } else {
-                        Err(InterpretResultStatus::Error(RuntimeError::new(
-                            operator,
-                            "Right operand not a string",
-                        )))
-                    }
-                } else {
-                    Err(InterpretResultStatus::Error(RuntimeError::new(
-                        operator,
-                        "Left operand not a number or string.",
-                    )))
-                }
-            }
+            TokenType::BangEqual => match equals(&left, &right) {
+                Ok(equal) => Ok(LoxObject::Boolean(!equal)),
+                Err(message) => Err(InterpretResultStatus::Error(RuntimeError::new(
+                    operator, message,
+                ))),
+            },
+
+            TokenType::EqualEqual => match equals(&left, &right) {
+                Ok(equal) => Ok(LoxObject::Boolean(equal)),
+                Err(message) => Err(InterpretResultStatus::Error(RuntimeError::new(
+                    operator, message,
+                ))),
+            },
 
             TokenType::Less => {
                 if let LoxObject::Number(l) = left {
@@ -361,6 +890,29 @@ impl ExprVisitor<InterpretResult<LoxObject>> for Interpreter {
         }
     }
 
+    fn visit_block_expr(
+        &mut self,
+        _expr: &Expr,
+        statements: &[Box<Stmt>],
+        final_expr: &Expr,
+    ) -> InterpretResult<LoxObject> {
+        // run the statements in a fresh scope, then evaluate final_expr within it
+        let env = Environment::as_child_of(self.environment.clone());
+        let previous_env = std::mem::replace(&mut self.environment, env);
+
+        let mut result = Ok(());
+        for statement in in_hoisted_order(statements) {
+            result = self.execute(statement);
+            if result.is_err() {
+                break;
+            }
+        }
+        let value = result.and_then(|_| self._evaluate(final_expr));
+
+        self.environment = previous_env;
+        value
+    }
+
     fn visit_call_expr(
         &mut self,
         _expr: &Expr,
@@ -368,101 +920,29 @@ impl ExprVisitor<InterpretResult<LoxObject>> for Interpreter {
         paren: &Token,
         arguments: &Vec<Box<Expr>>,
     ) -> InterpretResult<LoxObject> {
-        let callee = self._evaluate(callee)?;
-        let mut args = vec![];
-        for arg in arguments {
-            args.push(self._evaluate(arg)?);
-        }
+        let (function, callee, args) = self.evaluate_call(callee, arguments)?;
+        self.call_in_frame(&function, &callee, args, paren)
+    }
 
-        match callee {
-            LoxObject::Callable(callable) => {
-                if args.len() != callable.borrow().arity() {
-                    return Err(InterpretResultStatus::Error(RuntimeError::new(
-                        paren,
-                        format!(
-                            "Expected {} arguments but got {}",
-                            callable.borrow().arity(),
-                            args.len()
-                        )
-                        .as_str(),
-                    )));
-                }
+    fn visit_get_expr(
+        &mut self,
+        _expr: &Expr,
+        object: &Box<Expr>,
+        name: &Token,
+    ) -> InterpretResult<LoxObject> {
+        let object = self._evaluate(object)?;
+        self.get_property(object, name)
+    }
 
-                if let Some(v) = callable.borrow().call(self, &args)? {
-                    Ok(v)
-                } else {
-                    Ok(LoxObject::Nil)
-                }
-            }
-
-            LoxObject::Class(class) => {
-                if args.len() != class.arity() {
-                    return Err(InterpretResultStatus::Error(RuntimeError::new(
-                        paren,
-                        format!(
-                            "Expected {} arguments but got {}",
-                            class.arity(),
-                            args.len()
-                        )
-                        .as_str(),
-                    )));
-                }
-
-                if let Some(v) = class.call(self, &args)? {
-                    Ok(v)
-                } else {
-                    Ok(LoxObject::Nil)
-                }
-            }
-
-            _ => Err(InterpretResultStatus::Error(RuntimeError::new(
-                paren,
-                "Callee is not a callable expression (function, method, or class ctor).",
-            ))),
-        }
-    }
-
-    fn visit_get_expr(
+    fn visit_optional_get_expr(
         &mut self,
         _expr: &Expr,
-        object: &Box<Expr>,
+        object: &Expr,
         name: &Token,
     ) -> InterpretResult<LoxObject> {
-        let object = self.evaluate(object)?;
-
-        match object {
-            LoxObject::Instance(lox_instance) => {
-                match lox_instance.get(name) {
-                    Ok(obj) => {
-                        if let LoxObject::Callable(callable) = &obj {
-                            if callable.borrow().is_property() {
-                                // this is a property field on a class instance, invoke it.
-                                if let Some(r) = callable.borrow().call(self, &vec![])? {
-                                    Ok(r)
-                                } else {
-                                    // Property didn't explicitly return anything - which is weird, but let's
-                                    // allow it because it could be desired that the property invocation causes a
-                                    // desired side-effect.
-                                    Ok(LoxObject::Nil)
-                                }
-                            } else {
-                                Ok(obj)
-                            }
-                        } else {
-                            Ok(obj)
-                        }
-                    }
-                    Err(e) => Err(InterpretResultStatus::Error(e)),
-                }
-            }
-            LoxObject::Class(lox_class) => match lox_class.get(name) {
-                Ok(obj) => Ok(obj),
-                Err(e) => Err(InterpretResultStatus::Error(e)),
-            },
-            _ => Err(InterpretResultStatus::Error(RuntimeError::new(
-                name,
-                "Only instances have properties.",
-            ))),
+        match self._evaluate(object)? {
+            LoxObject::Nil => Ok(LoxObject::Nil),
+            object => self.get_property(object, name),
         }
     }
 
@@ -485,6 +965,18 @@ impl ExprVisitor<InterpretResult<LoxObject>> for Interpreter {
         Ok(callable)
     }
 
+    fn visit_list_expr(
+        &mut self,
+        _expr: &Expr,
+        elements: &[Box<Expr>],
+    ) -> InterpretResult<LoxObject> {
+        let mut values = vec![];
+        for element in elements {
+            values.push(self._evaluate(element)?);
+        }
+        Ok(LoxObject::new_list(values))
+    }
+
     fn visit_literal_expr(
         &mut self,
         _expr: &Expr,
@@ -500,7 +992,16 @@ impl ExprVisitor<InterpretResult<LoxObject>> for Interpreter {
         operator: &Token,
         right: &Box<Expr>,
     ) -> InterpretResult<LoxObject> {
-        let left = self.evaluate(left)?;
+        let left = match &**left {
+            // an uninitialized variable counts as nil here, so `var a; a ??= 1;` works
+            Expr::Variable { name }
+                if operator.token_type == TokenType::QuestionQuestion
+                    && self.is_uninitialized(name, left)? =>
+            {
+                LoxObject::Nil
+            }
+            _ => self._evaluate(left)?,
+        };
         match operator.token_type {
             TokenType::Or => {
                 // left side of Or is truthy, result of expr is the left side
@@ -514,10 +1015,16 @@ impl ExprVisitor<InterpretResult<LoxObject>> for Interpreter {
                     return Ok(left);
                 }
             }
+            TokenType::QuestionQuestion => {
+                // left side of ?? is only replaced when it is nil
+                if !matches!(left, LoxObject::Nil) {
+                    return Ok(left);
+                }
+            }
             _ => {
                 return Err(InterpretResultStatus::Error(RuntimeError::new(
                     operator,
-                    "Only And and Or are supported conditional operators.",
+                    "Only And, Or and ?? are supported conditional operators.",
                 )))
             }
         }
@@ -532,15 +1039,15 @@ impl ExprVisitor<InterpretResult<LoxObject>> for Interpreter {
         name: &Token,
         value: &Box<Expr>,
     ) -> InterpretResult<LoxObject> {
-        let object = self.evaluate(object)?;
+        let object = self._evaluate(object)?;
         match object {
             LoxObject::Instance(instance) => {
-                let value = self.evaluate(value)?;
+                let value = self._evaluate(value)?;
                 instance.set(name, &value);
                 Ok(value)
             }
             LoxObject::Class(class) => {
-                let value = self.evaluate(value)?;
+                let value = self._evaluate(value)?;
                 class.set(name, &value);
                 Ok(value)
             }
@@ -608,7 +1115,7 @@ impl ExprVisitor<InterpretResult<LoxObject>> for Interpreter {
         then_value: &Box<Expr>,
         else_value: &Box<Expr>,
     ) -> InterpretResult<LoxObject> {
-        if self.evaluate(condition)?.is_truthy() {
+        if self._evaluate(condition)?.is_truthy() {
             self._evaluate(then_value)
         } else {
             self._evaluate(else_value)
@@ -625,7 +1132,7 @@ impl ExprVisitor<InterpretResult<LoxObject>> for Interpreter {
         operator: &Token,
         right: &Box<Expr>,
     ) -> InterpretResult<LoxObject> {
-        let right = self.evaluate(right)?;
+        let right = self._evaluate(right)?;
         match operator.token_type {
             TokenType::Bang => Ok(LoxObject::Boolean(!right.is_truthy())),
             TokenType::Minus => match right {
@@ -635,6 +1142,13 @@ impl ExprVisitor<InterpretResult<LoxObject>> for Interpreter {
                     "Unary negative can only be applied to numbers.",
                 ))),
             },
+            TokenType::Plus => match right {
+                LoxObject::Number(_) => Ok(right),
+                _ => Err(InterpretResultStatus::Error(RuntimeError::new(
+                    operator,
+                    "Unary plus can only be applied to numbers.",
+                ))),
+            },
             _ => Err(InterpretResultStatus::Error(RuntimeError::new(
                 operator,
                 "Unsupported unary operator.",
@@ -643,11 +1157,41 @@ impl ExprVisitor<InterpretResult<LoxObject>> for Interpreter {
     }
 
     fn visit_variable_expr(&mut self, expr: &Expr, name: &Token) -> InterpretResult<LoxObject> {
-        self.look_up_variable(name, expr)
+        match self.look_up_variable(name, expr)? {
+            LoxObject::Undefined => Err(InterpretResultStatus::Error(RuntimeError::new(
+                name,
+                &format!(
+                    "Variable \"{}\" read before being assigned a value.",
+                    name.lexeme
+                ),
+            ))),
+            value => Ok(value),
+        }
     }
 }
 
 impl StmtVisitor<InterpretResult<()>> for Interpreter {
+    fn visit_assert_stmt(
+        &mut self,
+        _stmt: &Stmt,
+        condition: &Expr,
+        keyword: &Token,
+        message: &Option<Box<Expr>>,
+    ) -> InterpretResult<()> {
+        if self._evaluate(condition)?.is_truthy() {
+            return Ok(());
+        }
+
+        let message = if let Some(message) = message {
+            format!("Assertion failed: {}", self._evaluate(message)?)
+        } else {
+            String::from("Assertion failed.")
+        };
+        Err(InterpretResultStatus::Error(RuntimeError::new(
+            keyword, &message,
+        )))
+    }
+
     fn visit_block_stmt(
         &mut self,
         _stmt: &Stmt,
@@ -670,7 +1214,7 @@ impl StmtVisitor<InterpretResult<()>> for Interpreter {
         class_methods: &Vec<Box<Stmt>>,
     ) -> InterpretResult<()> {
         let super_class = if let Some(sc) = super_class {
-            match self.evaluate(sc)? {
+            match self._evaluate(sc)? {
                 LoxObject::Class(c) => Some(c),
                 _ => {
                     return Err(InterpretResultStatus::Error(RuntimeError::new(
@@ -770,15 +1314,58 @@ impl StmtVisitor<InterpretResult<()>> for Interpreter {
         Ok(())
     }
 
+    fn visit_destructure_stmt(
+        &mut self,
+        _stmt: &Stmt,
+        names: &[Token],
+        initializer: &Expr,
+    ) -> InterpretResult<()> {
+        let value = self._evaluate(initializer)?;
+        let elements = match &value {
+            LoxObject::List(elements) if elements.borrow().len() == names.len() => {
+                elements.borrow().clone()
+            }
+            _ => {
+                return Err(InterpretResultStatus::Error(RuntimeError::new(
+                    &names[0],
+                    &format!(
+                        "Expect a list of {} values to destructure, received \"{}\".",
+                        names.len(),
+                        value
+                    ),
+                )))
+            }
+        };
+        for (name, element) in names.iter().zip(elements) {
+            self.environment.define(&name.lexeme, &element);
+            self.observe_assignment(name, &element);
+        }
+        Ok(())
+    }
+
     fn visit_expression_stmt(
         &mut self,
         _stmt: &Stmt,
         expression: &Box<Expr>,
     ) -> InterpretResult<()> {
-        match self.evaluate(expression) {
-            Ok(_) => Ok(()),
-            Err(e) => Err(InterpretResultStatus::Error(e)),
-        }
+        self._evaluate(expression)?;
+        Ok(())
+    }
+
+    fn visit_for_stmt(
+        &mut self,
+        _stmt: &Stmt,
+        initializer: &Option<Box<Stmt>>,
+        condition: &Option<Box<Expr>>,
+        increment: &Option<Box<Expr>>,
+        body: &Stmt,
+    ) -> InterpretResult<()> {
+        // the loop gets its own environment, holding the initializer's variable
+        let previous_env = self.environment.clone();
+        self.environment = Environment::as_child_of(previous_env.clone());
+        let result = self.execute_for_loop(initializer, condition, increment, body);
+        self.environment = previous_env;
+        result
     }
 
     fn visit_function_stmt(
@@ -809,7 +1396,7 @@ impl StmtVisitor<InterpretResult<()>> for Interpreter {
         then_branch: &Box<Stmt>,
         else_branch: &Option<Box<Stmt>>,
     ) -> InterpretResult<()> {
-        if self.evaluate(condition)?.is_truthy() {
+        if self._evaluate(condition)?.is_truthy() {
             self.execute(then_branch)?;
         } else if let Some(else_branch) = else_branch {
             self.execute(else_branch)?;
@@ -817,9 +1404,28 @@ impl StmtVisitor<InterpretResult<()>> for Interpreter {
         Ok(())
     }
 
-    fn visit_print_stmt(&mut self, _stmt: &Stmt, expression: &Box<Expr>) -> InterpretResult<()> {
-        let value = self.evaluate(expression)?;
-        println!("{}", value);
+    fn visit_print_stmt(
+        &mut self,
+        _stmt: &Stmt,
+        expression: &Expr,
+        newline: bool,
+        precision: Option<usize>,
+    ) -> InterpretResult<()> {
+        let value = match (self._evaluate(expression)?, precision) {
+            (LoxObject::Number(n), Some(precision)) => format!("{:.*}", precision, n),
+            (value, _) => format!("{}", value),
+        };
+        let result = if newline {
+            writeln!(self.output, "{}", value)
+        } else {
+            // flush so partial lines show up immediately
+            write!(self.output, "{}", value).and_then(|_| self.output.flush())
+        };
+        if let Err(e) = result {
+            return Err(InterpretResultStatus::Error(RuntimeError::with_message(
+                &format!("Unable to write output: {}", e),
+            )));
+        }
         Ok(())
     }
 
@@ -829,6 +1435,28 @@ impl StmtVisitor<InterpretResult<()>> for Interpreter {
         _keyword: &Token,
         value: &Option<Box<Expr>>,
     ) -> InterpretResult<()> {
+        if let Some(value) = value {
+            if let Expr::Call {
+                callee,
+                paren,
+                arguments,
+            } = &**value
+            {
+                let (function, callee, args) = self.evaluate_call(callee, arguments)?;
+                if let LoxObject::Callable(_) = callee {
+                    return Err(InterpretResultStatus::TailCall(Box::new(TailCall {
+                        function,
+                        callee,
+                        args,
+                        paren: paren.clone(),
+                    })));
+                }
+                // classes are called here, since they can't be the returning function
+                let value = self.call_in_frame(&function, &callee, args, paren)?;
+                return Err(InterpretResultStatus::Return(Some(value)));
+            }
+        }
+
         let mut return_value = None;
         if let Some(value) = value {
             return_value = Some(self._evaluate(value)?);
@@ -844,9 +1472,21 @@ impl StmtVisitor<InterpretResult<()>> for Interpreter {
     ) -> InterpretResult<()> {
         let mut value = LoxObject::Undefined;
         if let Some(initializer) = initializer {
-            value = self.evaluate(initializer)?;
+            value = self._evaluate(initializer)?;
         }
         self.environment.define(&name.lexeme, &value);
+        self.observe_assignment(name, &value);
+        Ok(())
+    }
+
+    fn visit_var_list_stmt(
+        &mut self,
+        _stmt: &Stmt,
+        declarations: &[Box<Stmt>],
+    ) -> InterpretResult<()> {
+        for declaration in declarations {
+            self.execute(declaration)?;
+        }
         Ok(())
     }
 
@@ -856,7 +1496,7 @@ impl StmtVisitor<InterpretResult<()>> for Interpreter {
         condition: &Box<Expr>,
         body: &Box<Stmt>,
     ) -> InterpretResult<()> {
-        while self.evaluate(condition)?.is_truthy() {
+        while self._evaluate(condition)?.is_truthy() {
             match self.execute(body) {
                 Ok(_) => (),
                 Err(status) => match status {
@@ -871,6 +1511,9 @@ impl StmtVisitor<InterpretResult<()>> for Interpreter {
                         // pass the return statement up
                         return Err(InterpretResultStatus::Return(v));
                     }
+                    status @ InterpretResultStatus::TailCall(..) => {
+                        return Err(status);
+                    }
                 },
             };
         }
@@ -900,6 +1543,10 @@ mod tests {
             ("true", LoxObject::Boolean(true)),
             ("false", LoxObject::Boolean(false)),
             ("nil", LoxObject::Nil),
+            ("+5", LoxObject::Number(5.0)),
+            ("+5 == 5", LoxObject::Boolean(true)),
+            ("-+5", LoxObject::Number(-5.0)),
+            ("1 + +2", LoxObject::Number(3.0)),
         ];
         for (expression, expected_result) in inputs {
             let mut scanner = scanner::Scanner::new(expression);
@@ -924,6 +1571,8 @@ mod tests {
             "\"Hello\".bar",
             "\"Hello\".bar()",
             "4.foo = 10",
+            "+\"x\"",
+            "+nil",
         ];
         for expression in inputs {
             let mut scanner = scanner::Scanner::new(expression);
@@ -935,6 +1584,87 @@ mod tests {
         }
     }
 
+    #[test]
+    fn division_by_zero_is_an_error_unless_ieee() {
+        let evaluate = |expression: &str, ieee_division: bool| {
+            let mut scanner = scanner::Scanner::new(expression);
+            let tokens = scanner.scan_tokens();
+            let mut parser = parser::Parser::new(tokens);
+            let expr = parser.parse_expression().unwrap();
+
+            let mut interpreter = Interpreter::new();
+            interpreter.set_ieee_division(ieee_division);
+            interpreter.evaluate(&expr)
+        };
+
+        // tiny divisors are valid in either mode
+        for ieee_division in [false, true] {
+            assert_eq!(
+                evaluate("2 / 0.0000001", ieee_division).unwrap(),
+                LoxObject::Number(2.0 / 0.0000001)
+            );
+        }
+
+        let error = evaluate("1 / 0", false).unwrap_err();
+        assert_eq!(error.message, "Attempt to divide by zero.");
+        assert!(evaluate("1 / -0", false).is_err());
+
+        assert_eq!(
+            evaluate("1 / 0", true).unwrap(),
+            LoxObject::Number(f64::INFINITY)
+        );
+        assert_eq!(
+            evaluate("-1 / 0", true).unwrap(),
+            LoxObject::Number(f64::NEG_INFINITY)
+        );
+        match evaluate("0 / 0", true).unwrap() {
+            LoxObject::Number(n) => assert!(n.is_nan()),
+            result => panic!("Expected NaN, got {}", result),
+        }
+    }
+
+    #[test]
+    fn string_subtraction_removes_suffix() {
+        let inputs = vec![
+            (
+                "\"hello world\" - \" world\"",
+                LoxObject::Str(String::from("hello")),
+            ),
+            (
+                "\"hello world\" - \"hello\"",
+                LoxObject::Str(String::from("hello world")),
+            ),
+            ("\"abc\" - \"abc\"", LoxObject::Str(String::from(""))),
+            ("\"abc\" - \"\"", LoxObject::Str(String::from("abc"))),
+        ];
+        for (expression, expected_result) in inputs {
+            let mut scanner = scanner::Scanner::new(expression);
+            let tokens = scanner.scan_tokens();
+            let mut parser = parser::Parser::new(tokens);
+            let expr = parser.parse_expression().unwrap();
+
+            let mut interpreter = Interpreter::new();
+            let result = interpreter.evaluate(&expr).unwrap();
+            assert_eq!(result, expected_result, "{}", expression);
+        }
+
+        let inputs = vec![
+            ("\"abc\" - 1", "Right operand not a string."),
+            ("1 - \"abc\"", "Right operand not a number."),
+            ("nil - \"abc\"", "Left operand not a number or string."),
+        ];
+        for (expression, expected_message) in inputs {
+            let mut scanner = scanner::Scanner::new(expression);
+            let tokens = scanner.scan_tokens();
+            let mut parser = parser::Parser::new(tokens);
+            let expr = parser.parse_expression().unwrap();
+
+            let mut interpreter = Interpreter::new();
+            let error = interpreter.evaluate(&expr).unwrap_err();
+            assert_eq!(error.message, expected_message);
+        }
+    }
+
     #[test]
     fn logical_expressions_evaluate() {
         let inputs = vec![
@@ -955,6 +1685,90 @@ mod tests {
         }
     }
 
+    #[test]
+    fn ternary_expressions_evaluate_only_the_taken_branch() {
+        let inputs = vec![
+            ("true ? 1 : undefined_var", LoxObject::Number(1.0)),
+            ("false ? undefined_var : 2", LoxObject::Number(2.0)),
+            (
+                "nil ? undefined_var() : \"else\"",
+                LoxObject::Str(String::from("else")),
+            ),
+            // right associative: true ? 1 : (true ? 2 : 3)
+            ("true ? 1 : true ? 2 : 3", LoxObject::Number(1.0)),
+            ("false ? 1 : true ? 2 : 3", LoxObject::Number(2.0)),
+            ("false ? 1 : false ? 2 : 3", LoxObject::Number(3.0)),
+        ];
+        for (expression, expected_result) in inputs {
+            let mut scanner = scanner::Scanner::new(expression);
+            let tokens = scanner.scan_tokens();
+            let mut parser = parser::Parser::new(tokens);
+            let expr = parser.parse_expression().unwrap();
+
+            let mut interpreter = Interpreter::new();
+            let result = interpreter.evaluate(&expr).unwrap();
+            assert_eq!(result, expected_result, "{}", expression);
+        }
+
+        let inputs = vec![(
+            r#"
+            var calls = 0;
+            fun count() {
+                calls = calls + 1;
+                return calls;
+            }
+            var value_0 = true ? 10 : count();
+            var value_1 = false ? count() : 20;
+            var value_2 = calls;
+            "#,
+            vec![
+                ("value_0", LoxObject::Number(10.0)),
+                ("value_1", LoxObject::Number(20.0)),
+                ("value_2", LoxObject::Number(0.0)),
+            ],
+        )];
+        execute(&inputs);
+    }
+
+    #[test]
+    fn optional_get_short_circuits_on_nil() {
+        let inputs = vec![(
+            r#"
+            class Node {
+                init(value, next) {
+                    this.value = value;
+                    this.next = next;
+                }
+            }
+            var list = Node(1, Node(2, nil));
+            var empty = nil;
+            var value_0 = empty?.value;
+            var value_1 = empty?.next?.value;
+            var value_2 = list?.value;
+            var value_3 = list?.next?.value;
+            var value_4 = list.next.next?.value;
+            var value_5 = list?.next?.next?.next;
+            "#,
+            vec![
+                ("value_0", LoxObject::Nil),
+                ("value_1", LoxObject::Nil),
+                ("value_2", LoxObject::Number(1.0)),
+                ("value_3", LoxObject::Number(2.0)),
+                ("value_4", LoxObject::Nil),
+                ("value_5", LoxObject::Nil),
+            ],
+        )];
+        execute(&inputs);
+
+        // only nil short-circuits; other non-instances still error
+        let mut scanner = scanner::Scanner::new("1?.value");
+        let tokens = scanner.scan_tokens();
+        let mut parser = parser::Parser::new(tokens);
+        let expr = parser.parse_expression().unwrap();
+        let mut interpreter = Interpreter::new();
+        assert!(interpreter.evaluate(&expr).is_err());
+    }
+
     /// The inputs
     fn execute(inputs: &Vec<(&str, Vec<(&str, LoxObject)>)>) {
         for (program, expected_results) in inputs {
@@ -1020,6 +1834,23 @@ mod tests {
                 "#,
                 vec![("a", LoxObject::Number(377.0))],
             ),
+            (
+                r#"
+                var a = 0;
+                for (var i = 0; i < 10; i = i + 1) {
+                    if (i == 5) {
+                        break;
+                    }
+                    a = a + i;
+                }
+                var b = 0;
+                for (; b < 3;) b = b + 1;
+                "#,
+                vec![
+                    ("a", LoxObject::Number(10.0)),
+                    ("b", LoxObject::Number(3.0)),
+                ],
+            ),
             (
                 // `this` does what it should
                 r#"
@@ -1286,6 +2117,35 @@ mod tests {
                 "#,
                 vec![("value_0", LoxObject::Str(String::from("A method")))],
             ),
+            (
+                // subclass initializers can chain to the superclass initializer
+                r#"
+                class Animal {
+                    init(name) {
+                        this.name = name;
+                    }
+                }
+
+                class Dog < Animal {
+                    init(name) {
+                        super.init(name);
+                        this.sound = "Woof";
+                    }
+                }
+
+                class Puppy < Dog {}
+
+                var dog = Dog("Rex");
+                var value_0 = dog.name;
+                var value_1 = dog.sound;
+                var value_2 = Puppy("Bit").name;
+                "#,
+                vec![
+                    ("value_0", LoxObject::Str(String::from("Rex"))),
+                    ("value_1", LoxObject::Str(String::from("Woof"))),
+                    ("value_2", LoxObject::Str(String::from("Bit"))),
+                ],
+            ),
             (
                 r#"
                 class Doughnut {
@@ -1332,6 +2192,54 @@ mod tests {
                     ("value_2", LoxObject::Number(2.0)),
                 ],
             ),
+            (
+                // class fields are inherited, but setting one doesn't affect other classes
+                r#"
+                class A {}
+                class B < A {}
+                class C < B {}
+
+                A.shared = 1;
+                var value_0 = B.shared;
+                var value_1 = C.shared;
+
+                B.shared = 2;
+                var value_2 = A.shared;
+                var value_3 = B.shared;
+                var value_4 = C.shared;
+
+                C.own = 3;
+                A.own = 4;
+                var value_5 = C.own;
+                var value_6 = B.own;
+                "#,
+                vec![
+                    ("value_0", LoxObject::Number(1.0)),
+                    ("value_1", LoxObject::Number(1.0)),
+                    ("value_2", LoxObject::Number(1.0)),
+                    ("value_3", LoxObject::Number(2.0)),
+                    ("value_4", LoxObject::Number(2.0)),
+                    ("value_5", LoxObject::Number(3.0)),
+                    ("value_6", LoxObject::Number(4.0)),
+                ],
+            ),
+            (
+                // a class field shadows an inherited class method of the same name
+                r#"
+                class A {
+                class name() { return "method"; }
+                }
+                class B < A {}
+
+                B.name = "field";
+                var value_0 = A.name();
+                var value_1 = B.name;
+                "#,
+                vec![
+                    ("value_0", LoxObject::Str(String::from("method"))),
+                    ("value_1", LoxObject::Str(String::from("field"))),
+                ],
+            ),
         ];
         execute(&inputs);
     }
@@ -1383,15 +2291,685 @@ mod tests {
     }
 
     #[test]
-    fn incorrect_function_arity_is_runtime_error() {
-        let inputs = vec![
+    fn returning_several_values_returns_a_list() {
+        let inputs = vec![(
             r#"
-            fun no_args() {}
-            no_args(1);
+            fun pair() { return 1, 2; }
+            fun min_max(a, b) {
+                if (a < b) return a, b;
+                return b, a;
+            }
+            fun square(x) { return x * x; }
+            fun single() { return 1; }
+            var value_0 = pair();
+            var value_1 = min_max(5, 3);
+            var value_2 = min_max(3, 5);
+            var p = min_max(2, square(3));
+            var value_3 = list_pop(p);
+            var value_4 = list_pop(p);
+            var value_5 = single();
             "#,
+            vec![
+                (
+                    "value_0",
+                    LoxObject::new_list(vec![LoxObject::Number(1.0), LoxObject::Number(2.0)]),
+                ),
+                (
+                    "value_1",
+                    LoxObject::new_list(vec![LoxObject::Number(3.0), LoxObject::Number(5.0)]),
+                ),
+                (
+                    "value_2",
+                    LoxObject::new_list(vec![LoxObject::Number(3.0), LoxObject::Number(5.0)]),
+                ),
+                ("value_3", LoxObject::Number(9.0)),
+                ("value_4", LoxObject::Number(2.0)),
+                ("value_5", LoxObject::Number(1.0)),
+            ],
+        )];
+        execute(&inputs);
+    }
+
+    #[test]
+    fn returned_lists_can_be_destructured() {
+        let inputs = vec![(
             r#"
-            fun one_arg(a) {}
-            one_arg();
+            fun f() { return 1, 2; }
+            var (value_0, value_1) = f();
+            var value_2;
+            {
+                var (x, y) = f();
+                value_2 = y - x;
+            }
+            "#,
+            vec![
+                ("value_0", LoxObject::Number(1.0)),
+                ("value_1", LoxObject::Number(2.0)),
+                ("value_2", LoxObject::Number(1.0)),
+            ],
+        )];
+        execute(&inputs);
+
+        for program in [
+            "var (x, y) = 1;",
+            "fun f() { return 1, 2, 3; } var (x, y) = f();",
+        ] {
+            let mut scanner = scanner::Scanner::new(program);
+            let mut parser = parser::Parser::new(scanner.scan_tokens());
+            let ast = parser.parse().unwrap();
+            let mut interpreter = Interpreter::new();
+            assert!(interpreter.interpret(&ast).is_err(), "{}", program);
+        }
+    }
+
+    #[test]
+    fn concrete_subclasses_of_abstract_classes_can_be_instantiated() {
+        let inputs = vec![(
+            r#"
+            class Shape {
+                abstract area();
+                abstract name();
+                abstract scale(factor);
+                describe() { return this.name() + " " + this.area(); }
+            }
+            class Rect < Shape {
+                init(w, h) { this.w = w; this.h = h; }
+                area() { return this.w * this.h; }
+                scale(factor) { return Rect(this.w * factor, this.h * factor); }
+            }
+            class Square < Rect {
+                init(s) { super.init(s, s); }
+                name() { return "square"; }
+            }
+            var value_0 = Square(3).describe();
+            "#,
+            vec![("value_0", LoxObject::Str(String::from("square 9")))],
+        )];
+        execute(&inputs);
+    }
+
+    #[test]
+    fn abstract_classes_and_methods_are_runtime_errors() {
+        let inputs = vec![
+            r#"
+            class Shape {
+                abstract area();
+            }
+            Shape();
+            "#,
+            r#"
+            class Shape {
+                abstract area();
+                abstract name();
+            }
+            class Named < Shape {
+                name() { return "named"; }
+            }
+            Named();
+            "#,
+            r#"
+            class Shape {
+                abstract area();
+            }
+            class Square < Shape {
+                area() { return super.area(); }
+            }
+            Square().area();
+            "#,
+        ];
+
+        for program in inputs {
+            let mut scanner = scanner::Scanner::new(program);
+            let tokens = scanner.scan_tokens();
+            let mut parser = parser::Parser::new(tokens);
+            let ast = parser.parse().unwrap();
+            let mut interpreter = Interpreter::new();
+            let mut r = resolver::Resolver::new(&mut interpreter);
+            r.resolve(&ast).expect("Expected successful resolve pass");
+            let error = interpreter.interpret(&ast).unwrap_err();
+            assert!(error.token.is_some(), "{}", program);
+        }
+    }
+
+    #[test]
+    fn coalescing_operators_replace_nil() {
+        let inputs = vec![(
+            r#"
+            class Box {}
+            var box = Box();
+            box.contents = nil;
+
+            var a = nil;
+            a ??= 5;
+            var b = 1;
+            b ??= 5;
+            var c = false;
+            c ??= 5;
+            box.contents ??= "full";
+            box.contents ??= "overwritten";
+
+            var value_0 = a;
+            var value_1 = b;
+            var value_2 = c;
+            var value_3 = box.contents;
+            var value_4 = nil ?? nil ?? 3;
+            var value_5 = 0 ?? 3;
+            "#,
+            vec![
+                ("value_0", LoxObject::Number(5.0)),
+                ("value_1", LoxObject::Number(1.0)),
+                ("value_2", LoxObject::Boolean(false)),
+                ("value_3", LoxObject::Str(String::from("full"))),
+                ("value_4", LoxObject::Number(3.0)),
+                ("value_5", LoxObject::Number(0.0)),
+            ],
+        )];
+        execute(&inputs);
+    }
+
+    #[test]
+    fn coalescing_assignment_evaluates_its_object_once() {
+        let inputs = vec![(
+            r#"
+            class Box {}
+            var box = Box();
+            box.a = nil;
+            box.b = nil;
+            var calls = 0;
+            fun get_box() {
+                calls = calls + 1;
+                return box;
+            }
+            get_box().a ??= 1;
+            get_box().a ??= 2;
+            get_box().b ??= get_box().a ??= 3;
+
+            var value_0 = calls;
+            var value_1 = box.a;
+            var value_2 = box.b;
+            "#,
+            vec![
+                ("value_0", LoxObject::Number(4.0)),
+                ("value_1", LoxObject::Number(1.0)),
+                ("value_2", LoxObject::Number(1.0)),
+            ],
+        )];
+        execute(&inputs);
+    }
+
+    #[test]
+    fn closures_capture_each_for_loop_iteration() {
+        let inputs = vec![(
+            r#"
+            var f0;
+            var f1;
+            var f2;
+            for (var i = 0; i < 3; i = i + 1) {
+                fun capture() {
+                    return i;
+                }
+                if (i == 0) f0 = capture;
+                if (i == 1) f1 = capture;
+                if (i == 2) f2 = capture;
+            }
+
+            var count = 0;
+            for (var j = 0; j < 10; j = j + 1) {
+                if (j == 5) break;
+                count = count + 1;
+            }
+
+            var value_0 = f0();
+            var value_1 = f1();
+            var value_2 = f2();
+            var value_3 = count;
+            "#,
+            vec![
+                ("value_0", LoxObject::Number(0.0)),
+                ("value_1", LoxObject::Number(1.0)),
+                ("value_2", LoxObject::Number(2.0)),
+                ("value_3", LoxObject::Number(5.0)),
+            ],
+        )];
+        execute(&inputs);
+    }
+
+    #[test]
+    fn closures_capture_the_live_this() {
+        let inputs = vec![(
+            r#"
+            class Box {
+                init(value) {
+                    this.value = value;
+                }
+                getter() {
+                    return fun () { return this.value; };
+                }
+                setter() {
+                    return fun (value) { this.value = value; };
+                }
+            }
+
+            var box = Box(1);
+            var get = box.getter();
+            var set = box.setter();
+            var value_0 = get();
+            box.value = 2;
+            var value_1 = get();
+            set(3);
+            var value_2 = box.value;
+            var value_3 = get();
+
+            // the closure keeps an otherwise unreferenced instance alive
+            var orphan = Box(4).getter();
+            var value_4 = orphan();
+            "#,
+            vec![
+                ("value_0", LoxObject::Number(1.0)),
+                ("value_1", LoxObject::Number(2.0)),
+                ("value_2", LoxObject::Number(3.0)),
+                ("value_3", LoxObject::Number(3.0)),
+                ("value_4", LoxObject::Number(4.0)),
+            ],
+        )];
+        execute(&inputs);
+    }
+
+    #[test]
+    fn lambdas_in_methods_bind_the_method_this() {
+        let inputs = vec![(
+            r#"
+            class Counter {
+                init() {
+                    this.count = 0;
+                }
+                incrementer() {
+                    return fun () { return fun () { this.count = this.count + 1; return this.count; }; };
+                }
+                reader() {
+                    fun read() { return this.count; }
+                    return read;
+                }
+            }
+
+            var a = Counter();
+            var b = Counter();
+            var increment_a = a.incrementer()();
+            var read_a = a.reader();
+            increment_a();
+            increment_a();
+            var value_0 = read_a();
+            var value_1 = b.reader()();
+            "#,
+            vec![
+                ("value_0", LoxObject::Number(2.0)),
+                ("value_1", LoxObject::Number(0.0)),
+            ],
+        )];
+        execute(&inputs);
+    }
+
+    #[test]
+    fn calling_a_property_is_an_error() {
+        let inputs = vec![(
+            r#"
+            class Rect {
+                init(w, h) {
+                    this.w = w;
+                    this.h = h;
+                }
+                area { return this.w * this.h; }
+                scaler { return fun (s) { return Rect(this.w * s, this.h * s); }; }
+            }
+            var rect = Rect(2, 3);
+            var value_0 = rect.area;
+            var value_1 = (rect.scaler)(2).area;
+            var value_2 = rect?.area;
+            var value_3 = rect.scaler(3).area;
+            "#,
+            vec![
+                ("value_0", LoxObject::Number(6.0)),
+                ("value_1", LoxObject::Number(24.0)),
+                ("value_2", LoxObject::Number(6.0)),
+                ("value_3", LoxObject::Number(54.0)),
+            ],
+        )];
+        execute(&inputs);
+
+        let programs = vec!["var a = Rect(2, 3).area();", "var a = Rect(2, 3)?.area();"];
+        for program in programs {
+            let source = format!(
+                r#"
+                class Rect {{
+                    init(w, h) {{
+                        this.w = w;
+                        this.h = h;
+                    }}
+                    area {{ return this.w * this.h; }}
+                    scaler {{ return fun (s) {{ return Rect(this.w * s, this.h * s); }}; }}
+                }}
+                {}
+                "#,
+                program
+            );
+            let mut scanner = scanner::Scanner::new(&source);
+            let tokens = scanner.scan_tokens();
+            let mut parser = parser::Parser::new(tokens);
+            let statements = parser.parse().unwrap();
+            let mut interpreter = Interpreter::new();
+            let mut resolver = resolver::Resolver::new(&mut interpreter);
+            resolver.resolve(&statements).unwrap();
+            let error = interpreter.interpret(&statements).unwrap_err();
+            assert!(
+                error
+                    .message
+                    .ends_with("is a property and cannot be called."),
+                "{}: {}",
+                program,
+                error.message
+            );
+        }
+    }
+
+    #[test]
+    fn properties_returning_functions_can_be_called() {
+        let inputs = vec![(
+            r#"
+            class Counter {
+                init() {
+                    this.total = 0;
+                }
+                handler {
+                    var counter = this;
+                    return fun (a, b) {
+                        counter.total = counter.total + a + b;
+                        return counter.total;
+                    };
+                }
+            }
+            var counter = Counter();
+            var value_0 = counter.handler(1, 2);
+            var value_1 = counter.handler(3, 4);
+            var value_2 = counter.total;
+            var value_3 = counter?.handler(5, 0);
+            "#,
+            vec![
+                ("value_0", LoxObject::Number(3.0)),
+                ("value_1", LoxObject::Number(10.0)),
+                ("value_2", LoxObject::Number(10.0)),
+                ("value_3", LoxObject::Number(15.0)),
+            ],
+        )];
+        execute(&inputs);
+    }
+
+    #[test]
+    fn returned_calls_evaluate_their_callee_once() {
+        let inputs = vec![(
+            r#"
+            var count = 0;
+            class P {
+                init(x) {
+                    this.x = x;
+                }
+                adder {
+                    return fun (a, b) { return this.x + a + b; };
+                }
+            }
+            fun getP() {
+                count = count + 1;
+                return P;
+            }
+            fun make() {
+                return getP()(3);
+            }
+            fun add(p) {
+                return p.adder(1, 2);
+            }
+            var value_0 = make().x;
+            var value_1 = count;
+            var value_2 = add(P(10));
+            "#,
+            vec![
+                ("value_0", LoxObject::Number(3.0)),
+                ("value_1", LoxObject::Number(1.0)),
+                ("value_2", LoxObject::Number(13.0)),
+            ],
+        )];
+        execute(&inputs);
+    }
+
+    #[test]
+    fn native_errors_report_the_call_site() {
+        let mut scanner = scanner::Scanner::new("var a = 1;\n\nvar b = abs(\n\"x\");");
+        let tokens = scanner.scan_tokens();
+        let mut parser = parser::Parser::new(tokens);
+        let statements = parser.parse().unwrap();
+        let mut interpreter = Interpreter::new();
+        let error = interpreter.interpret(&statements).unwrap_err();
+        // the closing paren of the call
+        assert_eq!(error.token.unwrap().line, 4);
+
+        // errors from functions a native calls keep their own location
+        let mut scanner =
+            scanner::Scanner::new("fun f() {\n  return 1 / 0;\n}\nassert_throws(f);\nf();");
+        let tokens = scanner.scan_tokens();
+        let mut parser = parser::Parser::new(tokens);
+        let statements = parser.parse().unwrap();
+        let mut interpreter = Interpreter::new();
+        let error = interpreter.interpret(&statements).unwrap_err();
+        assert_eq!(error.token.unwrap().line, 2);
+    }
+
+    #[test]
+    fn prelude_stopwatch_measures_elapsed_time() {
+        let inputs = vec![(
+            r#"
+            var watch = Stopwatch();
+            var value_0 = watch.elapsed();
+            watch.start();
+            var i = 0;
+            while (i < 100) i = i + 1;
+            watch.stop();
+            var stopped = watch.elapsed();
+            var value_1 = stopped >= 0;
+            var value_2 = watch.elapsed() == stopped;
+            watch.start();
+            var value_3 = watch.elapsed() >= stopped;
+            "#,
+            vec![
+                ("value_0", LoxObject::Number(0.0)),
+                ("value_1", LoxObject::Boolean(true)),
+                ("value_2", LoxObject::Boolean(true)),
+                ("value_3", LoxObject::Boolean(true)),
+            ],
+        )];
+        execute(&inputs);
+
+        let stopwatch = Token::new(TokenType::Identifier, String::from("Stopwatch"), None, 1, 0);
+        let mut interpreter = Interpreter::new();
+        assert!(matches!(
+            interpreter.globals().get(&stopwatch),
+            Ok(LoxObject::Class(_))
+        ));
+        interpreter.reset_globals();
+        assert!(matches!(
+            interpreter.globals().get(&stopwatch),
+            Ok(LoxObject::Class(_))
+        ));
+    }
+
+    #[test]
+    fn nested_functions_are_hoisted() {
+        let inputs = vec![(
+            r#"
+            fun parity(n) {
+                fun is_even(n) {
+                    if (n == 0) return true;
+                    return is_odd(n - 1);
+                }
+                fun is_odd(n) {
+                    if (n == 0) return false;
+                    return is_even(n - 1);
+                }
+                return is_even(n);
+            }
+            var value_0 = parity(10);
+            var value_1 = parity(7);
+            "#,
+            vec![
+                ("value_0", LoxObject::Boolean(true)),
+                ("value_1", LoxObject::Boolean(false)),
+            ],
+        )];
+        execute(&inputs);
+    }
+
+    #[test]
+    fn block_functions_can_be_called_before_their_declaration() {
+        let inputs = vec![(
+            r#"
+            var value_0;
+            {
+                fun a() { return b(); }
+                value_0 = a();
+                fun b() { return 1; }
+            }
+            var value_1 = {
+                fun c() { return d(); }
+                var result = c();
+                fun d() { return 2; }
+                result
+            };
+            "#,
+            vec![
+                ("value_0", LoxObject::Number(1.0)),
+                ("value_1", LoxObject::Number(2.0)),
+            ],
+        )];
+        execute(&inputs);
+    }
+
+    #[test]
+    fn block_expressions_evaluate_to_final_expression() {
+        let inputs = vec![(
+            r#"
+            fun compute() {
+                return 21;
+            }
+            var t = "outer";
+            var value_0 = {
+                var t = compute();
+                t * 2
+            };
+            var value_1 = t;
+            var value_2 = { print "side effect"; 1 } + { 2 };
+            fun first_positive(a, b) {
+                var chosen = {
+                    if (a > 0) return a;
+                    b
+                };
+                return chosen;
+            }
+            var value_3 = first_positive(3, 4);
+            var value_4 = first_positive(-3, 4);
+            "#,
+            vec![
+                ("value_0", LoxObject::Number(42.0)),
+                ("value_1", LoxObject::Str(String::from("outer"))),
+                ("value_2", LoxObject::Number(3.0)),
+                ("value_3", LoxObject::Number(3.0)),
+                ("value_4", LoxObject::Number(4.0)),
+            ],
+        )];
+        execute(&inputs);
+    }
+
+    #[test]
+    fn self_recursive_tail_calls_do_not_grow_the_stack() {
+        let inputs = vec![(
+            r#"
+            fun count(n, total) {
+                if (n == 0) return total;
+                return count(n - 1, total + 1);
+            }
+
+            class Counter {
+                init() {
+                    this.step = 2;
+                }
+                count(n, total) {
+                    if (n == 0) return total;
+                    return this.count(n - 1, total + this.step);
+                }
+            }
+
+            fun is_even(n) {
+                if (n == 0) return true;
+                return is_odd(n - 1);
+            }
+            fun is_odd(n) {
+                if (n == 0) return false;
+                return is_even(n - 1);
+            }
+
+            fun fib(n) {
+                if (n < 2) return n;
+                return fib(n - 1) + fib(n - 2);
+            }
+
+            var value_0 = count(1000000, 0);
+            var value_1 = Counter().count(1000000, 0);
+            var value_2 = is_even(100);
+            var value_3 = fib(10);
+            "#,
+            vec![
+                ("value_0", LoxObject::Number(1000000.0)),
+                ("value_1", LoxObject::Number(2000000.0)),
+                ("value_2", LoxObject::Boolean(true)),
+                ("value_3", LoxObject::Number(55.0)),
+            ],
+        )];
+        execute(&inputs);
+    }
+
+    #[test]
+    fn var_statements_declare_several_variables_in_order() {
+        let inputs = vec![(
+            r#"
+            var a = 1, b, c = a + 1;
+            b = a + c;
+            var value_0 = a;
+            var value_1 = b;
+            var value_2 = c;
+
+            var sum = 0;
+            for (var i = 0, j = 10; i < j; i = i + 1) {
+                j = j - 1;
+                sum = sum + 1;
+            }
+            var value_3 = sum;
+            "#,
+            vec![
+                ("value_0", LoxObject::Number(1.0)),
+                ("value_1", LoxObject::Number(3.0)),
+                ("value_2", LoxObject::Number(2.0)),
+                ("value_3", LoxObject::Number(5.0)),
+            ],
+        )];
+        execute(&inputs);
+    }
+
+    #[test]
+    fn incorrect_function_arity_is_runtime_error() {
+        let inputs = vec![
+            r#"
+            fun no_args() {}
+            no_args(1);
+            "#,
+            r#"
+            fun one_arg(a) {}
+            one_arg();
             "#,
             r#"
             fun two_args(a,b) {}
@@ -1425,4 +3003,244 @@ mod tests {
             assert!(interpreter.interpret(&ast).is_err());
         }
     }
+
+    #[test]
+    fn failed_assertions_are_runtime_errors() {
+        let inputs = vec![
+            ("assert 1 < 2;\nassert true : \"unused\";", None),
+            (
+                "var a = 1;\n\nassert a == 2;",
+                Some((3, "Assertion failed.")),
+            ),
+            (
+                "var a = 1;\nassert a == 2 : \"a is \" + a;",
+                Some((2, "Assertion failed: a is 1")),
+            ),
+        ];
+
+        for (program, expected_error) in inputs {
+            let mut scanner = scanner::Scanner::new(program);
+            let tokens = scanner.scan_tokens();
+            let mut parser = parser::Parser::new(tokens);
+            let ast = parser.parse().unwrap();
+            let mut interpreter = Interpreter::new();
+            let result = interpreter.interpret(&ast);
+            match expected_error {
+                None => assert!(result.is_ok()),
+                Some((line, message)) => {
+                    let error = result.unwrap_err();
+                    assert_eq!(error.token.unwrap().line, line);
+                    assert_eq!(error.message, message);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn cached_method_lookups_match_uncached_lookups() {
+        let program = r#"
+            class Base {
+                value() { return 1; }
+                scaled(n) { return this.value() * n; }
+            }
+            class Derived < Base {
+                value() { return 2; }
+            }
+
+            var total = 0;
+            for (var i = 0; i < 2000; i = i + 1) {
+                var instance = Base();
+                if (i > 999) instance = Derived();
+                total = total + instance.scaled(i) + instance.value();
+            }
+        "#;
+
+        let mut scanner = scanner::Scanner::new(program);
+        let tokens = scanner.scan_tokens();
+        let mut parser = parser::Parser::new(tokens);
+        let statements = parser.parse().unwrap();
+
+        let mut totals = vec![];
+        for use_cache in [true, false] {
+            let mut interpreter = Interpreter::new();
+            if !use_cache {
+                interpreter.disable_method_cache();
+            }
+            let mut r = resolver::Resolver::new(&mut interpreter);
+            r.resolve(&statements)
+                .expect("Expected successful resolve pass");
+            interpreter.interpret(&statements).unwrap();
+
+            let token = Token::new(TokenType::Identifier, String::from("total"), None, 1, 0);
+            totals.push(interpreter.environment().get(&token).unwrap());
+        }
+
+        // sum(i) + 1 for i in 0..1000, then sum(2i) + 2 for i in 1000..2000
+        let expected = (499500.0 + 1000.0) + (2.0 * 1499500.0 + 2000.0);
+        assert_eq!(totals[0], LoxObject::Number(expected));
+        assert_eq!(totals[0], totals[1]);
+    }
+
+    #[test]
+    fn method_cache_is_cleared_for_each_run() {
+        let run = |interpreter: &mut Interpreter, program: &str| {
+            let mut scanner = scanner::Scanner::new(program);
+            let tokens = scanner.scan_tokens();
+            let mut parser = parser::Parser::new(tokens);
+            let statements = parser.parse().unwrap();
+            let mut r = resolver::Resolver::new(interpreter);
+            r.resolve(&statements)
+                .expect("Expected successful resolve pass");
+            interpreter.interpret(&statements).unwrap();
+        };
+
+        let mut interpreter = Interpreter::new();
+        run(
+            &mut interpreter,
+            "class A { f() { return 1; } g() { return 2; } } var a = A(); a.f(); a.g();",
+        );
+        assert_eq!(interpreter.method_cache.as_ref().unwrap().len(), 2);
+        run(&mut interpreter, "var b = 1;");
+        assert!(interpreter.method_cache.as_ref().unwrap().is_empty());
+        run(&mut interpreter, "a.f();");
+        assert_eq!(interpreter.method_cache.as_ref().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn step_limit_bounds_execution() {
+        let mut scanner = scanner::Scanner::new("var i = 0; while (true) { i = i + 1; }");
+        let tokens = scanner.scan_tokens();
+        let mut parser = parser::Parser::new(tokens);
+        let ast = parser.parse().unwrap();
+        let mut interpreter = Interpreter::new();
+        interpreter.set_step_limit(Some(500));
+        let error = interpreter.interpret(&ast).unwrap_err();
+        assert_eq!(error.message, "Execution step limit exceeded.");
+
+        let mut scanner = scanner::Scanner::new("var i = 0; while (i < 10) { i = i + 1; }");
+        let tokens = scanner.scan_tokens();
+        let mut parser = parser::Parser::new(tokens);
+        let ast = parser.parse().unwrap();
+        let mut interpreter = Interpreter::new();
+        interpreter.set_step_limit(Some(500));
+        assert!(interpreter.interpret(&ast).is_ok());
+
+        // each statement and evaluated expression is a step: the class statement,
+        // then `var a = A();` and `a.m();` each as statement, call and variable
+        let program = "class A { m() {} } var a = A(); a.m();";
+        for (step_limit, succeeds) in [(7, true), (6, false)] {
+            let mut scanner = scanner::Scanner::new(program);
+            let mut parser = parser::Parser::new(scanner.scan_tokens());
+            let ast = parser.parse().unwrap();
+            let mut interpreter = Interpreter::new();
+            interpreter.set_step_limit(Some(step_limit));
+            assert_eq!(
+                interpreter.interpret(&ast).is_ok(),
+                succeeds,
+                "{}",
+                step_limit
+            );
+        }
+    }
+
+    // A writer whose contents remain readable after it's handed to the interpreter.
+    #[derive(Clone)]
+    struct SharedBuffer(Rc<RefCell<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn write_statements_omit_the_newline() {
+        let mut scanner = scanner::Scanner::new(r#"write "a"; write "b"; print "c"; write 1;"#);
+        let tokens = scanner.scan_tokens();
+        let mut parser = parser::Parser::new(tokens);
+        let ast = parser.parse().unwrap();
+
+        let buffer = SharedBuffer(Rc::new(RefCell::new(vec![])));
+        let mut interpreter = Interpreter::new();
+        interpreter.set_output(Box::new(buffer.clone()));
+        interpreter.interpret(&ast).unwrap();
+        assert_eq!(
+            String::from_utf8(buffer.0.borrow().clone()).unwrap(),
+            "abc\n1"
+        );
+    }
+
+    #[test]
+    fn integer_suffixed_literals_print_as_integers() {
+        let mut scanner = scanner::Scanner::new("print 5_i; print 2.9_i; print 5_i / 2;");
+        let tokens = scanner.scan_tokens();
+        let mut parser = parser::Parser::new(tokens);
+        let ast = parser.parse().unwrap();
+
+        let buffer = SharedBuffer(Rc::new(RefCell::new(vec![])));
+        let mut interpreter = Interpreter::new();
+        interpreter.set_output(Box::new(buffer.clone()));
+        interpreter.interpret(&ast).unwrap();
+        assert_eq!(
+            String::from_utf8(buffer.0.borrow().clone()).unwrap(),
+            "5\n2\n2.5\n"
+        );
+    }
+
+    #[test]
+    fn reading_uninitialized_variables_is_an_error() {
+        let run = |source: &str| {
+            let mut scanner = scanner::Scanner::new(source);
+            let tokens = scanner.scan_tokens();
+            let mut parser = parser::Parser::new(tokens);
+            let ast = parser.parse().unwrap();
+
+            let buffer = SharedBuffer(Rc::new(RefCell::new(vec![])));
+            let mut interpreter = Interpreter::new();
+            interpreter.set_output(Box::new(buffer.clone()));
+            let result = interpreter.interpret(&ast);
+            let output = String::from_utf8(buffer.0.borrow().clone()).unwrap();
+            (result, output)
+        };
+
+        let (result, output) = run("var a = nil; print a; var b; b = 1; print b;");
+        assert!(result.is_ok());
+        assert_eq!(output, "nil\n1\n");
+
+        let (result, output) = run("var a; print a;");
+        assert_eq!(
+            result.unwrap_err().message,
+            "Variable \"a\" read before being assigned a value."
+        );
+        assert_eq!(output, "");
+        assert!(run("var a; var b = a;").0.is_err());
+        assert!(run("fun f() { var a; return a; } f();").0.is_err());
+
+        // ?? treats an uninitialized variable as nil
+        let (result, output) = run("var a; a ??= 2; print a; var b; print b ?? 3;");
+        assert!(result.is_ok());
+        assert_eq!(output, "2\n3\n");
+    }
+
+    #[test]
+    fn print_precision_formats_numbers() {
+        let mut scanner = scanner::Scanner::new(
+            r#"print :2 3.14159; write :0 2.6; print ""; print :3 "pi"; print :1 nil; print 3.14159;"#,
+        );
+        let tokens = scanner.scan_tokens();
+        let mut parser = parser::Parser::new(tokens);
+        let ast = parser.parse().unwrap();
+
+        let buffer = SharedBuffer(Rc::new(RefCell::new(vec![])));
+        let mut interpreter = Interpreter::new();
+        interpreter.set_output(Box::new(buffer.clone()));
+        interpreter.interpret(&ast).unwrap();
+        assert_eq!(
+            String::from_utf8(buffer.0.borrow().clone()).unwrap(),
+            "3.14\n3\npi\nnil\n3.14159\n"
+        );
+    }
 }
@@ -0,0 +1,333 @@
+use std::collections::HashMap;
+
+use crate::error::RuntimeError;
+use crate::interpreter::Result;
+use crate::object::LoxObject;
+
+/// Renders `value` as JSON text. Lists and sets become arrays; maps and instance
+/// fields become objects. Functions, classes and non-finite numbers can't be
+/// represented, nor can values which contain themselves.
+pub fn stringify(value: &LoxObject) -> Result<String> {
+    stringify_value(value, &mut vec![])
+}
+
+// Returns `s` as a quoted JSON string literal.
+fn quote(s: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            '\u{08}' => quoted.push_str("\\b"),
+            '\u{0c}' => quoted.push_str("\\f"),
+            c if (c as u32) < 0x20 => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+// Renders `value` as JSON text. Map keys and instance fields are sorted so output
+// is stable. `writing` holds the containers currently being rendered so that
+// cycles are reported rather than recursing forever.
+fn stringify_value(value: &LoxObject, writing: &mut Vec<LoxObject>) -> Result<String> {
+    let object_json =
+        |entries: &HashMap<String, LoxObject>, writing: &mut Vec<LoxObject>| -> Result<String> {
+            let mut members = vec![];
+            let mut keys: Vec<&String> = entries.keys().collect();
+            keys.sort();
+            for key in keys {
+                members.push(format!(
+                    "{}:{}",
+                    quote(key),
+                    stringify_value(&entries[key], writing)?
+                ));
+            }
+            Ok(format!("{{{}}}", members.join(",")))
+        };
+
+    match value {
+        LoxObject::Boolean(b) => return Ok(b.to_string()),
+        LoxObject::Nil => return Ok(String::from("null")),
+        LoxObject::Number(n) => {
+            return if n.is_finite() {
+                Ok(n.to_string())
+            } else {
+                Err(RuntimeError::with_message(&format!(
+                    "json_stringify() cannot represent \"{}\" in JSON.",
+                    value
+                )))
+            }
+        }
+        LoxObject::Str(s) => return Ok(quote(s)),
+        LoxObject::Instance(_) | LoxObject::List(_) | LoxObject::Map(_) | LoxObject::Set(_) => {}
+        _ => {
+            return Err(RuntimeError::with_message(&format!(
+                "json_stringify() cannot represent \"{}\" in JSON.",
+                value
+            )))
+        }
+    }
+
+    if writing.iter().any(|v| v.is_identical(value)) {
+        return Err(RuntimeError::with_message(
+            "json_stringify() cannot serialize a value which contains itself.",
+        ));
+    }
+
+    writing.push(value.clone());
+    let json = match value {
+        LoxObject::Instance(instance) => object_json(&instance.fields(), writing)?,
+        LoxObject::List(list) => {
+            let mut elements = vec![];
+            for element in list.borrow().iter() {
+                elements.push(stringify_value(element, writing)?);
+            }
+            format!("[{}]", elements.join(","))
+        }
+        LoxObject::Map(map) => object_json(&map.borrow(), writing)?,
+        LoxObject::Set(set) => {
+            let mut elements = vec![];
+            for element in set.borrow().iter() {
+                elements.push(stringify_value(element, writing)?);
+            }
+            format!("[{}]", elements.join(","))
+        }
+        _ => unreachable!(),
+    };
+    writing.pop();
+    Ok(json)
+}
+
+// --------------------------------------------------------------------------------------------------------------------
+
+/// Parses JSON text into Lox values: objects become maps, arrays become lists.
+pub fn parse(text: &str) -> Result<LoxObject> {
+    let mut parser = JsonParser {
+        chars: text.chars().collect(),
+        current: 0,
+    };
+    parser.skip_whitespace();
+    let value = parser.value()?;
+    parser.skip_whitespace();
+    if parser.current < parser.chars.len() {
+        return Err(parser.error("Unexpected trailing characters"));
+    }
+    Ok(value)
+}
+
+struct JsonParser {
+    chars: Vec<char>,
+    current: usize,
+}
+
+impl JsonParser {
+    fn value(&mut self) -> Result<LoxObject> {
+        match self.peek() {
+            Some('{') => self.object(),
+            Some('[') => self.array(),
+            Some('"') => Ok(LoxObject::Str(self.string()?)),
+            Some('t') => self.keyword("true", LoxObject::Boolean(true)),
+            Some('f') => self.keyword("false", LoxObject::Boolean(false)),
+            Some('n') => self.keyword("null", LoxObject::Nil),
+            Some(c) if c == '-' || c.is_ascii_digit() => self.number(),
+            Some(_) => Err(self.error("unexpected character")),
+            None => Err(self.error("unexpected end of input")),
+        }
+    }
+
+    fn object(&mut self) -> Result<LoxObject> {
+        self.expect('{')?;
+        let mut entries = HashMap::new();
+        self.skip_whitespace();
+        if !self.match_char('}') {
+            loop {
+                self.skip_whitespace();
+                if self.peek() != Some('"') {
+                    return Err(self.error("expected a string key"));
+                }
+                let key = self.string()?;
+                self.skip_whitespace();
+                self.expect(':')?;
+                self.skip_whitespace();
+                let value = self.value()?;
+                entries.insert(key, value);
+                self.skip_whitespace();
+                if self.match_char('}') {
+                    break;
+                }
+                self.expect(',')?;
+            }
+        }
+        Ok(LoxObject::new_map(entries))
+    }
+
+    fn array(&mut self) -> Result<LoxObject> {
+        self.expect('[')?;
+        let mut elements = vec![];
+        self.skip_whitespace();
+        if !self.match_char(']') {
+            loop {
+                self.skip_whitespace();
+                elements.push(self.value()?);
+                self.skip_whitespace();
+                if self.match_char(']') {
+                    break;
+                }
+                self.expect(',')?;
+            }
+        }
+        Ok(LoxObject::new_list(elements))
+    }
+
+    fn string(&mut self) -> Result<String> {
+        self.expect('"')?;
+        let mut s = String::new();
+        loop {
+            match self.advance() {
+                Some('"') => return Ok(s),
+                Some('\\') => match self.advance() {
+                    Some('"') => s.push('"'),
+                    Some('\\') => s.push('\\'),
+                    Some('/') => s.push('/'),
+                    Some('b') => s.push('\u{08}'),
+                    Some('f') => s.push('\u{0c}'),
+                    Some('n') => s.push('\n'),
+                    Some('r') => s.push('\r'),
+                    Some('t') => s.push('\t'),
+                    Some('u') => s.push(self.unicode_escape()?),
+                    _ => return Err(self.error("invalid escape sequence")),
+                },
+                Some(c) if (c as u32) < 0x20 => {
+                    return Err(self.error("unescaped control character in string"))
+                }
+                Some(c) => s.push(c),
+                None => return Err(self.error("unterminated string")),
+            }
+        }
+    }
+
+    // Reads the hex digits following "\u", combining surrogate pairs.
+    fn unicode_escape(&mut self) -> Result<char> {
+        let high = self.hex4()?;
+        let code = if (0xD800..0xDC00).contains(&high) {
+            if !(self.match_char('\\') && self.match_char('u')) {
+                return Err(self.error("expected a low surrogate escape"));
+            }
+            let low = self.hex4()?;
+            if !(0xDC00..0xE000).contains(&low) {
+                return Err(self.error("invalid low surrogate"));
+            }
+            0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+        } else {
+            high
+        };
+        std::char::from_u32(code).ok_or_else(|| self.error("invalid unicode escape"))
+    }
+
+    fn hex4(&mut self) -> Result<u32> {
+        let mut value = 0;
+        for _ in 0..4 {
+            match self.advance().and_then(|c| c.to_digit(16)) {
+                Some(digit) => value = value * 16 + digit,
+                None => return Err(self.error("invalid unicode escape")),
+            }
+        }
+        Ok(value)
+    }
+
+    fn number(&mut self) -> Result<LoxObject> {
+        let start = self.current;
+        self.match_char('-');
+        if !self.match_char('0') && !self.digits() {
+            return Err(self.error("expected a digit"));
+        }
+        if self.match_char('.') && !self.digits() {
+            return Err(self.error("expected a digit after the decimal point"));
+        }
+        if self.match_char('e') || self.match_char('E') {
+            if !self.match_char('+') {
+                self.match_char('-');
+            }
+            if !self.digits() {
+                return Err(self.error("expected a digit in the exponent"));
+            }
+        }
+
+        let text: String = self.chars[start..self.current].iter().collect();
+        match text.parse::<f64>() {
+            Ok(n) => Ok(LoxObject::Number(n)),
+            Err(_) => Err(self.error("invalid number")),
+        }
+    }
+
+    // Consumes a run of digits, returning false if there were none.
+    fn digits(&mut self) -> bool {
+        let start = self.current;
+        while let Some(c) = self.peek() {
+            if !c.is_ascii_digit() {
+                break;
+            }
+            self.current += 1;
+        }
+        self.current > start
+    }
+
+    fn keyword(&mut self, keyword: &str, value: LoxObject) -> Result<LoxObject> {
+        for expected in keyword.chars() {
+            if !self.match_char(expected) {
+                return Err(self.error(&format!("expected \"{}\"", keyword)));
+            }
+        }
+        Ok(value)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(c) = self.peek() {
+            if !matches!(c, ' ' | '\t' | '\n' | '\r') {
+                break;
+            }
+            self.current += 1;
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.current).copied()
+    }
+
+    fn advance(&mut self) -> Option<char> {
+        let c = self.peek();
+        if c.is_some() {
+            self.current += 1;
+        }
+        c
+    }
+
+    fn match_char(&mut self, expected: char) -> bool {
+        if self.peek() == Some(expected) {
+            self.current += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<()> {
+        if self.match_char(expected) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected \"{}\"", expected)))
+        }
+    }
+
+    fn error(&self, message: &str) -> RuntimeError {
+        RuntimeError::with_message(&format!(
+            "json_parse() {} at position {}.",
+            message, self.current
+        ))
+    }
+}
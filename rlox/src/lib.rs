@@ -9,23 +9,43 @@ mod environment;
 mod error;
 mod function;
 mod interpreter;
+mod json;
 mod natives;
 mod object;
 mod parser;
 mod resolver;
 mod scanner;
+mod set;
 
-use crate::ast::Stmt;
 use crate::ast_printer::AstPrinter;
+use crate::error::RuntimeError;
 use crate::interpreter::Interpreter;
 use crate::parser::Parser;
-use crate::resolver::Resolver;
+use crate::resolver::{Resolver, ResolverConfig};
 use crate::scanner::Scanner;
 
+pub use crate::ast::{CallableType, Expr, Stmt};
+pub use crate::error::{Diagnostic, Severity, StackFrame};
+pub use crate::object::LoxObject;
+pub use crate::scanner::{Literal, Token, TokenType};
+
 pub struct Lox {
     had_error: bool,
     had_runtime_error: bool,
     interpreter: Interpreter,
+    is_repl: bool,
+    step_limit: Option<usize>,
+    args: Vec<String>,
+    host_globals: Vec<(String, LoxObject)>,
+    backtrace: Vec<StackFrame>,
+    max_errors: usize,
+    strict: bool,
+    warn_on_shadowing: bool,
+    ieee_division: bool,
+    optional_semicolons: bool,
+    parenless_conditions: bool,
+    dump_tokens: bool,
+    no_resolve: bool,
 }
 
 impl Lox {
@@ -34,7 +54,117 @@ impl Lox {
             had_error: false,
             had_runtime_error: false,
             interpreter: Interpreter::new(),
+            is_repl: false,
+            step_limit: None,
+            args: vec![],
+            host_globals: vec![],
+            backtrace: vec![],
+            max_errors: parser::DEFAULT_MAX_ERRORS,
+            strict: false,
+            warn_on_shadowing: false,
+            ieee_division: false,
+            optional_semicolons: false,
+            parenless_conditions: false,
+            dump_tokens: false,
+            no_resolve: false,
+        }
+    }
+
+    /// Sets the number of parse errors reported before parsing gives up. Zero means
+    /// no limit.
+    pub fn set_max_errors(&mut self, max_errors: usize) {
+        self.max_errors = max_errors;
+    }
+
+    /// When set, dividing by zero yields Infinity, -Infinity or NaN as IEEE 754
+    /// specifies, rather than being a runtime error.
+    pub fn set_ieee_division(&mut self, ieee_division: bool) {
+        self.ieee_division = ieee_division;
+        self.interpreter.set_ieee_division(ieee_division);
+    }
+
+    /// When set, the ";" ending a statement may be omitted at the end of a line.
+    pub fn set_optional_semicolons(&mut self, optional: bool) {
+        self.optional_semicolons = optional;
+    }
+
+    /// When set, `if`, `while` and `for` conditions may omit their parentheses,
+    /// provided the body is a block.
+    pub fn set_allow_parenless_conditions(&mut self, allow: bool) {
+        self.parenless_conditions = allow;
+    }
+
+    /// When set, run prints the tokens scanned from its source instead of running it.
+    pub fn set_dump_tokens(&mut self, dump_tokens: bool) {
+        self.dump_tokens = dump_tokens;
+    }
+
+    /// When set, run skips the resolver pass, which is useful when debugging the
+    /// resolver: every variable is then looked up as a global, so locals, closures
+    /// and shadowing won't behave as they normally do.
+    pub fn set_no_resolve(&mut self, no_resolve: bool) {
+        self.no_resolve = no_resolve;
+    }
+
+    /// When set, resolver warnings are reported as errors, preventing execution.
+    pub fn set_strict(&mut self, strict: bool) {
+        self.strict = strict;
+    }
+
+    /// When set, declaring a local variable with the same name as one in an
+    /// enclosing local scope is warned about. Globals may be shadowed freely.
+    pub fn set_warn_on_shadowing(&mut self, warn: bool) {
+        self.warn_on_shadowing = warn;
+    }
+
+    /// Creates a Lox which reports a runtime error once `step_limit` statements
+    /// and expressions have been executed by a single run, bounding runaway scripts.
+    pub fn with_step_limit(step_limit: usize) -> Lox {
+        let mut lox = Lox::new();
+        lox.step_limit = Some(step_limit);
+        lox.interpreter.set_step_limit(lox.step_limit);
+        lox
+    }
+
+    /// Creates a Lox whose scripts can read `args` via the args() native.
+    pub fn with_args(args: Vec<String>) -> Lox {
+        let mut lox = Lox::new();
+        lox.args = args;
+        lox.interpreter.set_args(lox.args.clone());
+        lox
+    }
+
+    /// Discards all state left by previously run code, and clears the error flags.
+    /// Configuration and globals defined through `set_global` are kept.
+    pub fn reset(&mut self) {
+        let observer = self.interpreter.take_assign_observer();
+        self.interpreter = Interpreter::new();
+        self.interpreter.set_assign_observer(observer);
+        self.interpreter.set_step_limit(self.step_limit);
+        self.interpreter.set_ieee_division(self.ieee_division);
+        self.interpreter.set_args(self.args.clone());
+        for (name, value) in &self.host_globals {
+            self.interpreter.globals().define(name, value);
         }
+        self.had_error = false;
+        self.had_runtime_error = false;
+        self.backtrace.clear();
+    }
+
+    /// Calls `observer` with the name and new value of each variable defined by a var
+    /// statement or assigned by subsequently run code.
+    pub fn on_assign<F>(&mut self, observer: F)
+    where
+        F: FnMut(&str, &LoxObject) + 'static,
+    {
+        self.interpreter
+            .set_assign_observer(Some(Box::new(observer)));
+    }
+
+    /// Returns the calls in progress when the most recent runtime error occurred,
+    /// innermost first.
+    pub fn backtrace(&self) -> &[StackFrame] {
+        &self.backtrace
     }
 
     pub fn run_file(&mut self, file: &str, display_ast: bool) {
@@ -50,6 +180,7 @@ impl Lox {
     }
 
     pub fn run_prompt(&mut self, display_ast: bool) {
+        self.is_repl = true;
         loop {
             print!("> ");
             io::stdout().flush().unwrap();
@@ -62,38 +193,184 @@ impl Lox {
             if line.len() == 0 {
                 break;
             }
-            self.run(&line, display_ast);
+            if line == ":reset" {
+                self.interpreter.reset_globals();
+                continue;
+            }
+            if line == ":env" {
+                print!("{}", self.globals_listing());
+                continue;
+            }
+            if let Some(expression) = line.strip_prefix(":ast ") {
+                if let Some(ast) = self.expression_ast(expression) {
+                    print!("{}", ast);
+                }
+                continue;
+            }
+            self.run(line, display_ast);
             self.had_error = false;
         }
     }
 
+    /// Defines a global variable visible to subsequently run Lox code, allowing
+    /// host values to be injected before execution.
+    pub fn set_global(&mut self, name: &str, value: LoxObject) {
+        self.interpreter.globals().define(name, &value);
+        self.host_globals.push((name.to_owned(), value));
+    }
+
+    /// Evaluates a single expression, returning its value. Errors are reported
+    /// as they are for `run`, and yield None.
+    pub fn eval(&mut self, expression: &str) -> Option<LoxObject> {
+        let tokens = self.scan(expression)?;
+        let mut parser = self.parser(tokens);
+        match parser.parse_expression() {
+            Ok(expr) => match self.interpreter.evaluate(&expr) {
+                Ok(value) => Some(value),
+                Err(e) => {
+                    self.runtime_error(e);
+                    None
+                }
+            },
+            Err(e) => {
+                error::report::parse_error_at_token(&e.token, &e.message);
+                self.had_error = true;
+                None
+            }
+        }
+    }
+
     fn run(&mut self, source: &str, display_ast: bool) {
+        if self.dump_tokens {
+            for token in self.token_strings(source) {
+                println!("{}", token);
+            }
+            return;
+        }
+
+        let tokens = match self.scan(source) {
+            Some(tokens) => tokens,
+            None => return,
+        };
+        let mut parser = self.parser(tokens);
+        let (statements, errors) = parser.parse_all();
+        if !errors.is_empty() {
+            for e in errors {
+                error::report::parse_error_at_token(&e.token, &e.message);
+            }
+            self.had_error = true;
+        } else if display_ast {
+            self.display_ast(&statements);
+        } else if self.no_resolve {
+            self.run_statements(&statements);
+        } else {
+            self.run_ast(&statements);
+        }
+    }
+
+    /// Resolves and runs `statements`, as `run` does once it has parsed its source.
+    /// This allows hosts to build or transform an AST before running it. Tokens in
+    /// the AST need distinct ids, since resolved variables are looked up by them.
+    pub fn run_ast(&mut self, statements: &Vec<Box<Stmt>>) {
+        match self.resolve(statements) {
+            Ok(()) => {
+                self.run_statements(statements);
+            }
+            Err(e) => {
+                error::report::resolver_error(&e);
+                self.had_error = true;
+            }
+        }
+    }
+
+    /// Scans, parses and resolves `source` without running it, returning every problem
+    /// found rather than printing them. Parsing continues past errors, so several may be
+    /// reported, but only the first resolver error is.
+    pub fn diagnose(&mut self, source: &str) -> Vec<Diagnostic> {
         let mut scanner = Scanner::new(source);
         let tokens = scanner.scan_tokens();
+        let mut diagnostics: Vec<Diagnostic> =
+            scanner.errors().iter().map(Diagnostic::from).collect();
+
+        let mut parser = self.parser(tokens);
+        let (statements, errors) = parser.parse_all();
+        diagnostics.extend(errors.iter().map(Diagnostic::from));
+
+        // resolve into a scratch interpreter, so self's state is untouched
+        let mut interpreter = Interpreter::new();
+        let mut resolver = Resolver::new(&mut interpreter);
+        resolver.set_config(self.resolver_config());
+        if let Err(e) = resolver.resolve(&statements) {
+            diagnostics.push(Diagnostic::resolver_error(&e));
+        }
+        diagnostics.extend(resolver.warnings().iter().map(Diagnostic::resolver_warning));
+
+        diagnostics.sort_by_key(|d| d.line);
+        diagnostics
+    }
+
+    fn runtime_error(&mut self, e: RuntimeError) {
+        error::report::runtime_error(&e);
+        self.had_runtime_error = true;
+        self.backtrace = e.backtrace;
+    }
+
+    // Creates a parser for `tokens`, configured as set on self.
+    fn parser(&self, tokens: Vec<Token>) -> Parser {
         let mut parser = Parser::new(tokens);
-        match parser.parse() {
-            Ok(statements) => {
-                if display_ast {
-                    self.display_ast(&statements);
-                } else {
-                    match self.resolve(&statements) {
-                        Ok(()) => {
-                            self.run_statements(&statements);
-                        }
-                        Err(e) => {
-                            error::report::resolver_error(&e);
-                            self.had_error = true;
-                        }
-                    }
-                }
+        parser.set_max_errors(self.max_errors);
+        parser.set_optional_semicolons(self.optional_semicolons);
+        parser.set_allow_parenless_conditions(self.parenless_conditions);
+        parser
+    }
+
+    // Scans `source`, reporting any errors found, and returning None if there were any.
+    fn scan(&mut self, source: &str) -> Option<Vec<Token>> {
+        let mut scanner = Scanner::new(source);
+        let tokens = scanner.scan_tokens();
+        for e in scanner.errors() {
+            error::report::scan_error(e);
+            self.had_error = true;
+        }
+        if scanner.errors().is_empty() {
+            Some(tokens)
+        } else {
+            None
+        }
+    }
+
+    fn token_strings(&mut self, source: &str) -> Vec<String> {
+        let tokens = self.scan(source).unwrap_or_default();
+        tokens.iter().map(|t| t.to_string()).collect()
+    }
+
+    // Returns the parse tree of a single expression, or None if it fails to parse.
+    fn expression_ast(&mut self, expression: &str) -> Option<String> {
+        let tokens = self.scan(expression)?;
+        let mut parser = self.parser(tokens);
+        match parser.parse_expression() {
+            Ok(expression) => {
+                let mut ast_printer = AstPrinter::new();
+                Some(ast_printer.generate(&vec![Box::new(Stmt::Expression { expression })]))
             }
             Err(e) => {
                 error::report::parse_error_at_token(&e.token, &e.message);
                 self.had_error = true;
+                None
             }
         }
     }
 
+    // Returns a "name = value" line for each global, natives included, sorted by name.
+    fn globals_listing(&self) -> String {
+        self.interpreter
+            .globals()
+            .values()
+            .iter()
+            .map(|(name, value)| format!("{} = {}\n", name, value))
+            .collect()
+    }
+
     fn display_ast(&mut self, statements: &Vec<Box<Stmt>>) {
         let mut ast_printer = AstPrinter::new();
 
@@ -112,10 +389,10 @@ impl Lox {
                 Stmt::Expression { expression } => {
                     did_evaluate_single_expression = true;
                     match self.interpreter.evaluate(&expression) {
+                        Ok(r) if self.is_repl => println!("=> {}", repl_result_string(&r)),
                         Ok(r) => println!("{}", r),
                         Err(e) => {
-                            error::report::runtime_error(&e);
-                            self.had_runtime_error = true;
+                            self.runtime_error(e);
                         }
                     }
                 }
@@ -127,15 +404,434 @@ impl Lox {
             match self.interpreter.interpret(statements) {
                 Ok(()) => (),
                 Err(e) => {
-                    error::report::runtime_error(&e);
-                    self.had_runtime_error = true;
+                    self.runtime_error(e);
                 }
             }
         }
     }
 
-    fn resolve(&mut self, statements: &Vec<Box<Stmt>>) -> resolver::Result<()> {
+    fn resolver_config(&self) -> ResolverConfig {
+        if self.strict {
+            ResolverConfig::strict()
+        } else if self.warn_on_shadowing {
+            ResolverConfig {
+                shadowing: resolver::Severity::Warning,
+                ..ResolverConfig::default()
+            }
+        } else {
+            ResolverConfig::default()
+        }
+    }
+
+    fn resolve(&mut self, statements: &[Box<Stmt>]) -> resolver::Result<()> {
+        let config = self.resolver_config();
         let mut r = Resolver::new(&mut self.interpreter);
-        r.resolve(statements)
+        r.set_config(config);
+        let result = r.resolve(statements);
+        for warning in r.warnings() {
+            error::report::resolver_warning(warning);
+        }
+        result
+    }
+}
+
+// Formats the result of an expression evaluated in the REPL along with its type.
+fn repl_result_string(value: &LoxObject) -> String {
+    format!("{} : {}", value, value.type_name())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    #[test]
+    fn seeded_globals_are_visible_to_lox() {
+        let mut lox = Lox::new();
+        lox.set_global("answer", LoxObject::Number(42.0));
+        lox.set_global("greeting", LoxObject::Str(String::from("Hello")));
+
+        assert_eq!(lox.eval("answer + 1"), Some(LoxObject::Number(43.0)));
+        assert_eq!(
+            lox.eval("greeting"),
+            Some(LoxObject::Str(String::from("Hello")))
+        );
+
+        lox.run("var doubled = answer * 2;", false);
+        assert_eq!(lox.eval("doubled"), Some(LoxObject::Number(84.0)));
+    }
+
+    #[test]
+    fn token_strings_display_each_scanned_token() {
+        let mut lox = Lox::new();
+        assert_eq!(
+            lox.token_strings("var a = 1;\nprint a;"),
+            vec![
+                "Var lexeme: \"var\" line: 1 id: 0",
+                "Identifier lexeme: \"a\" line: 1 id: 1",
+                "Equal lexeme: \"=\" line: 1 id: 2",
+                "Number lexeme: \"1\" literal: 1 line: 1 id: 3",
+                "Semicolon lexeme: \";\" line: 1 id: 4",
+                "Print lexeme: \"print\" line: 2 id: 5",
+                "Identifier lexeme: \"a\" line: 2 id: 6",
+                "Semicolon lexeme: \";\" line: 2 id: 7",
+                "Eof lexeme: \"\" line: 2 id: 8",
+            ]
+        );
+    }
+
+    #[test]
+    fn resetting_globals_keeps_only_natives() {
+        let mut lox = Lox::new();
+        lox.run("var a = 1;", false);
+        assert_eq!(lox.eval("a"), Some(LoxObject::Number(1.0)));
+
+        lox.interpreter.reset_globals();
+        assert_eq!(lox.eval("a"), None);
+        assert!(lox.eval("clock").is_some());
+
+        lox.run("var a = 2;", false);
+        assert_eq!(lox.eval("a"), Some(LoxObject::Number(2.0)));
+    }
+
+    #[test]
+    fn expression_ast_prints_parse_tree() {
+        let mut lox = Lox::new();
+        assert_eq!(
+            lox.expression_ast("1 + 2 * 3"),
+            Some(String::from("(expression (+ 1 (* 2 3)))\n"))
+        );
+        assert_eq!(lox.expression_ast("1 +"), None);
+    }
+
+    #[test]
+    fn globals_listing_shows_sorted_globals() {
+        let mut lox = Lox::new();
+        lox.run("var b = \"two\"; var a = 1; var c; var d = nil;", false);
+        let listing = lox.globals_listing();
+        let lines: Vec<&str> = listing.lines().collect();
+        assert!(lines.contains(&"a = 1"));
+        assert!(lines.contains(&"b = two"));
+        assert!(lines.contains(&"c = <uninitialized>"));
+        assert!(lines.contains(&"d = nil"));
+        assert!(lines.contains(&"clock = <callable arity 0>"));
+
+        let mut sorted = lines.clone();
+        sorted.sort();
+        assert_eq!(lines, sorted);
+    }
+
+    #[test]
+    fn args_are_visible_to_lox() {
+        let mut lox = Lox::with_args(vec![String::from("one"), String::from("two")]);
+        assert_eq!(
+            lox.eval("args()"),
+            Some(LoxObject::new_list(vec![
+                LoxObject::Str(String::from("one")),
+                LoxObject::Str(String::from("two"))
+            ]))
+        );
+        assert_eq!(Lox::new().eval("args()"), Some(LoxObject::new_list(vec![])));
+    }
+
+    #[test]
+    fn step_limit_stops_runaway_loops() {
+        let mut lox = Lox::with_step_limit(1000);
+        lox.run("while (true) {}", false);
+        assert!(lox.had_runtime_error);
+
+        // each run has its own budget
+        let mut lox = Lox::with_step_limit(100);
+        for _ in 0..10 {
+            lox.run("var i = 0; while (i < 5) { i = i + 1; }", false);
+        }
+        assert!(!lox.had_runtime_error);
+    }
+
+    #[test]
+    fn reset_discards_script_state_but_keeps_host_globals() {
+        let mut lox = Lox::with_args(vec![String::from("one")]);
+        lox.set_global("answer", LoxObject::Number(42.0));
+        lox.run("var a = 1; var b = nil + 1;", false);
+        assert!(lox.had_runtime_error);
+        assert_eq!(lox.eval("a"), Some(LoxObject::Number(1.0)));
+
+        lox.reset();
+        assert!(!lox.had_runtime_error);
+        assert_eq!(lox.eval("a"), None);
+        assert_eq!(lox.eval("answer"), Some(LoxObject::Number(42.0)));
+        assert_eq!(
+            lox.eval("args()"),
+            Some(LoxObject::new_list(vec![LoxObject::Str(String::from(
+                "one"
+            ))]))
+        );
+    }
+
+    #[test]
+    fn repl_results_show_their_type() {
+        assert_eq!(repl_result_string(&LoxObject::Number(3.0)), "3 : number");
+        assert_eq!(
+            repl_result_string(&LoxObject::Str(String::from("hi"))),
+            "hi : string"
+        );
+        assert_eq!(repl_result_string(&LoxObject::Nil), "nil : nil");
+
+        let mut lox = Lox::new();
+        let clock = lox.eval("clock").unwrap();
+        assert_eq!(repl_result_string(&clock), "<callable arity 0> : function");
+    }
+
+    #[test]
+    fn diagnose_collects_errors_without_running() {
+        let mut lox = Lox::new();
+        let diagnostics = lox.diagnose("var a = ;\n{\n    var b = 1;\n}\nprint \"not run\";");
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(
+            (diagnostics[0].line, diagnostics[0].severity),
+            (1, Severity::Error)
+        );
+        assert_eq!(
+            (diagnostics[1].line, diagnostics[1].severity),
+            (3, Severity::Error)
+        );
+        assert_eq!(
+            diagnostics[1].message,
+            "Variable \"b\" defined but never accessed"
+        );
+
+        // the scanner error is reported alongside the parse error it causes
+        let diagnostics = lox.diagnose("var a = 1;\nvar s = \"open;\n");
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(diagnostics[0].message, "Unterminated string");
+
+        let diagnostics = lox.diagnose("var a = 1;\nif (a = 2) print a;");
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            (diagnostics[0].line, diagnostics[0].severity),
+            (2, Severity::Warning)
+        );
+
+        assert!(lox.diagnose("var a = 1; print a;").is_empty());
+        assert!(!lox.had_error);
+
+        lox.set_strict(true);
+        let diagnostics = lox.diagnose("var a = 1;\nif (a = 2) print a;");
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            (diagnostics[0].line, diagnostics[0].severity),
+            (2, Severity::Error)
+        );
+    }
+
+    #[test]
+    fn scan_errors_stop_the_program_running() {
+        let mut lox = Lox::new();
+        lox.run("var ran = true; @", false);
+        assert!(lox.had_error);
+        assert_eq!(lox.eval("ran"), None);
+        assert_eq!(lox.eval("1 @"), None);
+    }
+
+    #[test]
+    fn optional_semicolons_can_be_enabled() {
+        let mut lox = Lox::new();
+        lox.run("var a = 1\nvar b = a + 1", false);
+        assert!(lox.had_error);
+
+        let mut lox = Lox::new();
+        lox.set_optional_semicolons(true);
+        lox.run("var a = 1\nvar b = a + 1", false);
+        assert!(!lox.had_error);
+        assert_eq!(lox.eval("b"), Some(LoxObject::Number(2.0)));
+    }
+
+    #[test]
+    fn parenless_conditions_can_be_enabled() {
+        let program = "var a = 0; while a < 3 { a = a + 1; }";
+        let mut lox = Lox::new();
+        lox.run(program, false);
+        assert!(lox.had_error);
+
+        let mut lox = Lox::new();
+        lox.set_allow_parenless_conditions(true);
+        lox.run(program, false);
+        assert!(!lox.had_error);
+        assert_eq!(lox.eval("a"), Some(LoxObject::Number(3.0)));
+    }
+
+    #[test]
+    fn ieee_division_survives_reset() {
+        let mut lox = Lox::new();
+        lox.run("var a = 1 / 0;", false);
+        assert!(lox.had_runtime_error);
+
+        let mut lox = Lox::new();
+        lox.set_ieee_division(true);
+        lox.run("var a = 1 / 0;", false);
+        assert!(!lox.had_runtime_error);
+        assert_eq!(lox.eval("a"), Some(LoxObject::Number(f64::INFINITY)));
+
+        lox.reset();
+        assert_eq!(
+            lox.eval("-1 / 0"),
+            Some(LoxObject::Number(f64::NEG_INFINITY))
+        );
+    }
+
+    #[test]
+    fn strict_mode_refuses_to_run_programs_with_warnings() {
+        let program = "var a = 1; if (a = 2) a = 3;";
+        let mut lox = Lox::new();
+        lox.run(program, false);
+        assert!(!lox.had_error);
+
+        let mut lox = Lox::new();
+        lox.set_strict(true);
+        lox.run(program, false);
+        assert!(lox.had_error);
+    }
+
+    #[test]
+    fn shadowing_warnings_can_be_enabled() {
+        let program = "{ var x = 1; { var x = 2; print x; } print x; }";
+        let mut lox = Lox::new();
+        assert!(lox.diagnose(program).is_empty());
+
+        lox.set_warn_on_shadowing(true);
+        let diagnostics = lox.diagnose(program);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].severity, Severity::Warning);
+        lox.run(program, false);
+        assert!(!lox.had_error);
+    }
+
+    #[test]
+    fn globals_run_identically_without_resolution() {
+        let program = "var a = 1; var b = 2; a = a + b; var c = a * 10;";
+        for no_resolve in [false, true] {
+            let mut lox = Lox::new();
+            lox.set_no_resolve(no_resolve);
+            lox.run(program, false);
+            assert!(!lox.had_error && !lox.had_runtime_error);
+            assert_eq!(lox.eval("a"), Some(LoxObject::Number(3.0)));
+            assert_eq!(lox.eval("c"), Some(LoxObject::Number(30.0)));
+        }
+    }
+
+    #[test]
+    fn runtime_errors_record_the_call_stack() {
+        let mut lox = Lox::new();
+        lox.run(
+            "fun c() {\n  return nil + 1;\n}\nfun b() {\n  c();\n}\nfun a() {\n  b();\n}\na();",
+            false,
+        );
+        assert!(lox.had_runtime_error);
+        assert_eq!(
+            lox.backtrace(),
+            &[
+                StackFrame::new("c", 5),
+                StackFrame::new("b", 8),
+                StackFrame::new("a", 10)
+            ]
+        );
+
+        // the stack unwinds, so a later error only reports its own frames
+        lox.run("b();", false);
+        assert_eq!(
+            lox.backtrace(),
+            &[StackFrame::new("c", 5), StackFrame::new("b", 1)]
+        );
+
+        // returned calls keep their frames too
+        let mut lox = Lox::new();
+        lox.run(
+            "fun c() {\n  return nil + 1;\n}\nfun b() {\n  return c();\n}\nfun a() {\n  b();\n}\na();",
+            false,
+        );
+        assert!(lox.had_runtime_error);
+        assert_eq!(
+            lox.backtrace(),
+            &[
+                StackFrame::new("c", 5),
+                StackFrame::new("b", 8),
+                StackFrame::new("a", 10)
+            ]
+        );
+    }
+
+    #[test]
+    fn run_ast_runs_hand_built_statements() {
+        let token =
+            |token_type, lexeme: &str, id| Token::new(token_type, lexeme.to_owned(), None, 1, id);
+        let number = |n| {
+            Box::new(Expr::Literal {
+                value: Literal::Number(n),
+            })
+        };
+
+        // var a = 1 + 2; { var b = a; a = b * 10; }
+        let statements = vec![
+            Box::new(Stmt::Var {
+                name: token(TokenType::Identifier, "a", 0),
+                initializer: Some(Box::new(Expr::Binary {
+                    left: number(1.0),
+                    operator: token(TokenType::Plus, "+", 1),
+                    right: number(2.0),
+                })),
+            }),
+            Box::new(Stmt::Block {
+                statements: vec![
+                    Box::new(Stmt::Var {
+                        name: token(TokenType::Identifier, "b", 2),
+                        initializer: Some(Box::new(Expr::Variable {
+                            name: token(TokenType::Identifier, "a", 3),
+                        })),
+                    }),
+                    Box::new(Stmt::Expression {
+                        expression: Box::new(Expr::Assign {
+                            name: token(TokenType::Identifier, "a", 4),
+                            value: Box::new(Expr::Binary {
+                                left: Box::new(Expr::Variable {
+                                    name: token(TokenType::Identifier, "b", 5),
+                                }),
+                                operator: token(TokenType::Star, "*", 6),
+                                right: number(10.0),
+                            }),
+                        }),
+                    }),
+                ],
+            }),
+        ];
+
+        let mut lox = Lox::new();
+        lox.run_ast(&statements);
+        assert!(!lox.had_error && !lox.had_runtime_error);
+        assert_eq!(lox.eval("a"), Some(LoxObject::Number(30.0)));
+    }
+
+    #[test]
+    fn on_assign_observes_assignments_in_order() {
+        let observed = Rc::new(RefCell::new(vec![]));
+        let mut lox = Lox::new();
+        let collector = observed.clone();
+        lox.on_assign(move |name, value| {
+            collector.borrow_mut().push(format!("{} = {}", name, value))
+        });
+
+        lox.run(
+            "var a = 1; a = a + 1; var b = a * 2; fun f() { var c = b; c = c + 1; return c; } f();",
+            false,
+        );
+        assert_eq!(
+            *observed.borrow(),
+            vec!["a = 1", "a = 2", "b = 4", "c = 4", "c = 5"]
+        );
+
+        // the observer survives a reset
+        lox.reset();
+        lox.run("var d;", false);
+        assert_eq!(observed.borrow().last().unwrap(), "d = nil");
     }
 }
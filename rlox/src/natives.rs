@@ -1,8 +1,277 @@
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::collections::HashMap;
+use std::sync::OnceLock;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+use std::{cell::RefCell, rc::Rc};
 
 use crate::callable;
-use crate::interpreter::{InterpretResult, Interpreter};
+use crate::error::RuntimeError;
+use crate::interpreter::{self, InterpretResult, InterpretResultStatus, Interpreter, Result};
+use crate::json;
 use crate::object;
+use crate::set::LoxSet;
+
+/// Returns the numeric value of `value`, or a RuntimeError naming the native
+/// function `name` if the value isn't a number.
+pub fn expect_number(value: &object::LoxObject, name: &str) -> Result<f64> {
+    match value {
+        object::LoxObject::Number(n) => Ok(*n),
+        _ => Err(RuntimeError::with_message(&format!(
+            "{}() expects a number argument, received \"{}\".",
+            name, value
+        ))),
+    }
+}
+
+/// Returns the integral value of `value`, or a RuntimeError naming the native
+/// function `name` if the value isn't a whole number.
+pub fn expect_integer(value: &object::LoxObject, name: &str) -> Result<f64> {
+    let n = expect_number(value, name)?;
+    if n.fract() == 0.0 {
+        Ok(n)
+    } else {
+        Err(RuntimeError::with_message(&format!(
+            "{}() expects an integer argument, received \"{}\".",
+            name, value
+        )))
+    }
+}
+
+/// Returns the numeric value of `value` for use as a multiple to round to, or a
+/// RuntimeError naming the native function `name` if it isn't a non-zero number.
+fn expect_multiple(value: &object::LoxObject, name: &str) -> Result<f64> {
+    let multiple = expect_number(value, name)?;
+    if multiple == 0.0 {
+        Err(RuntimeError::with_message(&format!(
+            "{}() expects a non-zero multiple.",
+            name
+        )))
+    } else {
+        Ok(multiple)
+    }
+}
+
+/// Returns the string value of `value`, or a RuntimeError naming the native
+/// function `name` if the value isn't a string.
+pub fn expect_string(value: &object::LoxObject, name: &str) -> Result<String> {
+    match value {
+        object::LoxObject::Str(s) => Ok(s.clone()),
+        _ => Err(RuntimeError::with_message(&format!(
+            "{}() expects a string argument, received \"{}\".",
+            name, value
+        ))),
+    }
+}
+
+/// Returns the list referred to by `value`, or a RuntimeError naming the native
+/// function `name` if the value isn't a list.
+pub fn expect_list(
+    value: &object::LoxObject,
+    name: &str,
+) -> Result<Rc<RefCell<Vec<object::LoxObject>>>> {
+    match value {
+        object::LoxObject::List(l) => Ok(l.clone()),
+        _ => Err(RuntimeError::with_message(&format!(
+            "{}() expects a list argument, received \"{}\".",
+            name, value
+        ))),
+    }
+}
+
+/// Returns the map referred to by `value`, or a RuntimeError naming the native
+/// function `name` if the value isn't a map.
+pub fn expect_map(
+    value: &object::LoxObject,
+    name: &str,
+) -> Result<Rc<RefCell<HashMap<String, object::LoxObject>>>> {
+    match value {
+        object::LoxObject::Map(m) => Ok(m.clone()),
+        _ => Err(RuntimeError::with_message(&format!(
+            "{}() expects a map argument, received \"{}\".",
+            name, value
+        ))),
+    }
+}
+
+/// Returns the set referred to by `value`, or a RuntimeError naming the native
+/// function `name` if the value isn't a set.
+pub fn expect_set(value: &object::LoxObject, name: &str) -> Result<Rc<RefCell<LoxSet>>> {
+    match value {
+        object::LoxObject::Set(s) => Ok(s.clone()),
+        _ => Err(RuntimeError::with_message(&format!(
+            "{}() expects a set argument, received \"{}\".",
+            name, value
+        ))),
+    }
+}
+
+/// Returns the arity of the callable `value`, or a RuntimeError naming the native
+/// function `name` if the value isn't callable.
+fn expect_callable(value: &object::LoxObject, name: &str) -> Result<usize> {
+    match value {
+        object::LoxObject::Callable(callable) => Ok(callable.borrow().arity()),
+        _ => Err(RuntimeError::with_message(&format!(
+            "{}() expects a function argument, received \"{}\".",
+            name, value
+        ))),
+    }
+}
+
+/// Returns Ok if `value` may be stored in a set, or a RuntimeError naming the
+/// native function `name` otherwise.
+fn expect_hashable(value: &object::LoxObject, name: &str) -> Result<()> {
+    if LoxSet::is_hashable(value) {
+        Ok(())
+    } else {
+        Err(RuntimeError::with_message(&format!(
+            "{}() expects a number, string, boolean or nil member, received \"{}\".",
+            name, value
+        )))
+    }
+}
+
+// Splits `s` into single character strings.
+/// The largest result, in bytes, which natives building strings or lists will allocate.
+const MAX_ALLOCATION_BYTES: usize = 1 << 30;
+
+/// Returns `count` as a size, or a RuntimeError naming the native function `name` if
+/// `count` items of `item_size` bytes each would exceed MAX_ALLOCATION_BYTES.
+fn expect_allocation(count: f64, item_size: usize, name: &str) -> Result<usize> {
+    match (count as usize).checked_mul(item_size) {
+        Some(bytes) if bytes <= MAX_ALLOCATION_BYTES => Ok(count as usize),
+        _ => Err(RuntimeError::with_message(&format!(
+            "{}() result would be too large, at {} items.",
+            name, count
+        ))),
+    }
+}
+
+fn split_chars(s: &str) -> Vec<object::LoxObject> {
+    s.chars()
+        .map(|c| object::LoxObject::Str(c.to_string()))
+        .collect()
+}
+
+// Validates the (string, width, fill) arguments of pad_left() and pad_right(),
+// returning the string and the fill needed to bring it up to width characters.
+fn padding(arguments: &[object::LoxObject], name: &str) -> Result<(String, String)> {
+    let s = expect_string(&arguments[0], name)?;
+    let width = expect_integer(&arguments[1], name)?;
+    if width < 0.0 {
+        return Err(RuntimeError::with_message(&format!(
+            "{}() expects a non-negative width, received \"{}\".",
+            name, arguments[1]
+        )));
+    }
+    let fill = expect_string(&arguments[2], name)?;
+    if fill.chars().count() != 1 {
+        return Err(RuntimeError::with_message(&format!(
+            "{}() expects a single character fill, received \"{}\".",
+            name, fill
+        )));
+    }
+    let missing = expect_allocation(width, fill.len(), name)?.saturating_sub(s.chars().count());
+    Ok((s, fill.repeat(missing)))
+}
+
+// Compares list elements as `==` does, treating values `==` can't compare as unequal
+// so that lists of mixed types can be searched.
+fn is_equal(element: &object::LoxObject, value: &object::LoxObject) -> bool {
+    interpreter::equals(element, value).unwrap_or(false)
+}
+
+// Converts a slice index to an offset into a sequence of length `len`. Negative
+// indices count back from the end, and indices past either end are clamped.
+fn clamp_index(index: f64, len: usize) -> usize {
+    let len = len as f64;
+    let index = if index < 0.0 { index + len } else { index };
+    index.max(0.0).min(len) as usize
+}
+
+// Returns the keys of a map, sorted so iteration order is stable.
+fn sorted_keys(map: &HashMap<String, object::LoxObject>) -> Vec<String> {
+    let mut keys: Vec<String> = map.keys().cloned().collect();
+    keys.sort();
+    keys
+}
+
+// Returns the entries of a map as [key, value] lists, sorted by key.
+fn sorted_entries(map: &HashMap<String, object::LoxObject>) -> Vec<object::LoxObject> {
+    sorted_keys(map)
+        .into_iter()
+        .map(|key| {
+            let value = map[&key].clone();
+            object::LoxObject::new_list(vec![object::LoxObject::Str(key), value])
+        })
+        .collect()
+}
+
+// Recursively copies lists, maps, sets and instance fields. `copying` holds the
+// containers currently being copied so that cycles are reported rather than
+// recursing forever.
+fn deep_copy(
+    value: &object::LoxObject,
+    copying: &mut Vec<object::LoxObject>,
+) -> Result<object::LoxObject> {
+    match value {
+        object::LoxObject::Instance(_)
+        | object::LoxObject::List(_)
+        | object::LoxObject::Map(_)
+        | object::LoxObject::Set(_) => {}
+        _ => return Ok(value.clone()),
+    }
+    if copying.iter().any(|v| v.is_identical(value)) {
+        return Err(RuntimeError::with_message(
+            "deep_copy() cannot copy a value which contains itself.",
+        ));
+    }
+
+    copying.push(value.clone());
+    let copy = match value {
+        object::LoxObject::Instance(instance) => {
+            let mut fields = HashMap::new();
+            for (name, field) in instance.fields() {
+                fields.insert(name, deep_copy(&field, copying)?);
+            }
+            object::LoxObject::Instance(instance.with_fields(fields))
+        }
+        object::LoxObject::List(list) => {
+            let mut elements = vec![];
+            for element in list.borrow().iter() {
+                elements.push(deep_copy(element, copying)?);
+            }
+            object::LoxObject::new_list(elements)
+        }
+        object::LoxObject::Map(map) => {
+            let mut entries = HashMap::new();
+            for (key, entry) in map.borrow().iter() {
+                entries.insert(key.clone(), deep_copy(entry, copying)?);
+            }
+            object::LoxObject::new_map(entries)
+        }
+        object::LoxObject::Set(set) => {
+            // set members are primitives, so a shallow clone is a deep copy
+            object::LoxObject::Set(Rc::new(RefCell::new(set.borrow().clone())))
+        }
+        _ => unreachable!(),
+    };
+    copying.pop();
+    Ok(copy)
+}
+
+// Returns seconds on a monotonic clock, as reported by abs_time(), measured from
+// the first time it's read.
+fn monotonic_seconds() -> f64 {
+    static START: OnceLock<Instant> = OnceLock::new();
+    START.get_or_init(Instant::now).elapsed().as_secs_f64()
+}
+
+// Returns seconds since the Unix epoch, as reported by clock().
+fn clock_seconds() -> f64 {
+    let since_the_epoch = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+    since_the_epoch.as_secs_f64()
+}
+
+// --------------------------------------------------------------------------------------------------------------------
 
 pub struct NativeClock;
 impl NativeClock {
@@ -19,13 +288,4012 @@ impl callable::LoxCallable for NativeClock {
         _: &mut Interpreter,
         _: &Vec<object::LoxObject>,
     ) -> InterpretResult<Option<object::LoxObject>> {
-        let start = SystemTime::now();
-        let since_the_epoch = start.duration_since(UNIX_EPOCH).unwrap();
-        let seconds = since_the_epoch.as_secs_f64();
-        let fractional = since_the_epoch.subsec_nanos() as f64 / 1e9;
-        Ok(Some(object::LoxObject::Number(seconds + fractional)))
+        Ok(Some(object::LoxObject::Number(clock_seconds())))
+    }
+    fn is_property(&self) -> bool {
+        false
+    }
+}
+
+// --------------------------------------------------------------------------------------------------------------------
+
+pub struct NativeClockDiff;
+impl NativeClockDiff {
+    pub fn new() -> Self {
+        NativeClockDiff {}
+    }
+}
+impl callable::LoxCallable for NativeClockDiff {
+    fn arity(&self) -> usize {
+        1
+    }
+    fn call(
+        &self,
+        _: &mut Interpreter,
+        arguments: &Vec<object::LoxObject>,
+    ) -> InterpretResult<Option<object::LoxObject>> {
+        let start = expect_number(&arguments[0], "clock_diff")?;
+        Ok(Some(object::LoxObject::Number(clock_seconds() - start)))
+    }
+    fn is_property(&self) -> bool {
+        false
+    }
+}
+
+// --------------------------------------------------------------------------------------------------------------------
+
+/// Returns nanoseconds since the Unix epoch. Current times need about 61 bits, more
+/// than an f64's 53 bit mantissa, so the result is only precise to a few hundred
+/// nanoseconds; differences between nearby readings are still meaningful.
+pub struct NativeNowNanos;
+impl NativeNowNanos {
+    pub fn new() -> Self {
+        NativeNowNanos {}
+    }
+}
+impl callable::LoxCallable for NativeNowNanos {
+    fn arity(&self) -> usize {
+        0
+    }
+    fn call(
+        &self,
+        _: &mut Interpreter,
+        _: &Vec<object::LoxObject>,
+    ) -> InterpretResult<Option<object::LoxObject>> {
+        let since_the_epoch = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+        Ok(Some(object::LoxObject::Number(
+            since_the_epoch.as_nanos() as f64
+        )))
+    }
+    fn is_property(&self) -> bool {
+        false
+    }
+}
+
+// --------------------------------------------------------------------------------------------------------------------
+
+pub struct NativeAbs;
+impl NativeAbs {
+    pub fn new() -> Self {
+        NativeAbs {}
+    }
+}
+impl callable::LoxCallable for NativeAbs {
+    fn arity(&self) -> usize {
+        1
+    }
+    fn call(
+        &self,
+        _: &mut Interpreter,
+        arguments: &Vec<object::LoxObject>,
+    ) -> InterpretResult<Option<object::LoxObject>> {
+        let x = expect_number(&arguments[0], "abs")?;
+        Ok(Some(object::LoxObject::Number(x.abs())))
+    }
+    fn is_property(&self) -> bool {
+        false
+    }
+}
+
+// --------------------------------------------------------------------------------------------------------------------
+
+pub struct NativeAbsDiff;
+impl NativeAbsDiff {
+    pub fn new() -> Self {
+        NativeAbsDiff {}
+    }
+}
+impl callable::LoxCallable for NativeAbsDiff {
+    fn arity(&self) -> usize {
+        2
+    }
+    fn call(
+        &self,
+        _: &mut Interpreter,
+        arguments: &Vec<object::LoxObject>,
+    ) -> InterpretResult<Option<object::LoxObject>> {
+        let a = expect_number(&arguments[0], "abs_diff")?;
+        let b = expect_number(&arguments[1], "abs_diff")?;
+        Ok(Some(object::LoxObject::Number((a - b).abs())))
+    }
+    fn is_property(&self) -> bool {
+        false
+    }
+}
+
+// --------------------------------------------------------------------------------------------------------------------
+
+pub struct NativeIdentical;
+impl NativeIdentical {
+    pub fn new() -> Self {
+        NativeIdentical {}
+    }
+}
+impl callable::LoxCallable for NativeIdentical {
+    fn arity(&self) -> usize {
+        2
+    }
+    fn call(
+        &self,
+        _: &mut Interpreter,
+        arguments: &Vec<object::LoxObject>,
+    ) -> InterpretResult<Option<object::LoxObject>> {
+        Ok(Some(object::LoxObject::Boolean(
+            arguments[0].is_identical(&arguments[1]),
+        )))
+    }
+    fn is_property(&self) -> bool {
+        false
+    }
+}
+
+// --------------------------------------------------------------------------------------------------------------------
+
+/// Formats seconds since the unix epoch as an ISO-8601 UTC timestamp,
+/// e.g. "2024-01-02T03:04:05Z".
+fn format_iso8601(epoch_seconds: u64) -> String {
+    let days = (epoch_seconds / 86_400) as i64;
+    let seconds_of_day = epoch_seconds % 86_400;
+
+    // Convert days since epoch to a civil (proleptic gregorian) date.
+    // See http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        seconds_of_day / 3600,
+        (seconds_of_day % 3600) / 60,
+        seconds_of_day % 60
+    )
+}
+
+pub struct NativeClockIso;
+impl NativeClockIso {
+    pub fn new() -> Self {
+        NativeClockIso {}
+    }
+}
+impl callable::LoxCallable for NativeClockIso {
+    fn arity(&self) -> usize {
+        0
+    }
+    fn call(
+        &self,
+        _: &mut Interpreter,
+        _: &Vec<object::LoxObject>,
+    ) -> InterpretResult<Option<object::LoxObject>> {
+        let since_the_epoch = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+        Ok(Some(object::LoxObject::Str(format_iso8601(
+            since_the_epoch.as_secs(),
+        ))))
+    }
+    fn is_property(&self) -> bool {
+        false
+    }
+}
+
+// --------------------------------------------------------------------------------------------------------------------
+
+pub struct NativeKeys;
+impl NativeKeys {
+    pub fn new() -> Self {
+        NativeKeys {}
+    }
+}
+impl callable::LoxCallable for NativeKeys {
+    fn arity(&self) -> usize {
+        1
+    }
+    fn call(
+        &self,
+        _: &mut Interpreter,
+        arguments: &Vec<object::LoxObject>,
+    ) -> InterpretResult<Option<object::LoxObject>> {
+        let map = expect_map(&arguments[0], "keys")?;
+        let keys = sorted_keys(&map.borrow())
+            .into_iter()
+            .map(object::LoxObject::Str)
+            .collect();
+        Ok(Some(object::LoxObject::new_list(keys)))
+    }
+    fn is_property(&self) -> bool {
+        false
+    }
+}
+
+// --------------------------------------------------------------------------------------------------------------------
+
+pub struct NativeValues;
+impl NativeValues {
+    pub fn new() -> Self {
+        NativeValues {}
+    }
+}
+impl callable::LoxCallable for NativeValues {
+    fn arity(&self) -> usize {
+        1
+    }
+    fn call(
+        &self,
+        _: &mut Interpreter,
+        arguments: &Vec<object::LoxObject>,
+    ) -> InterpretResult<Option<object::LoxObject>> {
+        let map = expect_map(&arguments[0], "values")?;
+        let map = map.borrow();
+        let values = sorted_keys(&map).iter().map(|k| map[k].clone()).collect();
+        Ok(Some(object::LoxObject::new_list(values)))
+    }
+    fn is_property(&self) -> bool {
+        false
+    }
+}
+
+// --------------------------------------------------------------------------------------------------------------------
+
+pub struct NativeSetNew;
+impl NativeSetNew {
+    pub fn new() -> Self {
+        NativeSetNew {}
+    }
+}
+impl callable::LoxCallable for NativeSetNew {
+    fn arity(&self) -> usize {
+        0
+    }
+    fn call(
+        &self,
+        _: &mut Interpreter,
+        _: &Vec<object::LoxObject>,
+    ) -> InterpretResult<Option<object::LoxObject>> {
+        Ok(Some(object::LoxObject::Set(Rc::new(RefCell::new(
+            LoxSet::new(),
+        )))))
+    }
+    fn is_property(&self) -> bool {
+        false
+    }
+}
+
+// --------------------------------------------------------------------------------------------------------------------
+
+pub struct NativeSetAdd;
+impl NativeSetAdd {
+    pub fn new() -> Self {
+        NativeSetAdd {}
+    }
+}
+impl callable::LoxCallable for NativeSetAdd {
+    fn arity(&self) -> usize {
+        2
+    }
+    fn call(
+        &self,
+        _: &mut Interpreter,
+        arguments: &Vec<object::LoxObject>,
+    ) -> InterpretResult<Option<object::LoxObject>> {
+        let set = expect_set(&arguments[0], "set_add")?;
+        expect_hashable(&arguments[1], "set_add")?;
+        let added = set.borrow_mut().add(&arguments[1]);
+        Ok(Some(object::LoxObject::Boolean(added)))
+    }
+    fn is_property(&self) -> bool {
+        false
+    }
+}
+
+// --------------------------------------------------------------------------------------------------------------------
+
+pub struct NativeSetHas;
+impl NativeSetHas {
+    pub fn new() -> Self {
+        NativeSetHas {}
+    }
+}
+impl callable::LoxCallable for NativeSetHas {
+    fn arity(&self) -> usize {
+        2
+    }
+    fn call(
+        &self,
+        _: &mut Interpreter,
+        arguments: &Vec<object::LoxObject>,
+    ) -> InterpretResult<Option<object::LoxObject>> {
+        let set = expect_set(&arguments[0], "set_has")?;
+        expect_hashable(&arguments[1], "set_has")?;
+        let has = set.borrow().contains(&arguments[1]);
+        Ok(Some(object::LoxObject::Boolean(has)))
     }
     fn is_property(&self) -> bool {
         false
     }
 }
+
+// --------------------------------------------------------------------------------------------------------------------
+
+pub struct NativeSetRemove;
+impl NativeSetRemove {
+    pub fn new() -> Self {
+        NativeSetRemove {}
+    }
+}
+impl callable::LoxCallable for NativeSetRemove {
+    fn arity(&self) -> usize {
+        2
+    }
+    fn call(
+        &self,
+        _: &mut Interpreter,
+        arguments: &Vec<object::LoxObject>,
+    ) -> InterpretResult<Option<object::LoxObject>> {
+        let set = expect_set(&arguments[0], "set_remove")?;
+        expect_hashable(&arguments[1], "set_remove")?;
+        let removed = set.borrow_mut().remove(&arguments[1]);
+        Ok(Some(object::LoxObject::Boolean(removed)))
+    }
+    fn is_property(&self) -> bool {
+        false
+    }
+}
+
+// --------------------------------------------------------------------------------------------------------------------
+
+pub struct NativeRange;
+impl NativeRange {
+    pub fn new() -> Self {
+        NativeRange {}
+    }
+}
+impl callable::LoxCallable for NativeRange {
+    fn arity(&self) -> usize {
+        2
+    }
+    fn max_arity(&self) -> usize {
+        3
+    }
+    fn call(
+        &self,
+        _: &mut Interpreter,
+        arguments: &Vec<object::LoxObject>,
+    ) -> InterpretResult<Option<object::LoxObject>> {
+        let start = expect_integer(&arguments[0], "range")?;
+        let end = expect_integer(&arguments[1], "range")?;
+        let step = if arguments.len() > 2 {
+            expect_integer(&arguments[2], "range")?
+        } else {
+            1.0
+        };
+
+        if step == 0.0 {
+            return Err(InterpretResultStatus::Error(RuntimeError::with_message(
+                "range() step must not be zero.",
+            )));
+        }
+        if (end - start) * step < 0.0 {
+            return Err(InterpretResultStatus::Error(RuntimeError::with_message(
+                &format!(
+                    "range() step {} never reaches {} from {}.",
+                    step, end, start
+                ),
+            )));
+        }
+
+        let count = ((end - start) / step).ceil();
+        let count = expect_allocation(count, std::mem::size_of::<object::LoxObject>(), "range")?;
+        let mut elements = Vec::with_capacity(count);
+        let mut i = start;
+        while (step > 0.0 && i < end) || (step < 0.0 && i > end) {
+            elements.push(object::LoxObject::Number(i));
+            i += step;
+        }
+        Ok(Some(object::LoxObject::new_list(elements)))
+    }
+    fn is_property(&self) -> bool {
+        false
+    }
+}
+
+// --------------------------------------------------------------------------------------------------------------------
+
+pub struct NativeDeepCopy;
+impl NativeDeepCopy {
+    pub fn new() -> Self {
+        NativeDeepCopy {}
+    }
+}
+impl callable::LoxCallable for NativeDeepCopy {
+    fn arity(&self) -> usize {
+        1
+    }
+    fn call(
+        &self,
+        _: &mut Interpreter,
+        arguments: &Vec<object::LoxObject>,
+    ) -> InterpretResult<Option<object::LoxObject>> {
+        Ok(Some(deep_copy(&arguments[0], &mut vec![])?))
+    }
+    fn is_property(&self) -> bool {
+        false
+    }
+}
+
+// --------------------------------------------------------------------------------------------------------------------
+
+pub struct NativeApply;
+impl NativeApply {
+    pub fn new() -> Self {
+        NativeApply {}
+    }
+}
+impl callable::LoxCallable for NativeApply {
+    fn arity(&self) -> usize {
+        2
+    }
+    fn call(
+        &self,
+        interpreter: &mut Interpreter,
+        arguments: &Vec<object::LoxObject>,
+    ) -> InterpretResult<Option<object::LoxObject>> {
+        let args = match &arguments[1] {
+            object::LoxObject::List(list) => list.borrow().clone(),
+            _ => {
+                return Err(InterpretResultStatus::Error(RuntimeError::with_message(
+                    &format!(
+                        "apply() expects a list of arguments, received \"{}\".",
+                        arguments[1]
+                    ),
+                )))
+            }
+        };
+        Ok(Some(interpreter.call(&arguments[0], &args, None)?))
+    }
+    fn is_property(&self) -> bool {
+        false
+    }
+}
+
+// --------------------------------------------------------------------------------------------------------------------
+
+// Returns the arguments of a floored division native, rejecting a zero divisor.
+fn expect_floor_operands(arguments: &[object::LoxObject], name: &str) -> Result<(f64, f64)> {
+    let a = expect_number(&arguments[0], name)?;
+    let b = expect_number(&arguments[1], name)?;
+    if b == 0.0 {
+        Err(RuntimeError::with_message(&format!(
+            "{}() division by zero.",
+            name
+        )))
+    } else {
+        Ok((a, b))
+    }
+}
+
+pub struct NativeFloorDiv;
+impl NativeFloorDiv {
+    pub fn new() -> Self {
+        NativeFloorDiv {}
+    }
+}
+impl callable::LoxCallable for NativeFloorDiv {
+    fn arity(&self) -> usize {
+        2
+    }
+    fn call(
+        &self,
+        _: &mut Interpreter,
+        arguments: &Vec<object::LoxObject>,
+    ) -> InterpretResult<Option<object::LoxObject>> {
+        let (a, b) = expect_floor_operands(arguments, "floor_div")?;
+        Ok(Some(object::LoxObject::Number((a / b).floor())))
+    }
+    fn is_property(&self) -> bool {
+        false
+    }
+}
+
+// --------------------------------------------------------------------------------------------------------------------
+
+/// Modulo which rounds toward negative infinity, so the result takes the sign of
+/// the divisor: floor_mod(-7, 3) is 2, where a truncating remainder gives -1.
+pub struct NativeFloorMod;
+impl NativeFloorMod {
+    pub fn new() -> Self {
+        NativeFloorMod {}
+    }
+}
+impl callable::LoxCallable for NativeFloorMod {
+    fn arity(&self) -> usize {
+        2
+    }
+    fn call(
+        &self,
+        _: &mut Interpreter,
+        arguments: &Vec<object::LoxObject>,
+    ) -> InterpretResult<Option<object::LoxObject>> {
+        let (a, b) = expect_floor_operands(arguments, "floor_mod")?;
+        let remainder = a % b;
+        let result = if remainder != 0.0 && (remainder < 0.0) != (b < 0.0) {
+            remainder + b
+        } else {
+            remainder
+        };
+        Ok(Some(object::LoxObject::Number(result)))
+    }
+    fn is_property(&self) -> bool {
+        false
+    }
+}
+
+// --------------------------------------------------------------------------------------------------------------------
+
+pub struct NativeReadAll;
+impl NativeReadAll {
+    pub fn new() -> Self {
+        NativeReadAll {}
+    }
+}
+impl callable::LoxCallable for NativeReadAll {
+    fn arity(&self) -> usize {
+        0
+    }
+    fn call(
+        &self,
+        interpreter: &mut Interpreter,
+        _: &Vec<object::LoxObject>,
+    ) -> InterpretResult<Option<object::LoxObject>> {
+        let mut contents = String::new();
+        if let Err(e) = interpreter.input().read_to_string(&mut contents) {
+            return Err(InterpretResultStatus::Error(RuntimeError::with_message(
+                &format!("read_all() failed to read input: {}", e),
+            )));
+        }
+        Ok(Some(object::LoxObject::Str(contents)))
+    }
+    fn is_property(&self) -> bool {
+        false
+    }
+}
+
+// --------------------------------------------------------------------------------------------------------------------
+
+pub struct NativeToRadix;
+impl NativeToRadix {
+    pub fn new() -> Self {
+        NativeToRadix {}
+    }
+}
+impl callable::LoxCallable for NativeToRadix {
+    fn arity(&self) -> usize {
+        2
+    }
+    fn call(
+        &self,
+        _: &mut Interpreter,
+        arguments: &Vec<object::LoxObject>,
+    ) -> InterpretResult<Option<object::LoxObject>> {
+        let n = expect_integer(&arguments[0], "to_radix")?;
+        let radix = expect_integer(&arguments[1], "to_radix")?;
+        // 2^64 is exactly representable; anything at or above it won't fit a u64
+        if !(0.0..18446744073709551616.0).contains(&n) {
+            return Err(InterpretResultStatus::Error(RuntimeError::with_message(
+                &format!(
+                    "to_radix() expects a non-negative integer below 2^64, received \"{}\".",
+                    arguments[0]
+                ),
+            )));
+        }
+        if !(2.0..=36.0).contains(&radix) {
+            return Err(InterpretResultStatus::Error(RuntimeError::with_message(
+                &format!(
+                    "to_radix() expects a radix from 2 to 36, received \"{}\".",
+                    arguments[1]
+                ),
+            )));
+        }
+
+        let mut n = n as u64;
+        let radix = radix as u64;
+        let mut digits = vec![];
+        loop {
+            digits.push(std::char::from_digit((n % radix) as u32, radix as u32).unwrap());
+            n /= radix;
+            if n == 0 {
+                break;
+            }
+        }
+        Ok(Some(object::LoxObject::Str(digits.iter().rev().collect())))
+    }
+    fn is_property(&self) -> bool {
+        false
+    }
+}
+
+// --------------------------------------------------------------------------------------------------------------------
+
+pub struct NativeEnv;
+impl NativeEnv {
+    pub fn new() -> Self {
+        NativeEnv {}
+    }
+}
+impl callable::LoxCallable for NativeEnv {
+    fn arity(&self) -> usize {
+        1
+    }
+    fn call(
+        &self,
+        _: &mut Interpreter,
+        arguments: &Vec<object::LoxObject>,
+    ) -> InterpretResult<Option<object::LoxObject>> {
+        let name = expect_string(&arguments[0], "env")?;
+        match std::env::var(name) {
+            Ok(value) => Ok(Some(object::LoxObject::Str(value))),
+            Err(_) => Ok(Some(object::LoxObject::Nil)),
+        }
+    }
+    fn is_property(&self) -> bool {
+        false
+    }
+}
+
+// --------------------------------------------------------------------------------------------------------------------
+
+pub struct NativeArgs;
+impl NativeArgs {
+    pub fn new() -> Self {
+        NativeArgs {}
+    }
+}
+impl callable::LoxCallable for NativeArgs {
+    fn arity(&self) -> usize {
+        0
+    }
+    fn call(
+        &self,
+        interpreter: &mut Interpreter,
+        _: &Vec<object::LoxObject>,
+    ) -> InterpretResult<Option<object::LoxObject>> {
+        let args = interpreter
+            .args()
+            .iter()
+            .map(|arg| object::LoxObject::Str(arg.clone()))
+            .collect();
+        Ok(Some(object::LoxObject::new_list(args)))
+    }
+    fn is_property(&self) -> bool {
+        false
+    }
+}
+
+// --------------------------------------------------------------------------------------------------------------------
+
+pub struct NativeIsNil;
+impl NativeIsNil {
+    pub fn new() -> Self {
+        NativeIsNil {}
+    }
+}
+impl callable::LoxCallable for NativeIsNil {
+    fn arity(&self) -> usize {
+        1
+    }
+    fn call(
+        &self,
+        _: &mut Interpreter,
+        arguments: &Vec<object::LoxObject>,
+    ) -> InterpretResult<Option<object::LoxObject>> {
+        let result = matches!(arguments[0], object::LoxObject::Nil);
+        Ok(Some(object::LoxObject::Boolean(result)))
+    }
+    fn is_property(&self) -> bool {
+        false
+    }
+}
+
+// --------------------------------------------------------------------------------------------------------------------
+
+pub struct NativeIsNumber;
+impl NativeIsNumber {
+    pub fn new() -> Self {
+        NativeIsNumber {}
+    }
+}
+impl callable::LoxCallable for NativeIsNumber {
+    fn arity(&self) -> usize {
+        1
+    }
+    fn call(
+        &self,
+        _: &mut Interpreter,
+        arguments: &Vec<object::LoxObject>,
+    ) -> InterpretResult<Option<object::LoxObject>> {
+        let result = matches!(arguments[0], object::LoxObject::Number(_));
+        Ok(Some(object::LoxObject::Boolean(result)))
+    }
+    fn is_property(&self) -> bool {
+        false
+    }
+}
+
+// --------------------------------------------------------------------------------------------------------------------
+
+pub struct NativeIsString;
+impl NativeIsString {
+    pub fn new() -> Self {
+        NativeIsString {}
+    }
+}
+impl callable::LoxCallable for NativeIsString {
+    fn arity(&self) -> usize {
+        1
+    }
+    fn call(
+        &self,
+        _: &mut Interpreter,
+        arguments: &Vec<object::LoxObject>,
+    ) -> InterpretResult<Option<object::LoxObject>> {
+        let result = matches!(arguments[0], object::LoxObject::Str(_));
+        Ok(Some(object::LoxObject::Boolean(result)))
+    }
+    fn is_property(&self) -> bool {
+        false
+    }
+}
+
+// --------------------------------------------------------------------------------------------------------------------
+
+pub struct NativeIsBool;
+impl NativeIsBool {
+    pub fn new() -> Self {
+        NativeIsBool {}
+    }
+}
+impl callable::LoxCallable for NativeIsBool {
+    fn arity(&self) -> usize {
+        1
+    }
+    fn call(
+        &self,
+        _: &mut Interpreter,
+        arguments: &Vec<object::LoxObject>,
+    ) -> InterpretResult<Option<object::LoxObject>> {
+        let result = matches!(arguments[0], object::LoxObject::Boolean(_));
+        Ok(Some(object::LoxObject::Boolean(result)))
+    }
+    fn is_property(&self) -> bool {
+        false
+    }
+}
+
+// --------------------------------------------------------------------------------------------------------------------
+
+pub struct NativeJsonStringify;
+impl NativeJsonStringify {
+    pub fn new() -> Self {
+        NativeJsonStringify {}
+    }
+}
+impl callable::LoxCallable for NativeJsonStringify {
+    fn arity(&self) -> usize {
+        1
+    }
+    fn call(
+        &self,
+        _: &mut Interpreter,
+        arguments: &Vec<object::LoxObject>,
+    ) -> InterpretResult<Option<object::LoxObject>> {
+        let json = json::stringify(&arguments[0])?;
+        Ok(Some(object::LoxObject::Str(json)))
+    }
+    fn is_property(&self) -> bool {
+        false
+    }
+}
+
+// --------------------------------------------------------------------------------------------------------------------
+
+pub struct NativeJsonParse;
+impl NativeJsonParse {
+    pub fn new() -> Self {
+        NativeJsonParse {}
+    }
+}
+impl callable::LoxCallable for NativeJsonParse {
+    fn arity(&self) -> usize {
+        1
+    }
+    fn call(
+        &self,
+        _: &mut Interpreter,
+        arguments: &Vec<object::LoxObject>,
+    ) -> InterpretResult<Option<object::LoxObject>> {
+        let text = expect_string(&arguments[0], "json_parse")?;
+        Ok(Some(json::parse(&text)?))
+    }
+    fn is_property(&self) -> bool {
+        false
+    }
+}
+
+// --------------------------------------------------------------------------------------------------------------------
+
+pub struct NativeHash;
+impl NativeHash {
+    pub fn new() -> Self {
+        NativeHash {}
+    }
+}
+impl callable::LoxCallable for NativeHash {
+    fn arity(&self) -> usize {
+        1
+    }
+    fn call(
+        &self,
+        _: &mut Interpreter,
+        arguments: &Vec<object::LoxObject>,
+    ) -> InterpretResult<Option<object::LoxObject>> {
+        expect_hashable(&arguments[0], "hash")?;
+        let hash = LoxSet::hash(&arguments[0]).unwrap();
+        // keep 53 bits so the hash is exactly representable as a number
+        let hash = hash & ((1 << 53) - 1);
+        Ok(Some(object::LoxObject::Number(hash as f64)))
+    }
+    fn is_property(&self) -> bool {
+        false
+    }
+}
+
+// --------------------------------------------------------------------------------------------------------------------
+
+pub struct NativeClamp;
+impl NativeClamp {
+    pub fn new() -> Self {
+        NativeClamp {}
+    }
+}
+impl callable::LoxCallable for NativeClamp {
+    fn arity(&self) -> usize {
+        3
+    }
+    fn call(
+        &self,
+        _: &mut Interpreter,
+        arguments: &Vec<object::LoxObject>,
+    ) -> InterpretResult<Option<object::LoxObject>> {
+        let x = expect_number(&arguments[0], "clamp")?;
+        let lo = expect_number(&arguments[1], "clamp")?;
+        let hi = expect_number(&arguments[2], "clamp")?;
+        if lo > hi {
+            return Err(InterpretResultStatus::Error(RuntimeError::with_message(
+                &format!("clamp() expects lo <= hi, received lo: {} hi: {}.", lo, hi),
+            )));
+        }
+        Ok(Some(object::LoxObject::Number(x.max(lo).min(hi))))
+    }
+    fn is_property(&self) -> bool {
+        false
+    }
+}
+
+// --------------------------------------------------------------------------------------------------------------------
+
+pub struct NativeAssertEq;
+impl NativeAssertEq {
+    pub fn new() -> Self {
+        NativeAssertEq {}
+    }
+}
+impl callable::LoxCallable for NativeAssertEq {
+    fn arity(&self) -> usize {
+        2
+    }
+    fn call(
+        &self,
+        _: &mut Interpreter,
+        arguments: &Vec<object::LoxObject>,
+    ) -> InterpretResult<Option<object::LoxObject>> {
+        let (actual, expected) = (&arguments[0], &arguments[1]);
+        match interpreter::equals(actual, expected) {
+            Ok(true) => Ok(Some(object::LoxObject::Nil)),
+            Ok(false) => Err(InterpretResultStatus::Error(RuntimeError::with_message(
+                &format!("assert_eq() expected {} but got {}.", expected, actual),
+            ))),
+            Err(message) => Err(InterpretResultStatus::Error(RuntimeError::with_message(
+                &format!("assert_eq() cannot compare values: {}", message),
+            ))),
+        }
+    }
+    fn is_property(&self) -> bool {
+        false
+    }
+}
+
+// --------------------------------------------------------------------------------------------------------------------
+
+/// Splits a string into a list of its characters, or a map into a list of
+/// [key, value] pairs sorted by key.
+pub struct NativeToList;
+impl NativeToList {
+    pub fn new() -> Self {
+        NativeToList {}
+    }
+}
+impl callable::LoxCallable for NativeToList {
+    fn arity(&self) -> usize {
+        1
+    }
+    fn call(
+        &self,
+        _: &mut Interpreter,
+        arguments: &Vec<object::LoxObject>,
+    ) -> InterpretResult<Option<object::LoxObject>> {
+        let elements = match &arguments[0] {
+            object::LoxObject::Str(s) => split_chars(s),
+            object::LoxObject::Map(map) => sorted_entries(&map.borrow()),
+            _ => {
+                return Err(InterpretResultStatus::Error(RuntimeError::with_message(
+                    &format!(
+                        "to_list() expects a string or map argument, received \"{}\".",
+                        arguments[0]
+                    ),
+                )))
+            }
+        };
+        Ok(Some(object::LoxObject::new_list(elements)))
+    }
+    fn is_property(&self) -> bool {
+        false
+    }
+}
+
+// --------------------------------------------------------------------------------------------------------------------
+
+pub struct NativeCallableArity;
+impl NativeCallableArity {
+    pub fn new() -> Self {
+        NativeCallableArity {}
+    }
+}
+impl callable::LoxCallable for NativeCallableArity {
+    fn arity(&self) -> usize {
+        1
+    }
+    fn call(
+        &self,
+        _: &mut Interpreter,
+        arguments: &Vec<object::LoxObject>,
+    ) -> InterpretResult<Option<object::LoxObject>> {
+        let arity = match &arguments[0] {
+            object::LoxObject::Callable(callable) => callable.borrow().arity(),
+            object::LoxObject::Class(class) => class.arity(),
+            _ => {
+                return Err(InterpretResultStatus::Error(RuntimeError::with_message(
+                    &format!(
+                        "callable_arity() expects a function or class argument, received \"{}\".",
+                        arguments[0]
+                    ),
+                )))
+            }
+        };
+        Ok(Some(object::LoxObject::Number(arity as f64)))
+    }
+    fn is_property(&self) -> bool {
+        false
+    }
+}
+
+// --------------------------------------------------------------------------------------------------------------------
+
+// Returns the numbers in the list `value`, or a RuntimeError naming the native
+// function `name` if it isn't a list of numbers.
+fn expect_numbers(value: &object::LoxObject, name: &str) -> Result<Vec<f64>> {
+    let list = expect_list(value, name)?;
+    let numbers = list
+        .borrow()
+        .iter()
+        .map(|element| expect_number(element, name))
+        .collect();
+    numbers
+}
+
+// As expect_numbers, but the list mustn't be empty.
+fn expect_non_empty_numbers(value: &object::LoxObject, name: &str) -> Result<Vec<f64>> {
+    let numbers = expect_numbers(value, name)?;
+    if numbers.is_empty() {
+        return Err(RuntimeError::with_message(&format!(
+            "{}() expects a non-empty list.",
+            name
+        )));
+    }
+    Ok(numbers)
+}
+
+pub struct NativeMaxList;
+impl NativeMaxList {
+    pub fn new() -> Self {
+        NativeMaxList {}
+    }
+}
+impl callable::LoxCallable for NativeMaxList {
+    fn arity(&self) -> usize {
+        1
+    }
+    fn call(
+        &self,
+        _: &mut Interpreter,
+        arguments: &Vec<object::LoxObject>,
+    ) -> InterpretResult<Option<object::LoxObject>> {
+        let numbers = expect_non_empty_numbers(&arguments[0], "max_list")?;
+        let result = numbers.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+        Ok(Some(object::LoxObject::Number(result)))
+    }
+    fn is_property(&self) -> bool {
+        false
+    }
+}
+
+// --------------------------------------------------------------------------------------------------------------------
+
+pub struct NativeMinList;
+impl NativeMinList {
+    pub fn new() -> Self {
+        NativeMinList {}
+    }
+}
+impl callable::LoxCallable for NativeMinList {
+    fn arity(&self) -> usize {
+        1
+    }
+    fn call(
+        &self,
+        _: &mut Interpreter,
+        arguments: &Vec<object::LoxObject>,
+    ) -> InterpretResult<Option<object::LoxObject>> {
+        let numbers = expect_non_empty_numbers(&arguments[0], "min_list")?;
+        let result = numbers.iter().cloned().fold(f64::INFINITY, f64::min);
+        Ok(Some(object::LoxObject::Number(result)))
+    }
+    fn is_property(&self) -> bool {
+        false
+    }
+}
+
+// --------------------------------------------------------------------------------------------------------------------
+
+pub struct NativeRepeat;
+impl NativeRepeat {
+    pub fn new() -> Self {
+        NativeRepeat {}
+    }
+}
+impl callable::LoxCallable for NativeRepeat {
+    fn arity(&self) -> usize {
+        2
+    }
+    fn call(
+        &self,
+        _: &mut Interpreter,
+        arguments: &Vec<object::LoxObject>,
+    ) -> InterpretResult<Option<object::LoxObject>> {
+        let s = expect_string(&arguments[0], "repeat")?;
+        let count = expect_integer(&arguments[1], "repeat")?;
+        if count < 0.0 {
+            return Err(InterpretResultStatus::Error(RuntimeError::with_message(
+                &format!(
+                    "repeat() expects a non-negative count, received \"{}\".",
+                    arguments[1]
+                ),
+            )));
+        }
+        let count = expect_allocation(count, s.len(), "repeat")?;
+        Ok(Some(object::LoxObject::Str(s.repeat(count))))
+    }
+    fn is_property(&self) -> bool {
+        false
+    }
+}
+
+// --------------------------------------------------------------------------------------------------------------------
+
+pub struct NativeCodepoints;
+impl NativeCodepoints {
+    pub fn new() -> Self {
+        NativeCodepoints {}
+    }
+}
+impl callable::LoxCallable for NativeCodepoints {
+    fn arity(&self) -> usize {
+        1
+    }
+    fn call(
+        &self,
+        _: &mut Interpreter,
+        arguments: &Vec<object::LoxObject>,
+    ) -> InterpretResult<Option<object::LoxObject>> {
+        let s = expect_string(&arguments[0], "codepoints")?;
+        let codepoints = s
+            .chars()
+            .map(|c| object::LoxObject::Number(c as u32 as f64))
+            .collect();
+        Ok(Some(object::LoxObject::new_list(codepoints)))
+    }
+    fn is_property(&self) -> bool {
+        false
+    }
+}
+
+// --------------------------------------------------------------------------------------------------------------------
+
+pub struct NativeBool;
+impl NativeBool {
+    pub fn new() -> Self {
+        NativeBool {}
+    }
+}
+impl callable::LoxCallable for NativeBool {
+    fn arity(&self) -> usize {
+        1
+    }
+    fn call(
+        &self,
+        _: &mut Interpreter,
+        arguments: &Vec<object::LoxObject>,
+    ) -> InterpretResult<Option<object::LoxObject>> {
+        Ok(Some(object::LoxObject::Boolean(arguments[0].is_truthy())))
+    }
+    fn is_property(&self) -> bool {
+        false
+    }
+}
+
+// --------------------------------------------------------------------------------------------------------------------
+
+pub struct NativeFloorTo;
+impl NativeFloorTo {
+    pub fn new() -> Self {
+        NativeFloorTo {}
+    }
+}
+impl callable::LoxCallable for NativeFloorTo {
+    fn arity(&self) -> usize {
+        2
+    }
+    fn call(
+        &self,
+        _: &mut Interpreter,
+        arguments: &Vec<object::LoxObject>,
+    ) -> InterpretResult<Option<object::LoxObject>> {
+        let n = expect_number(&arguments[0], "floor_to")?;
+        let multiple = expect_multiple(&arguments[1], "floor_to")?;
+        Ok(Some(object::LoxObject::Number(
+            (n / multiple).floor() * multiple,
+        )))
+    }
+    fn is_property(&self) -> bool {
+        false
+    }
+}
+
+// --------------------------------------------------------------------------------------------------------------------
+
+pub struct NativeRoundTo;
+impl NativeRoundTo {
+    pub fn new() -> Self {
+        NativeRoundTo {}
+    }
+}
+impl callable::LoxCallable for NativeRoundTo {
+    fn arity(&self) -> usize {
+        2
+    }
+    fn call(
+        &self,
+        _: &mut Interpreter,
+        arguments: &Vec<object::LoxObject>,
+    ) -> InterpretResult<Option<object::LoxObject>> {
+        let n = expect_number(&arguments[0], "round_to")?;
+        let multiple = expect_multiple(&arguments[1], "round_to")?;
+        Ok(Some(object::LoxObject::Number(
+            (n / multiple).round() * multiple,
+        )))
+    }
+    fn is_property(&self) -> bool {
+        false
+    }
+}
+
+// --------------------------------------------------------------------------------------------------------------------
+
+pub struct NativeListPush;
+impl NativeListPush {
+    pub fn new() -> Self {
+        NativeListPush {}
+    }
+}
+impl callable::LoxCallable for NativeListPush {
+    fn arity(&self) -> usize {
+        2
+    }
+    fn call(
+        &self,
+        _: &mut Interpreter,
+        arguments: &Vec<object::LoxObject>,
+    ) -> InterpretResult<Option<object::LoxObject>> {
+        let list = expect_list(&arguments[0], "list_push")?;
+        list.borrow_mut().push(arguments[1].clone());
+        let length = list.borrow().len();
+        Ok(Some(object::LoxObject::Number(length as f64)))
+    }
+    fn is_property(&self) -> bool {
+        false
+    }
+}
+
+// --------------------------------------------------------------------------------------------------------------------
+
+pub struct NativeListPop;
+impl NativeListPop {
+    pub fn new() -> Self {
+        NativeListPop {}
+    }
+}
+impl callable::LoxCallable for NativeListPop {
+    fn arity(&self) -> usize {
+        1
+    }
+    fn call(
+        &self,
+        _: &mut Interpreter,
+        arguments: &Vec<object::LoxObject>,
+    ) -> InterpretResult<Option<object::LoxObject>> {
+        let list = expect_list(&arguments[0], "list_pop")?;
+        let last = list.borrow_mut().pop();
+        Ok(Some(last.unwrap_or(object::LoxObject::Nil)))
+    }
+    fn is_property(&self) -> bool {
+        false
+    }
+}
+
+// --------------------------------------------------------------------------------------------------------------------
+
+pub struct NativeListConcat;
+impl NativeListConcat {
+    pub fn new() -> Self {
+        NativeListConcat {}
+    }
+}
+impl callable::LoxCallable for NativeListConcat {
+    fn arity(&self) -> usize {
+        2
+    }
+    fn call(
+        &self,
+        _: &mut Interpreter,
+        arguments: &Vec<object::LoxObject>,
+    ) -> InterpretResult<Option<object::LoxObject>> {
+        let a = expect_list(&arguments[0], "list_concat")?;
+        let b = expect_list(&arguments[1], "list_concat")?;
+        let mut elements = a.borrow().clone();
+        elements.extend(b.borrow().iter().cloned());
+        Ok(Some(object::LoxObject::new_list(elements)))
+    }
+    fn is_property(&self) -> bool {
+        false
+    }
+}
+
+// --------------------------------------------------------------------------------------------------------------------
+
+pub struct NativeAssertThrows;
+impl NativeAssertThrows {
+    pub fn new() -> Self {
+        NativeAssertThrows {}
+    }
+}
+impl callable::LoxCallable for NativeAssertThrows {
+    fn arity(&self) -> usize {
+        1
+    }
+    fn call(
+        &self,
+        interpreter: &mut Interpreter,
+        arguments: &Vec<object::LoxObject>,
+    ) -> InterpretResult<Option<object::LoxObject>> {
+        let arity = expect_callable(&arguments[0], "assert_throws")?;
+        if arity != 0 {
+            // otherwise the arity mismatch itself would count as the expected error
+            return Err(InterpretResultStatus::Error(RuntimeError::with_message(
+                "assert_throws() expects a function taking no arguments.",
+            )));
+        }
+        match interpreter.call(&arguments[0], &vec![], None) {
+            Ok(_) => Err(InterpretResultStatus::Error(RuntimeError::with_message(
+                "assert_throws() expected the function to raise an error.",
+            ))),
+            Err(InterpretResultStatus::Error(_)) => Ok(Some(object::LoxObject::Nil)),
+            Err(e) => Err(e),
+        }
+    }
+    fn is_property(&self) -> bool {
+        false
+    }
+}
+
+// --------------------------------------------------------------------------------------------------------------------
+
+pub struct NativeIsInstanceOf;
+impl NativeIsInstanceOf {
+    pub fn new() -> Self {
+        NativeIsInstanceOf {}
+    }
+}
+impl callable::LoxCallable for NativeIsInstanceOf {
+    fn arity(&self) -> usize {
+        2
+    }
+    fn call(
+        &self,
+        _: &mut Interpreter,
+        arguments: &Vec<object::LoxObject>,
+    ) -> InterpretResult<Option<object::LoxObject>> {
+        let class = match &arguments[1] {
+            object::LoxObject::Class(class) => class,
+            _ => {
+                return Err(InterpretResultStatus::Error(RuntimeError::with_message(
+                    &format!(
+                        "is_instance_of() expects a class argument, received \"{}\".",
+                        arguments[1]
+                    ),
+                )))
+            }
+        };
+        let result = match &arguments[0] {
+            object::LoxObject::Instance(instance) => instance.is_instance_of(class),
+            _ => false,
+        };
+        Ok(Some(object::LoxObject::Boolean(result)))
+    }
+    fn is_property(&self) -> bool {
+        false
+    }
+}
+
+// --------------------------------------------------------------------------------------------------------------------
+
+pub struct NativeSum;
+impl NativeSum {
+    pub fn new() -> Self {
+        NativeSum {}
+    }
+}
+impl callable::LoxCallable for NativeSum {
+    fn arity(&self) -> usize {
+        1
+    }
+    fn call(
+        &self,
+        _: &mut Interpreter,
+        arguments: &Vec<object::LoxObject>,
+    ) -> InterpretResult<Option<object::LoxObject>> {
+        let numbers = expect_numbers(&arguments[0], "sum")?;
+        Ok(Some(object::LoxObject::Number(numbers.iter().sum())))
+    }
+    fn is_property(&self) -> bool {
+        false
+    }
+}
+
+// --------------------------------------------------------------------------------------------------------------------
+
+pub struct NativeCharAt;
+impl NativeCharAt {
+    pub fn new() -> Self {
+        NativeCharAt {}
+    }
+}
+impl callable::LoxCallable for NativeCharAt {
+    fn arity(&self) -> usize {
+        2
+    }
+    fn call(
+        &self,
+        _: &mut Interpreter,
+        arguments: &Vec<object::LoxObject>,
+    ) -> InterpretResult<Option<object::LoxObject>> {
+        let s = expect_string(&arguments[0], "char_at")?;
+        let index = expect_integer(&arguments[1], "char_at")?;
+        let c = if index < 0.0 {
+            None
+        } else {
+            s.chars().nth(index as usize)
+        };
+        match c {
+            Some(c) => Ok(Some(object::LoxObject::Str(c.to_string()))),
+            None => Err(InterpretResultStatus::Error(RuntimeError::with_message(
+                &format!(
+                    "char_at() index {} is out of range for a string of length {}.",
+                    arguments[1],
+                    s.chars().count()
+                ),
+            ))),
+        }
+    }
+    fn is_property(&self) -> bool {
+        false
+    }
+}
+
+// --------------------------------------------------------------------------------------------------------------------
+
+pub struct NativePanic;
+impl NativePanic {
+    pub fn new() -> Self {
+        NativePanic {}
+    }
+}
+impl callable::LoxCallable for NativePanic {
+    fn arity(&self) -> usize {
+        1
+    }
+    fn call(
+        &self,
+        _: &mut Interpreter,
+        arguments: &Vec<object::LoxObject>,
+    ) -> InterpretResult<Option<object::LoxObject>> {
+        Err(InterpretResultStatus::Error(RuntimeError::with_message(
+            &format!("panic: {}", arguments[0]),
+        )))
+    }
+    fn is_property(&self) -> bool {
+        false
+    }
+}
+
+// --------------------------------------------------------------------------------------------------------------------
+
+pub struct NativeZip;
+impl NativeZip {
+    pub fn new() -> Self {
+        NativeZip {}
+    }
+}
+impl callable::LoxCallable for NativeZip {
+    fn arity(&self) -> usize {
+        2
+    }
+    fn call(
+        &self,
+        _: &mut Interpreter,
+        arguments: &Vec<object::LoxObject>,
+    ) -> InterpretResult<Option<object::LoxObject>> {
+        let a = expect_list(&arguments[0], "zip")?;
+        let b = expect_list(&arguments[1], "zip")?;
+        let pairs = a
+            .borrow()
+            .iter()
+            .zip(b.borrow().iter())
+            .map(|(x, y)| object::LoxObject::new_list(vec![x.clone(), y.clone()]))
+            .collect();
+        Ok(Some(object::LoxObject::new_list(pairs)))
+    }
+    fn is_property(&self) -> bool {
+        false
+    }
+}
+
+// --------------------------------------------------------------------------------------------------------------------
+
+pub struct NativeStrReverse;
+impl NativeStrReverse {
+    pub fn new() -> Self {
+        NativeStrReverse {}
+    }
+}
+impl callable::LoxCallable for NativeStrReverse {
+    fn arity(&self) -> usize {
+        1
+    }
+    fn call(
+        &self,
+        _: &mut Interpreter,
+        arguments: &Vec<object::LoxObject>,
+    ) -> InterpretResult<Option<object::LoxObject>> {
+        let s = expect_string(&arguments[0], "str_reverse")?;
+        Ok(Some(object::LoxObject::Str(s.chars().rev().collect())))
+    }
+    fn is_property(&self) -> bool {
+        false
+    }
+}
+
+// --------------------------------------------------------------------------------------------------------------------
+
+pub struct NativeChars;
+impl NativeChars {
+    pub fn new() -> Self {
+        NativeChars {}
+    }
+}
+impl callable::LoxCallable for NativeChars {
+    fn arity(&self) -> usize {
+        1
+    }
+    fn call(
+        &self,
+        _: &mut Interpreter,
+        arguments: &Vec<object::LoxObject>,
+    ) -> InterpretResult<Option<object::LoxObject>> {
+        let s = expect_string(&arguments[0], "chars")?;
+        Ok(Some(object::LoxObject::new_list(split_chars(&s))))
+    }
+    fn is_property(&self) -> bool {
+        false
+    }
+}
+
+// --------------------------------------------------------------------------------------------------------------------
+
+// Returns the element of the list `list` whose key, computed by calling `key`, is
+// preferred by `is_better` over every earlier element's key.
+fn extreme_by(
+    interpreter: &mut Interpreter,
+    list: &object::LoxObject,
+    key: &object::LoxObject,
+    name: &str,
+    is_better: fn(f64, f64) -> bool,
+) -> InterpretResult<Option<object::LoxObject>> {
+    // copy the elements, since the key function may modify the list
+    let elements = expect_list(list, name)?.borrow().clone();
+    let mut best: Option<(object::LoxObject, f64)> = None;
+    for element in elements {
+        let k = interpreter.call(key, &vec![element.clone()], None)?;
+        let k = match k {
+            object::LoxObject::Number(k) => k,
+            _ => {
+                return Err(InterpretResultStatus::Error(RuntimeError::with_message(
+                    &format!(
+                        "{}() expects the key function to return numbers, received \"{}\".",
+                        name, k
+                    ),
+                )))
+            }
+        };
+        best = match best {
+            Some((_, best_k)) if !is_better(k, best_k) => best,
+            _ => Some((element, k)),
+        };
+    }
+    match best {
+        Some((element, _)) => Ok(Some(element)),
+        None => Err(InterpretResultStatus::Error(RuntimeError::with_message(
+            &format!("{}() expects a non-empty list.", name),
+        ))),
+    }
+}
+
+// --------------------------------------------------------------------------------------------------------------------
+
+pub struct NativeMinBy;
+impl NativeMinBy {
+    pub fn new() -> Self {
+        NativeMinBy {}
+    }
+}
+impl callable::LoxCallable for NativeMinBy {
+    fn arity(&self) -> usize {
+        2
+    }
+    fn call(
+        &self,
+        interpreter: &mut Interpreter,
+        arguments: &Vec<object::LoxObject>,
+    ) -> InterpretResult<Option<object::LoxObject>> {
+        extreme_by(
+            interpreter,
+            &arguments[0],
+            &arguments[1],
+            "min_by",
+            |k, best| k < best,
+        )
+    }
+    fn is_property(&self) -> bool {
+        false
+    }
+}
+
+// --------------------------------------------------------------------------------------------------------------------
+
+pub struct NativeMaxBy;
+impl NativeMaxBy {
+    pub fn new() -> Self {
+        NativeMaxBy {}
+    }
+}
+impl callable::LoxCallable for NativeMaxBy {
+    fn arity(&self) -> usize {
+        2
+    }
+    fn call(
+        &self,
+        interpreter: &mut Interpreter,
+        arguments: &Vec<object::LoxObject>,
+    ) -> InterpretResult<Option<object::LoxObject>> {
+        extreme_by(
+            interpreter,
+            &arguments[0],
+            &arguments[1],
+            "max_by",
+            |k, best| k > best,
+        )
+    }
+    fn is_property(&self) -> bool {
+        false
+    }
+}
+
+// --------------------------------------------------------------------------------------------------------------------
+
+pub struct NativeSign;
+impl NativeSign {
+    pub fn new() -> Self {
+        NativeSign {}
+    }
+}
+impl callable::LoxCallable for NativeSign {
+    fn arity(&self) -> usize {
+        1
+    }
+    fn call(
+        &self,
+        _: &mut Interpreter,
+        arguments: &Vec<object::LoxObject>,
+    ) -> InterpretResult<Option<object::LoxObject>> {
+        let x = expect_number(&arguments[0], "sign")?;
+        // f64::signum is 1 for +0.0, but sign(0) should be 0
+        let sign = if x == 0.0 { 0.0 } else { x.signum() };
+        Ok(Some(object::LoxObject::Number(sign)))
+    }
+    fn is_property(&self) -> bool {
+        false
+    }
+}
+
+// --------------------------------------------------------------------------------------------------------------------
+
+pub struct NativeTrunc;
+impl NativeTrunc {
+    pub fn new() -> Self {
+        NativeTrunc {}
+    }
+}
+impl callable::LoxCallable for NativeTrunc {
+    fn arity(&self) -> usize {
+        1
+    }
+    fn call(
+        &self,
+        _: &mut Interpreter,
+        arguments: &Vec<object::LoxObject>,
+    ) -> InterpretResult<Option<object::LoxObject>> {
+        let x = expect_number(&arguments[0], "trunc")?;
+        Ok(Some(object::LoxObject::Number(x.trunc())))
+    }
+    fn is_property(&self) -> bool {
+        false
+    }
+}
+
+// --------------------------------------------------------------------------------------------------------------------
+
+pub struct NativeInt;
+impl NativeInt {
+    pub fn new() -> Self {
+        NativeInt {}
+    }
+}
+impl callable::LoxCallable for NativeInt {
+    fn arity(&self) -> usize {
+        1
+    }
+    fn call(
+        &self,
+        _: &mut Interpreter,
+        arguments: &Vec<object::LoxObject>,
+    ) -> InterpretResult<Option<object::LoxObject>> {
+        let x = expect_number(&arguments[0], "int")?;
+        // adding zero turns -0 into 0, so int(-0.5) displays as "0"
+        Ok(Some(object::LoxObject::Number(x.trunc() + 0.0)))
+    }
+    fn is_property(&self) -> bool {
+        false
+    }
+}
+
+// --------------------------------------------------------------------------------------------------------------------
+
+pub struct NativePadLeft;
+impl NativePadLeft {
+    pub fn new() -> Self {
+        NativePadLeft {}
+    }
+}
+impl callable::LoxCallable for NativePadLeft {
+    fn arity(&self) -> usize {
+        3
+    }
+    fn call(
+        &self,
+        _: &mut Interpreter,
+        arguments: &Vec<object::LoxObject>,
+    ) -> InterpretResult<Option<object::LoxObject>> {
+        let (s, fill) = padding(arguments, "pad_left")?;
+        Ok(Some(object::LoxObject::Str(fill + &s)))
+    }
+    fn is_property(&self) -> bool {
+        false
+    }
+}
+
+// --------------------------------------------------------------------------------------------------------------------
+
+pub struct NativePadRight;
+impl NativePadRight {
+    pub fn new() -> Self {
+        NativePadRight {}
+    }
+}
+impl callable::LoxCallable for NativePadRight {
+    fn arity(&self) -> usize {
+        3
+    }
+    fn call(
+        &self,
+        _: &mut Interpreter,
+        arguments: &Vec<object::LoxObject>,
+    ) -> InterpretResult<Option<object::LoxObject>> {
+        let (s, fill) = padding(arguments, "pad_right")?;
+        Ok(Some(object::LoxObject::Str(s + &fill)))
+    }
+    fn is_property(&self) -> bool {
+        false
+    }
+}
+
+// --------------------------------------------------------------------------------------------------------------------
+
+pub struct NativeCount;
+impl NativeCount {
+    pub fn new() -> Self {
+        NativeCount {}
+    }
+}
+impl callable::LoxCallable for NativeCount {
+    fn arity(&self) -> usize {
+        2
+    }
+    fn call(
+        &self,
+        _: &mut Interpreter,
+        arguments: &Vec<object::LoxObject>,
+    ) -> InterpretResult<Option<object::LoxObject>> {
+        let list = expect_list(&arguments[0], "count")?;
+        let count = list
+            .borrow()
+            .iter()
+            .filter(|element| is_equal(element, &arguments[1]))
+            .count();
+        Ok(Some(object::LoxObject::Number(count as f64)))
+    }
+    fn is_property(&self) -> bool {
+        false
+    }
+}
+
+// --------------------------------------------------------------------------------------------------------------------
+
+pub struct NativeIndexOfList;
+impl NativeIndexOfList {
+    pub fn new() -> Self {
+        NativeIndexOfList {}
+    }
+}
+impl callable::LoxCallable for NativeIndexOfList {
+    fn arity(&self) -> usize {
+        2
+    }
+    fn call(
+        &self,
+        _: &mut Interpreter,
+        arguments: &Vec<object::LoxObject>,
+    ) -> InterpretResult<Option<object::LoxObject>> {
+        let list = expect_list(&arguments[0], "index_of_list")?;
+        let index = list
+            .borrow()
+            .iter()
+            .position(|element| is_equal(element, &arguments[1]));
+        Ok(Some(object::LoxObject::Number(match index {
+            Some(index) => index as f64,
+            None => -1.0,
+        })))
+    }
+    fn is_property(&self) -> bool {
+        false
+    }
+}
+
+// --------------------------------------------------------------------------------------------------------------------
+
+pub struct NativeEntries;
+impl NativeEntries {
+    pub fn new() -> Self {
+        NativeEntries {}
+    }
+}
+impl callable::LoxCallable for NativeEntries {
+    fn arity(&self) -> usize {
+        1
+    }
+    fn call(
+        &self,
+        _: &mut Interpreter,
+        arguments: &Vec<object::LoxObject>,
+    ) -> InterpretResult<Option<object::LoxObject>> {
+        let map = expect_map(&arguments[0], "entries")?;
+        let entries = sorted_entries(&map.borrow());
+        Ok(Some(object::LoxObject::new_list(entries)))
+    }
+    fn is_property(&self) -> bool {
+        false
+    }
+}
+
+// --------------------------------------------------------------------------------------------------------------------
+
+pub struct NativeFlatten;
+impl NativeFlatten {
+    pub fn new() -> Self {
+        NativeFlatten {}
+    }
+}
+impl callable::LoxCallable for NativeFlatten {
+    fn arity(&self) -> usize {
+        1
+    }
+    fn call(
+        &self,
+        _: &mut Interpreter,
+        arguments: &Vec<object::LoxObject>,
+    ) -> InterpretResult<Option<object::LoxObject>> {
+        let list = expect_list(&arguments[0], "flatten")?;
+        let mut flattened = vec![];
+        for element in list.borrow().iter() {
+            match element {
+                object::LoxObject::List(inner) => flattened.extend(inner.borrow().iter().cloned()),
+                _ => flattened.push(element.clone()),
+            }
+        }
+        Ok(Some(object::LoxObject::new_list(flattened)))
+    }
+    fn is_property(&self) -> bool {
+        false
+    }
+}
+
+// --------------------------------------------------------------------------------------------------------------------
+
+pub struct NativeGroupBy;
+impl NativeGroupBy {
+    pub fn new() -> Self {
+        NativeGroupBy {}
+    }
+}
+impl callable::LoxCallable for NativeGroupBy {
+    fn arity(&self) -> usize {
+        2
+    }
+    fn call(
+        &self,
+        interpreter: &mut Interpreter,
+        arguments: &Vec<object::LoxObject>,
+    ) -> InterpretResult<Option<object::LoxObject>> {
+        // copy the elements, since the key function may modify the list
+        let elements = expect_list(&arguments[0], "group_by")?.borrow().clone();
+        expect_callable(&arguments[1], "group_by")?;
+        let mut groups: HashMap<String, Vec<object::LoxObject>> = HashMap::new();
+        for element in elements {
+            let key = match interpreter.call(&arguments[1], &vec![element.clone()], None)? {
+                object::LoxObject::Str(key) => key,
+                key => format!("{}", key),
+            };
+            groups.entry(key).or_default().push(element);
+        }
+        let groups = groups
+            .into_iter()
+            .map(|(key, elements)| (key, object::LoxObject::new_list(elements)))
+            .collect();
+        Ok(Some(object::LoxObject::new_map(groups)))
+    }
+    fn is_property(&self) -> bool {
+        false
+    }
+}
+
+// --------------------------------------------------------------------------------------------------------------------
+
+/// Copies the elements of a list from start up to but not including end. Negative
+/// indices count back from the end, and out of range indices are clamped.
+pub struct NativeSlice;
+impl NativeSlice {
+    pub fn new() -> Self {
+        NativeSlice {}
+    }
+}
+impl callable::LoxCallable for NativeSlice {
+    fn arity(&self) -> usize {
+        3
+    }
+    fn call(
+        &self,
+        _: &mut Interpreter,
+        arguments: &Vec<object::LoxObject>,
+    ) -> InterpretResult<Option<object::LoxObject>> {
+        let list = expect_list(&arguments[0], "slice")?;
+        let start = expect_integer(&arguments[1], "slice")?;
+        let end = expect_integer(&arguments[2], "slice")?;
+        let list = list.borrow();
+        let start = clamp_index(start, list.len());
+        let end = clamp_index(end, list.len()).max(start);
+        Ok(Some(object::LoxObject::new_list(list[start..end].to_vec())))
+    }
+    fn is_property(&self) -> bool {
+        false
+    }
+}
+
+// --------------------------------------------------------------------------------------------------------------------
+
+pub struct NativeEnumerate;
+impl NativeEnumerate {
+    pub fn new() -> Self {
+        NativeEnumerate {}
+    }
+}
+impl callable::LoxCallable for NativeEnumerate {
+    fn arity(&self) -> usize {
+        1
+    }
+    fn call(
+        &self,
+        _: &mut Interpreter,
+        arguments: &Vec<object::LoxObject>,
+    ) -> InterpretResult<Option<object::LoxObject>> {
+        let list = expect_list(&arguments[0], "enumerate")?;
+        let pairs = list
+            .borrow()
+            .iter()
+            .enumerate()
+            .map(|(index, value)| {
+                object::LoxObject::new_list(vec![
+                    object::LoxObject::Number(index as f64),
+                    value.clone(),
+                ])
+            })
+            .collect();
+        Ok(Some(object::LoxObject::new_list(pairs)))
+    }
+    fn is_property(&self) -> bool {
+        false
+    }
+}
+
+// --------------------------------------------------------------------------------------------------------------------
+
+pub struct NativeReverse;
+impl NativeReverse {
+    pub fn new() -> Self {
+        NativeReverse {}
+    }
+}
+impl callable::LoxCallable for NativeReverse {
+    fn arity(&self) -> usize {
+        1
+    }
+    fn call(
+        &self,
+        _: &mut Interpreter,
+        arguments: &Vec<object::LoxObject>,
+    ) -> InterpretResult<Option<object::LoxObject>> {
+        let list = expect_list(&arguments[0], "reverse")?;
+        let reversed = list.borrow().iter().rev().cloned().collect();
+        Ok(Some(object::LoxObject::new_list(reversed)))
+    }
+    fn is_property(&self) -> bool {
+        false
+    }
+}
+
+// --------------------------------------------------------------------------------------------------------------------
+
+pub struct NativeMerge;
+impl NativeMerge {
+    pub fn new() -> Self {
+        NativeMerge {}
+    }
+}
+impl callable::LoxCallable for NativeMerge {
+    fn arity(&self) -> usize {
+        2
+    }
+    fn call(
+        &self,
+        _: &mut Interpreter,
+        arguments: &Vec<object::LoxObject>,
+    ) -> InterpretResult<Option<object::LoxObject>> {
+        let a = expect_map(&arguments[0], "merge")?;
+        let b = expect_map(&arguments[1], "merge")?;
+        let mut merged = a.borrow().clone();
+        for (key, value) in b.borrow().iter() {
+            merged.insert(key.clone(), value.clone());
+        }
+        Ok(Some(object::LoxObject::new_map(merged)))
+    }
+    fn is_property(&self) -> bool {
+        false
+    }
+}
+
+// --------------------------------------------------------------------------------------------------------------------
+
+/// Returns seconds on a monotonic clock from an arbitrary starting point. Unlike
+/// clock() it never runs backwards, so differences between readings are reliable.
+pub struct NativeAbsTime;
+impl NativeAbsTime {
+    pub fn new() -> Self {
+        NativeAbsTime {}
+    }
+}
+impl callable::LoxCallable for NativeAbsTime {
+    fn arity(&self) -> usize {
+        0
+    }
+    fn call(
+        &self,
+        _: &mut Interpreter,
+        _: &Vec<object::LoxObject>,
+    ) -> InterpretResult<Option<object::LoxObject>> {
+        Ok(Some(object::LoxObject::Number(monotonic_seconds())))
+    }
+    fn is_property(&self) -> bool {
+        false
+    }
+}
+
+// --------------------------------------------------------------------------------------------------------------------
+
+pub struct NativeTryCall;
+impl NativeTryCall {
+    pub fn new() -> Self {
+        NativeTryCall {}
+    }
+}
+impl callable::LoxCallable for NativeTryCall {
+    fn arity(&self) -> usize {
+        1
+    }
+    fn call(
+        &self,
+        interpreter: &mut Interpreter,
+        arguments: &Vec<object::LoxObject>,
+    ) -> InterpretResult<Option<object::LoxObject>> {
+        if expect_callable(&arguments[0], "try_call")? != 0 {
+            return Err(InterpretResultStatus::Error(RuntimeError::with_message(
+                "try_call() expects a function taking no arguments.",
+            )));
+        }
+        let (ok, value) = match interpreter.call(&arguments[0], &vec![], None) {
+            Ok(value) => (true, value),
+            Err(InterpretResultStatus::Error(e)) => (false, object::LoxObject::Str(e.message)),
+            Err(e) => return Err(e),
+        };
+        Ok(Some(object::LoxObject::new_list(vec![
+            object::LoxObject::Boolean(ok),
+            value,
+        ])))
+    }
+    fn is_property(&self) -> bool {
+        false
+    }
+}
+
+// --------------------------------------------------------------------------------------------------------------------
+
+pub struct NativeSorted;
+impl NativeSorted {
+    pub fn new() -> Self {
+        NativeSorted {}
+    }
+}
+impl callable::LoxCallable for NativeSorted {
+    fn arity(&self) -> usize {
+        1
+    }
+    fn call(
+        &self,
+        _: &mut Interpreter,
+        arguments: &Vec<object::LoxObject>,
+    ) -> InterpretResult<Option<object::LoxObject>> {
+        let mut elements = expect_list(&arguments[0], "sorted")?.borrow().clone();
+        let mismatch = |a: &object::LoxObject, b: &object::LoxObject| {
+            InterpretResultStatus::Error(RuntimeError::with_message(&format!(
+                "sorted() can't compare \"{}\" with \"{}\".",
+                a, b
+            )))
+        };
+        // check every element against the first, then sort by that type's ordering
+        match elements.first() {
+            Some(object::LoxObject::Number(_)) | Some(object::LoxObject::Str(_)) | None => {}
+            Some(first) => {
+                return Err(InterpretResultStatus::Error(RuntimeError::with_message(
+                    &format!(
+                        "sorted() expects a list of numbers or strings, received \"{}\".",
+                        first
+                    ),
+                )))
+            }
+        }
+        for element in elements.iter().skip(1) {
+            match (&elements[0], element) {
+                (object::LoxObject::Number(_), object::LoxObject::Number(_))
+                | (object::LoxObject::Str(_), object::LoxObject::Str(_)) => {}
+                (first, other) => return Err(mismatch(first, other)),
+            }
+        }
+        elements.sort_by(|a, b| match (a, b) {
+            (object::LoxObject::Number(a), object::LoxObject::Number(b)) => a.total_cmp(b),
+            (object::LoxObject::Str(a), object::LoxObject::Str(b)) => a.cmp(b),
+            _ => std::cmp::Ordering::Equal,
+        });
+        Ok(Some(object::LoxObject::new_list(elements)))
+    }
+    fn is_property(&self) -> bool {
+        false
+    }
+}
+
+// --------------------------------------------------------------------------------------------------------------------
+
+pub struct NativeStringJoinMap;
+impl NativeStringJoinMap {
+    pub fn new() -> Self {
+        NativeStringJoinMap {}
+    }
+}
+impl callable::LoxCallable for NativeStringJoinMap {
+    fn arity(&self) -> usize {
+        3
+    }
+    fn call(
+        &self,
+        _: &mut Interpreter,
+        arguments: &Vec<object::LoxObject>,
+    ) -> InterpretResult<Option<object::LoxObject>> {
+        let map = expect_map(&arguments[0], "string_join_map")?;
+        let pair_separator = expect_string(&arguments[1], "string_join_map")?;
+        let key_value_separator = expect_string(&arguments[2], "string_join_map")?;
+        let map = map.borrow();
+        let pairs: Vec<String> = sorted_keys(&map)
+            .iter()
+            .map(|key| format!("{}{}{}", key, key_value_separator, map[key]))
+            .collect();
+        Ok(Some(object::LoxObject::Str(pairs.join(&pair_separator))))
+    }
+    fn is_property(&self) -> bool {
+        false
+    }
+}
+
+// --------------------------------------------------------------------------------------------------------------------
+
+pub struct NativeNumberFormat;
+impl NativeNumberFormat {
+    pub fn new() -> Self {
+        NativeNumberFormat {}
+    }
+}
+impl callable::LoxCallable for NativeNumberFormat {
+    fn arity(&self) -> usize {
+        1
+    }
+    fn call(
+        &self,
+        _: &mut Interpreter,
+        arguments: &Vec<object::LoxObject>,
+    ) -> InterpretResult<Option<object::LoxObject>> {
+        let n = expect_number(&arguments[0], "number_format")?;
+        if !n.is_finite() {
+            return Ok(Some(object::LoxObject::Str(object::format_number(n))));
+        }
+        let formatted = object::format_number(n.abs());
+        let (integer, fraction) = match formatted.find('.') {
+            Some(i) => formatted.split_at(i),
+            None => (formatted.as_str(), ""),
+        };
+        let mut grouped = String::new();
+        for (i, digit) in integer.chars().enumerate() {
+            if i > 0 && (integer.len() - i) % 3 == 0 {
+                grouped.push(',');
+            }
+            grouped.push(digit);
+        }
+        let sign = if n < 0.0 { "-" } else { "" };
+        Ok(Some(object::LoxObject::Str(format!(
+            "{}{}{}",
+            sign, grouped, fraction
+        ))))
+    }
+    fn is_property(&self) -> bool {
+        false
+    }
+}
+
+// --------------------------------------------------------------------------------------------------------------------
+
+// Compares `a` and `b` structurally. `visiting` holds the pairs of containers being
+// compared further up the stack; meeting one again means the structures are cyclic
+// in the same way so far, so the pair is assumed equal rather than recursing forever.
+fn deep_equals(
+    a: &object::LoxObject,
+    b: &object::LoxObject,
+    visiting: &mut Vec<(object::LoxObject, object::LoxObject)>,
+) -> bool {
+    use object::LoxObject::*;
+    if a.is_identical(b) {
+        return true;
+    }
+    if visiting
+        .iter()
+        .any(|(x, y)| x.is_identical(a) && y.is_identical(b))
+    {
+        return true;
+    }
+    visiting.push((a.clone(), b.clone()));
+    let equal = match (a, b) {
+        (List(l1), List(l2)) => {
+            let (l1, l2) = (l1.borrow().clone(), l2.borrow().clone());
+            l1.len() == l2.len() && l1.iter().zip(&l2).all(|(x, y)| deep_equals(x, y, visiting))
+        }
+        (Map(m1), Map(m2)) => {
+            fields_deep_equal(&m1.borrow().clone(), &m2.borrow().clone(), visiting)
+        }
+        (Set(s1), Set(s2)) => {
+            let (s1, s2) = (s1.borrow(), s2.borrow());
+            s1.iter().count() == s2.iter().count() && s1.iter().all(|v| s2.contains(v))
+        }
+        (Instance(i1), Instance(i2)) => {
+            i1.has_same_class(i2) && fields_deep_equal(&i1.fields(), &i2.fields(), visiting)
+        }
+        _ => false,
+    };
+    visiting.pop();
+    equal
+}
+
+fn fields_deep_equal(
+    a: &HashMap<String, object::LoxObject>,
+    b: &HashMap<String, object::LoxObject>,
+    visiting: &mut Vec<(object::LoxObject, object::LoxObject)>,
+) -> bool {
+    a.len() == b.len()
+        && a.iter().all(|(key, value)| match b.get(key) {
+            Some(other) => deep_equals(value, other, visiting),
+            None => false,
+        })
+}
+
+// --------------------------------------------------------------------------------------------------------------------
+
+pub struct NativeDeepEquals;
+impl NativeDeepEquals {
+    pub fn new() -> Self {
+        NativeDeepEquals {}
+    }
+}
+impl callable::LoxCallable for NativeDeepEquals {
+    fn arity(&self) -> usize {
+        2
+    }
+    fn call(
+        &self,
+        _: &mut Interpreter,
+        arguments: &Vec<object::LoxObject>,
+    ) -> InterpretResult<Option<object::LoxObject>> {
+        let equal = deep_equals(&arguments[0], &arguments[1], &mut vec![]);
+        Ok(Some(object::LoxObject::Boolean(equal)))
+    }
+    fn is_property(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interpreter::{Interpreter, Result};
+    use crate::object::LoxObject;
+    use crate::parser;
+    use crate::resolver;
+    use crate::scanner;
+    use crate::scanner::{Token, TokenType};
+
+    fn evaluate(expression: &str) -> Result<LoxObject> {
+        let mut scanner = scanner::Scanner::new(expression);
+        let tokens = scanner.scan_tokens();
+        let mut parser = parser::Parser::new(tokens);
+        let expr = parser.parse_expression().unwrap();
+        let mut interpreter = Interpreter::new();
+        interpreter.evaluate(&expr)
+    }
+
+    /// Evaluates `expression` with `globals` defined beforehand, for natives taking
+    /// values which can't be written as Lox literals.
+    fn evaluate_with(globals: Vec<(&str, LoxObject)>, expression: &str) -> Result<LoxObject> {
+        let mut scanner = scanner::Scanner::new(expression);
+        let tokens = scanner.scan_tokens();
+        let mut parser = parser::Parser::new(tokens);
+        let expr = parser.parse_expression().unwrap();
+        let mut interpreter = Interpreter::new();
+        for (name, value) in globals {
+            interpreter.globals().define(name, &value);
+        }
+        interpreter.evaluate(&expr)
+    }
+
+    /// Runs `program`, returning the interpreter so globals can be inspected.
+    fn execute(program: &str) -> Interpreter {
+        let mut scanner = scanner::Scanner::new(program);
+        let tokens = scanner.scan_tokens();
+        let mut parser = parser::Parser::new(tokens);
+        let statements = parser.parse().unwrap();
+        let mut interpreter = Interpreter::new();
+        let mut r = resolver::Resolver::new(&mut interpreter);
+        r.resolve(&statements)
+            .expect("Expected successful resolve pass");
+        interpreter.interpret(&statements).unwrap();
+        interpreter
+    }
+
+    fn global(interpreter: &Interpreter, name: &str) -> LoxObject {
+        let token = Token::new(TokenType::Identifier, String::from(name), None, 1, 0);
+        interpreter.environment().get(&token).unwrap()
+    }
+
+    #[test]
+    fn abs_and_abs_diff_work() {
+        let inputs = vec![
+            ("abs(-3)", LoxObject::Number(3.0)),
+            ("abs(3)", LoxObject::Number(3.0)),
+            ("abs_diff(3, 7)", LoxObject::Number(4.0)),
+            ("abs_diff(7, 3)", LoxObject::Number(4.0)),
+        ];
+        for (expression, expected_result) in inputs {
+            assert_eq!(evaluate(expression).unwrap(), expected_result);
+        }
+    }
+
+    #[test]
+    fn math_natives_reject_non_numbers() {
+        let inputs = vec![
+            "abs(\"3\")",
+            "abs(nil)",
+            "abs_diff(\"3\", 7)",
+            "abs_diff(3, true)",
+        ];
+        for expression in inputs {
+            assert!(evaluate(expression).is_err());
+        }
+    }
+
+    #[test]
+    fn identical_compares_by_identity() {
+        let interpreter = execute(
+            r#"
+            class Point {
+                init(x) { this.x = x; }
+            }
+            var a = Point(1);
+            var b = a;
+            var c = Point(1);
+            var value_0 = identical(a, b);
+            var value_1 = identical(a, c);
+            var value_2 = identical(Point, Point);
+            var value_3 = identical(1, 1);
+            var value_4 = identical("a", "b");
+            "#,
+        );
+        let expected = vec![
+            ("value_0", LoxObject::Boolean(true)),
+            ("value_1", LoxObject::Boolean(false)),
+            ("value_2", LoxObject::Boolean(true)),
+            ("value_3", LoxObject::Boolean(true)),
+            ("value_4", LoxObject::Boolean(false)),
+        ];
+        for (name, value) in expected {
+            assert_eq!(global(&interpreter, name), value);
+        }
+    }
+
+    #[test]
+    fn format_iso8601_produces_expected_timestamps() {
+        assert_eq!(format_iso8601(0), "1970-01-01T00:00:00Z");
+        assert_eq!(format_iso8601(1704164645), "2024-01-02T03:04:05Z");
+        assert_eq!(format_iso8601(951868799), "2000-02-29T23:59:59Z");
+    }
+
+    #[test]
+    fn clock_diff_measures_elapsed_seconds() {
+        let elapsed = match evaluate("clock_diff(clock())").unwrap() {
+            LoxObject::Number(n) => n,
+            other => panic!("Expected a number, received {}", other),
+        };
+        assert!((0.0..1.0).contains(&elapsed), "{}", elapsed);
+
+        let elapsed = match evaluate("clock_diff(clock() - 10)").unwrap() {
+            LoxObject::Number(n) => n,
+            other => panic!("Expected a number, received {}", other),
+        };
+        assert!((10.0..11.0).contains(&elapsed), "{}", elapsed);
+
+        assert!(evaluate("clock_diff(\"now\")").is_err());
+    }
+
+    #[test]
+    fn clock_iso_returns_iso8601_shaped_string() {
+        let timestamp = match evaluate("clock_iso()").unwrap() {
+            LoxObject::Str(s) => s,
+            other => panic!("Expected a string, received {}", other),
+        };
+        assert_eq!(timestamp.len(), 20);
+        for (i, c) in timestamp.chars().enumerate() {
+            match i {
+                4 | 7 => assert_eq!(c, '-'),
+                10 => assert_eq!(c, 'T'),
+                13 | 16 => assert_eq!(c, ':'),
+                19 => assert_eq!(c, 'Z'),
+                _ => assert!(c.is_ascii_digit(), "Unexpected char {} in {}", c, timestamp),
+            }
+        }
+    }
+
+    fn str(s: &str) -> LoxObject {
+        LoxObject::Str(String::from(s))
+    }
+
+    #[test]
+    fn keys_and_values_are_sorted_by_key() {
+        let mut entries = HashMap::new();
+        entries.insert(String::from("c"), LoxObject::Number(3.0));
+        entries.insert(String::from("a"), LoxObject::Number(1.0));
+        entries.insert(String::from("b"), LoxObject::Number(2.0));
+        let map = LoxObject::new_map(entries);
+
+        assert_eq!(
+            evaluate_with(vec![("m", map.clone())], "keys(m)").unwrap(),
+            LoxObject::new_list(vec![str("a"), str("b"), str("c")])
+        );
+        assert_eq!(
+            evaluate_with(vec![("m", map)], "values(m)").unwrap(),
+            LoxObject::new_list(vec![
+                LoxObject::Number(1.0),
+                LoxObject::Number(2.0),
+                LoxObject::Number(3.0)
+            ])
+        );
+    }
+
+    #[test]
+    fn keys_and_values_reject_non_maps() {
+        for expression in ["keys(1)", "values(\"a\")", "keys(nil)"] {
+            assert!(evaluate(expression).is_err());
+        }
+    }
+
+    #[test]
+    fn sets_add_has_and_remove_members() {
+        let interpreter = execute(
+            r#"
+            var s = set_new();
+            var added_1 = set_add(s, 1);
+            set_add(s, "two");
+            set_add(s, 3);
+            var added_1_again = set_add(s, 1);
+            var has_two = set_has(s, "two");
+            var removed_two = set_remove(s, "two");
+            var has_two_after = set_has(s, "two");
+            var removed_two_again = set_remove(s, "two");
+            "#,
+        );
+        let expected = vec![
+            ("added_1", LoxObject::Boolean(true)),
+            ("added_1_again", LoxObject::Boolean(false)),
+            ("has_two", LoxObject::Boolean(true)),
+            ("removed_two", LoxObject::Boolean(true)),
+            ("has_two_after", LoxObject::Boolean(false)),
+            ("removed_two_again", LoxObject::Boolean(false)),
+        ];
+        for (name, value) in expected {
+            assert_eq!(global(&interpreter, name), value);
+        }
+        assert_eq!(global(&interpreter, "s").to_string(), "{1, 3}");
+
+        let interpreter = execute(
+            r#"
+            var s = set_new();
+            set_add(s, 1);
+            set_add(s, "two");
+            set_add(s, 1);
+            "#,
+        );
+        assert_eq!(global(&interpreter, "s").to_string(), "{1, \"two\"}");
+    }
+
+    #[test]
+    fn sets_reject_non_primitive_members() {
+        let interpreter = execute(
+            r#"
+            class Foo {}
+            var s = set_new();
+            var f = Foo();
+            "#,
+        );
+        let s = global(&interpreter, "s");
+        let f = global(&interpreter, "f");
+        assert!(evaluate_with(vec![("s", s.clone()), ("f", f)], "set_add(s, f)").is_err());
+        assert!(evaluate_with(vec![("s", s)], "set_add(s, clock)").is_err());
+        assert!(evaluate("set_add(1, 1)").is_err());
+    }
+
+    fn numbers(values: Vec<f64>) -> LoxObject {
+        LoxObject::new_list(values.into_iter().map(LoxObject::Number).collect())
+    }
+
+    #[test]
+    fn range_produces_lists() {
+        let inputs = vec![
+            ("range(0, 5)", numbers(vec![0.0, 1.0, 2.0, 3.0, 4.0])),
+            ("range(0, 10, 2)", numbers(vec![0.0, 2.0, 4.0, 6.0, 8.0])),
+            ("range(3, 0, -1)", numbers(vec![3.0, 2.0, 1.0])),
+            ("range(2, 2)", numbers(vec![])),
+        ];
+        for (expression, expected_result) in inputs {
+            assert_eq!(evaluate(expression).unwrap(), expected_result);
+        }
+    }
+
+    #[test]
+    fn range_rejects_bad_arguments() {
+        let inputs = vec![
+            "range(0, 5, 0)",
+            "range(0, 5, -1)",
+            "range(5, 0)",
+            "range(0.5, 5)",
+            "range(0, 1000000000000)",
+            "range(0)",
+            "range(0, 5, 1, 1)",
+            "range(\"a\", 5)",
+        ];
+        for expression in inputs {
+            assert!(evaluate(expression).is_err(), "{} should fail", expression);
+        }
+    }
+
+    #[test]
+    fn deep_copy_does_not_alias_the_original() {
+        let interpreter = execute(
+            r#"
+            class Box {
+                init(value) { this.value = value; }
+            }
+            var original = Box(Box(1));
+            var copy = deep_copy(original);
+            copy.value.value = 2;
+            var value_0 = original.value.value;
+            var value_1 = copy.value.value;
+            var value_2 = identical(original, copy);
+            var value_3 = deep_copy(3);
+            "#,
+        );
+        let expected = vec![
+            ("value_0", LoxObject::Number(1.0)),
+            ("value_1", LoxObject::Number(2.0)),
+            ("value_2", LoxObject::Boolean(false)),
+            ("value_3", LoxObject::Number(3.0)),
+        ];
+        for (name, expected_value) in expected {
+            assert_eq!(global(&interpreter, name), expected_value);
+        }
+    }
+
+    #[test]
+    fn deep_copy_copies_lists() {
+        let list = numbers(vec![1.0, 2.0]);
+        let nested = LoxObject::new_list(vec![list.clone(), list.clone()]);
+        let copy = evaluate_with(vec![("l", nested.clone())], "deep_copy(l)").unwrap();
+        assert_eq!(copy, nested);
+        assert!(!copy.is_identical(&nested));
+        if let LoxObject::List(elements) = &copy {
+            assert!(!elements.borrow()[0].is_identical(&list));
+        } else {
+            panic!("Expected deep_copy() to return a list");
+        }
+    }
+
+    #[test]
+    fn deep_copy_rejects_cycles() {
+        let list = LoxObject::new_list(vec![]);
+        if let LoxObject::List(elements) = &list {
+            elements.borrow_mut().push(list.clone());
+        }
+        assert!(evaluate_with(vec![("l", list.clone())], "deep_copy(l)").is_err());
+    }
+
+    #[test]
+    fn apply_calls_functions_with_list_arguments() {
+        let interpreter = execute(
+            r#"
+            fun add(a, b) { return a + b; }
+            class Pair {
+                init(a, b) { this.sum = a + b; }
+            }
+            var value_0 = apply(add, range(1, 3));
+            var value_1 = apply(Pair, range(2, 4)).sum;
+            "#,
+        );
+        assert_eq!(global(&interpreter, "value_0"), LoxObject::Number(3.0));
+        assert_eq!(global(&interpreter, "value_1"), LoxObject::Number(5.0));
+    }
+
+    #[test]
+    fn apply_enforces_arity_and_argument_types() {
+        let inputs = vec![
+            "apply(abs_diff, range(0, 1))",
+            "apply(abs_diff, range(0, 3))",
+            "apply(abs_diff, 1)",
+            "apply(1, range(0, 2))",
+        ];
+        for expression in inputs {
+            assert!(evaluate(expression).is_err(), "{}", expression);
+        }
+        assert_eq!(
+            evaluate("apply(abs_diff, range(1, 5, 3))").unwrap(),
+            LoxObject::Number(3.0)
+        );
+    }
+
+    #[test]
+    fn floor_div_and_floor_mod_round_toward_negative_infinity() {
+        // Lox has no % operator yet; this is the truncating remainder it would produce
+        assert_eq!(-7.0 % 3.0, -1.0);
+
+        let inputs = vec![
+            ("floor_mod(-7, 3)", 2.0),
+            ("floor_mod(7, 3)", 1.0),
+            ("floor_mod(7, -3)", -2.0),
+            ("floor_mod(-7, -3)", -1.0),
+            ("floor_mod(6, 3)", 0.0),
+            ("floor_div(-7, 3)", -3.0),
+            ("floor_div(7, 3)", 2.0),
+            ("floor_div(7, -3)", -3.0),
+            ("floor_div(-7, -3)", 2.0),
+        ];
+        for (expression, expected_result) in inputs {
+            assert_eq!(
+                evaluate(expression).unwrap(),
+                LoxObject::Number(expected_result),
+                "{}",
+                expression
+            );
+        }
+    }
+
+    #[test]
+    fn floor_div_and_floor_mod_reject_bad_arguments() {
+        let inputs = vec![
+            "floor_div(1, 0)",
+            "floor_mod(1, 0)",
+            "floor_div(\"1\", 2)",
+            "floor_mod(1, nil)",
+        ];
+        for expression in inputs {
+            assert!(evaluate(expression).is_err(), "{}", expression);
+        }
+    }
+
+    fn evaluate_with_input(input: &'static str, expression: &str) -> Result<LoxObject> {
+        let mut scanner = scanner::Scanner::new(expression);
+        let tokens = scanner.scan_tokens();
+        let mut parser = parser::Parser::new(tokens);
+        let expr = parser.parse_expression().unwrap();
+        let mut interpreter = Interpreter::new();
+        interpreter.set_input(Box::new(input.as_bytes()));
+        interpreter.evaluate(&expr)
+    }
+
+    #[test]
+    fn read_all_returns_remaining_input() {
+        let inputs = vec![
+            ("first\nsecond\n\nthird", "first\nsecond\n\nthird"),
+            ("", ""),
+        ];
+        for (input, expected_result) in inputs {
+            assert_eq!(
+                evaluate_with_input(input, "read_all()").unwrap(),
+                str(expected_result)
+            );
+        }
+        assert_eq!(
+            evaluate_with_input("a\nb\n", "read_all() + read_all()").unwrap(),
+            str("a\nb\n")
+        );
+    }
+
+    #[test]
+    fn to_radix_formats_integers() {
+        let inputs = vec![
+            ("to_radix(255, 16)", "ff"),
+            ("to_radix(5, 2)", "101"),
+            ("to_radix(0, 2)", "0"),
+            ("to_radix(35, 36)", "z"),
+            ("to_radix(100, 10)", "100"),
+        ];
+        for (expression, expected_result) in inputs {
+            assert_eq!(evaluate(expression).unwrap(), str(expected_result));
+        }
+    }
+
+    #[test]
+    fn to_radix_rejects_bad_arguments() {
+        let inputs = vec![
+            "to_radix(-1, 16)",
+            "to_radix(1.5, 16)",
+            "to_radix(10, 1)",
+            "to_radix(10, 37)",
+            "to_radix(10, 2.5)",
+            "to_radix(\"10\", 2)",
+        ];
+        for expression in inputs {
+            assert!(evaluate(expression).is_err(), "{}", expression);
+        }
+    }
+
+    #[test]
+    fn env_reads_environment_variables() {
+        std::env::set_var("RLOX_ENV_TEST_VALUE", "hello");
+        std::env::remove_var("RLOX_ENV_TEST_UNSET");
+
+        assert_eq!(
+            evaluate("env(\"RLOX_ENV_TEST_VALUE\")").unwrap(),
+            str("hello")
+        );
+        assert_eq!(
+            evaluate("env(\"RLOX_ENV_TEST_UNSET\")").unwrap(),
+            LoxObject::Nil
+        );
+        assert!(evaluate("env(1)").is_err());
+    }
+
+    #[test]
+    fn type_predicates_match_their_types() {
+        let inputs = vec![
+            ("is_nil(nil)", true),
+            ("is_nil(false)", false),
+            ("is_number(1.5)", true),
+            ("is_number(\"1.5\")", false),
+            ("is_string(\"a\")", true),
+            ("is_string(nil)", false),
+            ("is_bool(false)", true),
+            ("is_bool(0)", false),
+        ];
+        for (expression, expected_result) in inputs {
+            assert_eq!(
+                evaluate(expression).unwrap(),
+                LoxObject::Boolean(expected_result),
+                "{}",
+                expression
+            );
+        }
+    }
+
+    #[test]
+    fn json_stringify_renders_nested_values() {
+        let mut entries = HashMap::new();
+        entries.insert(String::from("name"), str("lox"));
+        entries.insert(
+            String::from("versions"),
+            LoxObject::new_list(vec![
+                LoxObject::Number(1.0),
+                LoxObject::Number(2.5),
+                LoxObject::Nil,
+                LoxObject::Boolean(true),
+            ]),
+        );
+        entries.insert(String::from("empty"), LoxObject::new_map(HashMap::new()));
+        let map = LoxObject::new_map(entries);
+
+        assert_eq!(
+            evaluate_with(vec![("m", map)], "json_stringify(m)").unwrap(),
+            str(r#"{"empty":{},"name":"lox","versions":[1,2.5,null,true]}"#)
+        );
+
+        let interpreter = execute(
+            r#"
+            class Point {}
+            var p = Point();
+            p.y = 2;
+            p.x = "one";
+            var value = json_stringify(p);
+            "#,
+        );
+        assert_eq!(global(&interpreter, "value"), str(r#"{"x":"one","y":2}"#));
+    }
+
+    #[test]
+    fn json_stringify_escapes_strings() {
+        assert_eq!(
+            evaluate_with(
+                vec![("s", str("say \"hi\"\\\n\ttab\u{1}"))],
+                "json_stringify(s)"
+            )
+            .unwrap(),
+            str(r#""say \"hi\"\\\n\ttab\u0001""#)
+        );
+    }
+
+    #[test]
+    fn json_stringify_rejects_unrepresentable_values() {
+        let list = LoxObject::new_list(vec![]);
+        if let LoxObject::List(elements) = &list {
+            elements.borrow_mut().push(list.clone());
+        }
+        assert!(evaluate_with(vec![("l", list.clone())], "json_stringify(l)").is_err());
+        assert!(evaluate("json_stringify(clock)").is_err());
+    }
+
+    fn json_parse(text: &str) -> Result<LoxObject> {
+        evaluate_with(vec![("text", str(text))], "json_parse(text)")
+    }
+
+    #[test]
+    fn json_parse_produces_lox_values() {
+        let mut entries = HashMap::new();
+        entries.insert(String::from("a"), numbers(vec![1.0, 2.0]));
+        entries.insert(String::from("b"), LoxObject::new_map(HashMap::new()));
+        let inputs = vec![
+            ("-12.5e1", LoxObject::Number(-125.0)),
+            ("0", LoxObject::Number(0.0)),
+            (r#""tab\t\"quoted\" é😀""#, str("tab\t\"quoted\" é😀")),
+            (" [1, 2] ", numbers(vec![1.0, 2.0])),
+            ("[]", numbers(vec![])),
+            (
+                "[true, false, null]",
+                LoxObject::new_list(vec![
+                    LoxObject::Boolean(true),
+                    LoxObject::Boolean(false),
+                    LoxObject::Nil,
+                ]),
+            ),
+            (r#"{"a": [1, 2], "b": {}}"#, LoxObject::new_map(entries)),
+        ];
+        for (text, expected_result) in inputs {
+            assert_eq!(json_parse(text).unwrap(), expected_result, "{}", text);
+        }
+    }
+
+    #[test]
+    fn json_parse_round_trips_json_stringify() {
+        let text = r#"{"list":[1,"two",null,{"nested":true}],"name":"lox"}"#;
+        assert_eq!(
+            evaluate_with(
+                vec![("text", str(text))],
+                "json_stringify(json_parse(text))"
+            )
+            .unwrap(),
+            str(text)
+        );
+    }
+
+    #[test]
+    fn json_parse_rejects_malformed_input() {
+        let inputs = vec![
+            "",
+            "[1, 2",
+            "[1,]",
+            r#"{"a" 1}"#,
+            r#"{a: 1}"#,
+            "01",
+            "1.",
+            "tru",
+            r#""unterminated"#,
+            r#""\x""#,
+            "1 2",
+        ];
+        for text in inputs {
+            assert!(json_parse(text).is_err(), "{}", text);
+        }
+        let error = json_parse("[1, 2").unwrap_err();
+        assert_eq!(error.message, "json_parse() expected \",\" at position 5.");
+        assert!(evaluate("json_parse(1)").is_err());
+    }
+
+    #[test]
+    fn hash_is_stable_for_equal_primitives() {
+        let equal_pairs = vec![
+            ("hash(\"lox\")", "hash(\"l\" + \"ox\")"),
+            ("hash(1.5)", "hash(3 / 2)"),
+            ("hash(0)", "hash(-0)"),
+            ("hash(true)", "hash(1 < 2)"),
+            ("hash(nil)", "hash(nil)"),
+        ];
+        for (a, b) in equal_pairs {
+            assert_eq!(evaluate(a).unwrap(), evaluate(b).unwrap(), "{} {}", a, b);
+        }
+
+        assert_ne!(
+            evaluate("hash(\"lox\")").unwrap(),
+            evaluate("hash(\"xol\")").unwrap()
+        );
+        assert_ne!(
+            evaluate("hash(1)").unwrap(),
+            evaluate("hash(\"1\")").unwrap()
+        );
+    }
+
+    #[test]
+    fn hash_rejects_non_primitives() {
+        assert!(evaluate("hash(clock)").is_err());
+        assert!(evaluate_with(vec![("l", numbers(vec![1.0]))], "hash(l)").is_err());
+    }
+
+    #[test]
+    fn clamp_constrains_to_range() {
+        let inputs = vec![
+            ("clamp(-5, 0, 10)", 0.0),
+            ("clamp(5, 0, 10)", 5.0),
+            ("clamp(15, 0, 10)", 10.0),
+            ("clamp(3, 3, 3)", 3.0),
+        ];
+        for (expression, expected) in inputs {
+            assert_eq!(
+                evaluate(expression).unwrap(),
+                LoxObject::Number(expected),
+                "{}",
+                expression
+            );
+        }
+
+        assert!(evaluate("clamp(5, 10, 0)").is_err());
+        assert!(evaluate("clamp(\"5\", 0, 10)").is_err());
+    }
+
+    #[test]
+    fn assert_eq_reports_mismatched_values() {
+        assert_eq!(evaluate("assert_eq(2 * 2, 4)").unwrap(), LoxObject::Nil);
+        assert_eq!(
+            evaluate("assert_eq(\"a\" + \"b\", \"ab\")").unwrap(),
+            LoxObject::Nil
+        );
+
+        let error = evaluate("assert_eq(2 + 3, 4)").unwrap_err();
+        assert_eq!(error.message, "assert_eq() expected 4 but got 5.");
+
+        assert!(evaluate("assert_eq(1, \"1\")").is_err());
+    }
+
+    #[test]
+    fn to_list_splits_strings_and_maps() {
+        assert_eq!(
+            evaluate("to_list(\"añ€😀\")").unwrap(),
+            LoxObject::new_list(vec![str("a"), str("ñ"), str("€"), str("😀")])
+        );
+        assert_eq!(
+            evaluate("to_list(\"\")").unwrap(),
+            LoxObject::new_list(vec![])
+        );
+
+        let mut entries = HashMap::new();
+        entries.insert(String::from("b"), LoxObject::Number(2.0));
+        entries.insert(String::from("a"), LoxObject::Number(1.0));
+        assert_eq!(
+            evaluate_with(vec![("m", LoxObject::new_map(entries))], "to_list(m)").unwrap(),
+            LoxObject::new_list(vec![
+                LoxObject::new_list(vec![str("a"), LoxObject::Number(1.0)]),
+                LoxObject::new_list(vec![str("b"), LoxObject::Number(2.0)]),
+            ])
+        );
+
+        assert!(evaluate("to_list(1)").is_err());
+        assert!(evaluate("to_list(nil)").is_err());
+    }
+
+    #[test]
+    fn callable_arity_reports_parameter_counts() {
+        let interpreter = execute(
+            r#"
+            fun add(a, b) { return a + b; }
+            class Pair {
+                init(a, b) { this.sum = a + b; }
+            }
+            var value_0 = callable_arity(add);
+            var value_1 = callable_arity(fun () { return 1; });
+            var value_2 = callable_arity(Pair);
+            var value_3 = callable_arity(clock);
+            "#,
+        );
+        assert_eq!(global(&interpreter, "value_0"), LoxObject::Number(2.0));
+        assert_eq!(global(&interpreter, "value_1"), LoxObject::Number(0.0));
+        assert_eq!(global(&interpreter, "value_2"), LoxObject::Number(2.0));
+        assert_eq!(global(&interpreter, "value_3"), LoxObject::Number(0.0));
+
+        assert!(evaluate("callable_arity(1)").is_err());
+    }
+
+    #[test]
+    fn now_nanos_is_non_decreasing() {
+        let interpreter = execute(
+            r#"
+            var value_0 = now_nanos();
+            var value_1 = now_nanos();
+            "#,
+        );
+        let (first, second) = match (
+            global(&interpreter, "value_0"),
+            global(&interpreter, "value_1"),
+        ) {
+            (LoxObject::Number(first), LoxObject::Number(second)) => (first, second),
+            _ => panic!("Expected now_nanos() to return numbers"),
+        };
+        assert!(second >= first);
+        // between 2020 and 2200
+        assert!(first > 1.577e18 && first < 7.258e18, "{}", first);
+    }
+
+    #[test]
+    fn max_list_and_min_list_reduce_lists() {
+        let list = numbers(vec![3.0, 1.0, 4.0, 1.0, 5.0]);
+        assert_eq!(
+            evaluate_with(vec![("l", list.clone())], "max_list(l)").unwrap(),
+            LoxObject::Number(5.0)
+        );
+        assert_eq!(
+            evaluate_with(vec![("l", list)], "min_list(l)").unwrap(),
+            LoxObject::Number(1.0)
+        );
+        assert_eq!(
+            evaluate_with(vec![("l", numbers(vec![-2.0]))], "max_list(l)").unwrap(),
+            LoxObject::Number(-2.0)
+        );
+
+        let empty = numbers(vec![]);
+        assert!(evaluate_with(vec![("l", empty.clone())], "max_list(l)").is_err());
+        assert!(evaluate_with(vec![("l", empty)], "min_list(l)").is_err());
+
+        let mixed = LoxObject::new_list(vec![LoxObject::Number(1.0), str("2")]);
+        assert!(evaluate_with(vec![("l", mixed.clone())], "max_list(l)").is_err());
+        assert!(evaluate_with(vec![("l", mixed)], "min_list(l)").is_err());
+        assert!(evaluate("max_list(1)").is_err());
+    }
+
+    #[test]
+    fn repeat_concatenates_copies_of_a_string() {
+        assert_eq!(evaluate("repeat(\"ab\", 3)").unwrap(), str("ababab"));
+        assert_eq!(evaluate("repeat(\"x\", 0)").unwrap(), str(""));
+        assert_eq!(evaluate("repeat(\"\", 5)").unwrap(), str(""));
+
+        for expression in [
+            "repeat(\"x\", -1)",
+            "repeat(\"x\", 1.5)",
+            "repeat(1, 2)",
+            "repeat(\"x\", \"2\")",
+        ] {
+            assert!(evaluate(expression).is_err(), "{}", expression);
+        }
+
+        let error = evaluate("repeat(\"ab\", 1000000000000000000)").unwrap_err();
+        assert_eq!(
+            error.message,
+            "repeat() result would be too large, at 1000000000000000000 items."
+        );
+    }
+
+    #[test]
+    fn codepoints_returns_unicode_scalar_values() {
+        assert_eq!(
+            evaluate("codepoints(\"AB\")").unwrap(),
+            numbers(vec![65.0, 66.0])
+        );
+        // "é" is two bytes in UTF-8 and "😀" is four, but each is one codepoint
+        assert_eq!(
+            evaluate("codepoints(\"é😀\")").unwrap(),
+            numbers(vec![233.0, 128512.0])
+        );
+        assert_eq!(evaluate("codepoints(\"\")").unwrap(), numbers(vec![]));
+        assert!(evaluate("codepoints(65)").is_err());
+    }
+
+    #[test]
+    fn bool_applies_lox_truthiness() {
+        // only nil and false are falsey
+        let inputs = vec![
+            ("bool(nil)", LoxObject::Boolean(false)),
+            ("bool(false)", LoxObject::Boolean(false)),
+            ("bool(true)", LoxObject::Boolean(true)),
+            ("bool(0)", LoxObject::Boolean(true)),
+            ("bool(\"\")", LoxObject::Boolean(true)),
+        ];
+        for (expression, expected) in inputs {
+            assert_eq!(evaluate(expression).unwrap(), expected, "{}", expression);
+        }
+
+        let interpreter = execute(
+            r#"
+            class Empty {}
+            var instance = bool(Empty());
+            "#,
+        );
+        assert_eq!(global(&interpreter, "instance"), LoxObject::Boolean(true));
+    }
+
+    #[test]
+    fn floor_to_and_round_to_snap_to_multiples() {
+        let inputs = vec![
+            ("floor_to(7, 5)", LoxObject::Number(5.0)),
+            ("floor_to(9.9, 5)", LoxObject::Number(5.0)),
+            ("floor_to(10, 5)", LoxObject::Number(10.0)),
+            ("floor_to(-1, 5)", LoxObject::Number(-5.0)),
+            ("floor_to(0.7, 0.5)", LoxObject::Number(0.5)),
+            ("round_to(7, 5)", LoxObject::Number(5.0)),
+            ("round_to(8, 5)", LoxObject::Number(10.0)),
+            ("round_to(7.5, 5)", LoxObject::Number(10.0)),
+            ("round_to(-8, 5)", LoxObject::Number(-10.0)),
+            ("round_to(0.8, 0.5)", LoxObject::Number(1.0)),
+        ];
+        for (expression, expected) in inputs {
+            assert_eq!(evaluate(expression).unwrap(), expected, "{}", expression);
+        }
+
+        assert!(evaluate("floor_to(7, 0)").is_err());
+        assert!(evaluate("round_to(7, 0)").is_err());
+        assert!(evaluate("round_to(\"7\", 5)").is_err());
+        assert!(evaluate("floor_to(7, nil)").is_err());
+    }
+
+    #[test]
+    fn list_push_and_pop_round_trip() {
+        let interpreter = execute(
+            r#"
+            var xs = to_list("ab");
+            var pushed = list_push(xs, "c");
+            var popped = list_pop(xs);
+            var length = list_push(xs, "d");
+            list_pop(xs);
+            list_pop(xs);
+            list_pop(xs);
+            var empty = list_pop(xs);
+            "#,
+        );
+        assert_eq!(global(&interpreter, "pushed"), LoxObject::Number(3.0));
+        assert_eq!(global(&interpreter, "popped"), str("c"));
+        assert_eq!(global(&interpreter, "length"), LoxObject::Number(3.0));
+        assert_eq!(global(&interpreter, "empty"), LoxObject::Nil);
+        assert_eq!(global(&interpreter, "xs"), LoxObject::new_list(vec![]));
+
+        assert!(evaluate("list_push(\"abc\", 1)").is_err());
+        assert!(evaluate("list_pop(nil)").is_err());
+    }
+
+    #[test]
+    fn list_concat_returns_a_new_list() {
+        let interpreter = execute(
+            r#"
+            var a = to_list("ab");
+            var b = to_list("c");
+            var c = list_concat(a, b);
+            list_push(c, "d");
+            var aliased = identical(c, a) or identical(c, b);
+            "#,
+        );
+        assert_eq!(
+            global(&interpreter, "c"),
+            LoxObject::new_list(vec![str("a"), str("b"), str("c"), str("d")])
+        );
+        assert_eq!(
+            global(&interpreter, "a"),
+            LoxObject::new_list(vec![str("a"), str("b")])
+        );
+        assert_eq!(
+            global(&interpreter, "b"),
+            LoxObject::new_list(vec![str("c")])
+        );
+        assert_eq!(global(&interpreter, "aliased"), LoxObject::Boolean(false));
+
+        assert!(evaluate("list_concat(to_list(\"a\"), 1)").is_err());
+    }
+
+    #[test]
+    fn assert_throws_passes_only_when_the_function_errors() {
+        let interpreter = execute(
+            r#"
+            fun bad() {
+                return nil + 1;
+            }
+            var passed = assert_throws(fun () { bad(); });
+            var after = "still running";
+            "#,
+        );
+        assert_eq!(global(&interpreter, "passed"), LoxObject::Nil);
+        assert_eq!(global(&interpreter, "after"), str("still running"));
+
+        let error = evaluate("assert_throws(fun () { return 1; })").unwrap_err();
+        assert_eq!(
+            error.message,
+            "assert_throws() expected the function to raise an error."
+        );
+
+        assert!(evaluate("assert_throws(fun (x) { return x + nil; })").is_err());
+        assert!(evaluate("assert_throws(nil)").is_err());
+    }
+
+    #[test]
+    fn is_instance_of_follows_the_class_chain() {
+        let interpreter = execute(
+            r#"
+            class Animal {}
+            class Dog < Animal {}
+            class Cat < Animal {}
+            var dog = Dog();
+            var direct = is_instance_of(dog, Dog);
+            var inherited = is_instance_of(dog, Animal);
+            var sibling = is_instance_of(dog, Cat);
+            var parent = is_instance_of(Animal(), Dog);
+            var number = is_instance_of(4, Dog);
+            var of_class = is_instance_of(Dog, Dog);
+            var none = is_instance_of(nil, Animal);
+            "#,
+        );
+        assert_eq!(global(&interpreter, "direct"), LoxObject::Boolean(true));
+        assert_eq!(global(&interpreter, "inherited"), LoxObject::Boolean(true));
+        assert_eq!(global(&interpreter, "sibling"), LoxObject::Boolean(false));
+        assert_eq!(global(&interpreter, "parent"), LoxObject::Boolean(false));
+        assert_eq!(global(&interpreter, "number"), LoxObject::Boolean(false));
+        assert_eq!(global(&interpreter, "of_class"), LoxObject::Boolean(false));
+        assert_eq!(global(&interpreter, "none"), LoxObject::Boolean(false));
+
+        assert!(evaluate("is_instance_of(1, 2)").is_err());
+        assert!(evaluate("is_instance_of(nil, nil)").is_err());
+    }
+
+    #[test]
+    fn sum_totals_numeric_lists() {
+        assert_eq!(
+            evaluate_with(vec![("l", numbers(vec![1.0, 2.0, 3.0]))], "sum(l)").unwrap(),
+            LoxObject::Number(6.0)
+        );
+        assert_eq!(
+            evaluate_with(vec![("l", numbers(vec![0.5, -2.0]))], "sum(l)").unwrap(),
+            LoxObject::Number(-1.5)
+        );
+        assert_eq!(
+            evaluate_with(vec![("l", numbers(vec![]))], "sum(l)").unwrap(),
+            LoxObject::Number(0.0)
+        );
+
+        let mixed = LoxObject::new_list(vec![LoxObject::Number(1.0), str("2")]);
+        assert!(evaluate_with(vec![("l", mixed)], "sum(l)").is_err());
+        assert!(evaluate("sum(6)").is_err());
+    }
+
+    #[test]
+    fn char_at_indexes_by_character() {
+        assert_eq!(evaluate("char_at(\"abc\", 0)").unwrap(), str("a"));
+        assert_eq!(evaluate("char_at(\"abc\", 2)").unwrap(), str("c"));
+        // "é" is two bytes in UTF-8, but indexing is by character
+        assert_eq!(evaluate("char_at(\"é😀x\", 1)").unwrap(), str("😀"));
+        assert_eq!(evaluate("char_at(\"é😀x\", 2)").unwrap(), str("x"));
+
+        let error = evaluate("char_at(\"abc\", 3)").unwrap_err();
+        assert_eq!(
+            error.message,
+            "char_at() index 3 is out of range for a string of length 3."
+        );
+        assert!(evaluate("char_at(\"abc\", -1)").is_err());
+        assert!(evaluate("char_at(\"abc\", 1.5)").is_err());
+        assert!(evaluate("char_at(\"\", 0)").is_err());
+        assert!(evaluate("char_at(123, 0)").is_err());
+    }
+
+    #[test]
+    fn panic_always_raises() {
+        let error = evaluate("panic(\"boom\")").unwrap_err();
+        assert_eq!(error.message, "panic: boom");
+        assert_eq!(evaluate("panic(42)").unwrap_err().message, "panic: 42");
+        assert_eq!(evaluate("panic(nil)").unwrap_err().message, "panic: nil");
+    }
+
+    #[test]
+    fn zip_pairs_lists_up_to_the_shorter() {
+        let pair = |x: LoxObject, y: LoxObject| LoxObject::new_list(vec![x, y]);
+        let numbers_and_letters = vec![
+            ("a", numbers(vec![1.0, 2.0])),
+            ("b", LoxObject::new_list(vec![str("a"), str("b")])),
+        ];
+        assert_eq!(
+            evaluate_with(numbers_and_letters, "zip(a, b)").unwrap(),
+            LoxObject::new_list(vec![
+                pair(LoxObject::Number(1.0), str("a")),
+                pair(LoxObject::Number(2.0), str("b")),
+            ])
+        );
+
+        let mismatched = vec![
+            ("a", numbers(vec![1.0, 2.0, 3.0])),
+            ("b", numbers(vec![4.0])),
+        ];
+        assert_eq!(
+            evaluate_with(mismatched.clone(), "zip(a, b)").unwrap(),
+            LoxObject::new_list(vec![pair(LoxObject::Number(1.0), LoxObject::Number(4.0))])
+        );
+        assert_eq!(
+            evaluate_with(mismatched, "zip(b, a)").unwrap(),
+            LoxObject::new_list(vec![pair(LoxObject::Number(4.0), LoxObject::Number(1.0))])
+        );
+        assert_eq!(
+            evaluate_with(vec![("a", numbers(vec![]))], "zip(a, a)").unwrap(),
+            numbers(vec![])
+        );
+
+        assert!(evaluate_with(vec![("a", numbers(vec![1.0]))], "zip(a, \"b\")").is_err());
+        assert!(evaluate("zip(nil, nil)").is_err());
+    }
+
+    #[test]
+    fn str_reverse_reverses_characters() {
+        assert_eq!(evaluate("str_reverse(\"abc\")").unwrap(), str("cba"));
+        assert_eq!(evaluate("str_reverse(\"añ😀\")").unwrap(), str("😀ña"));
+        assert_eq!(evaluate("str_reverse(\"\")").unwrap(), str(""));
+        assert!(evaluate("str_reverse(123)").is_err());
+    }
+
+    #[test]
+    fn chars_splits_strings_into_characters() {
+        assert_eq!(
+            evaluate("chars(\"abc\")").unwrap(),
+            LoxObject::new_list(vec![str("a"), str("b"), str("c")])
+        );
+        assert_eq!(
+            evaluate("chars(\"héllo\")").unwrap(),
+            LoxObject::new_list(vec![str("h"), str("é"), str("l"), str("l"), str("o")])
+        );
+        assert_eq!(
+            evaluate("chars(\"\")").unwrap(),
+            LoxObject::new_list(vec![])
+        );
+        assert!(evaluate("chars(1)").is_err());
+    }
+
+    #[test]
+    fn min_by_and_max_by_compare_keys() {
+        let interpreter = execute(
+            r#"
+            class Person {
+                init(name, age) {
+                    this.name = name;
+                    this.age = age;
+                }
+            }
+            var people = to_list("");
+            list_push(people, Person("ann", 31));
+            list_push(people, Person("bob", 25));
+            list_push(people, Person("cat", 47));
+            list_push(people, Person("dan", 25));
+            fun age(p) { return p.age; }
+            var youngest = min_by(people, age).name;
+            var oldest = max_by(people, age).name;
+            "#,
+        );
+        // ties go to the first element
+        assert_eq!(global(&interpreter, "youngest"), str("bob"));
+        assert_eq!(global(&interpreter, "oldest"), str("cat"));
+
+        let error = evaluate("min_by(to_list(\"\"), clock)").unwrap_err();
+        assert_eq!(error.message, "min_by() expects a non-empty list.");
+        assert!(evaluate("max_by(to_list(\"ab\"), fun (s) { return s; })").is_err());
+        assert!(evaluate("max_by(\"ab\", fun (s) { return 1; })").is_err());
+    }
+
+    #[test]
+    fn abs_sign_and_trunc_handle_each_sign() {
+        let inputs = vec![
+            ("abs(-2.5)", 2.5),
+            ("abs(0)", 0.0),
+            ("abs(2.5)", 2.5),
+            ("sign(-2.5)", -1.0),
+            ("sign(0)", 0.0),
+            ("sign(-0)", 0.0),
+            ("sign(2.5)", 1.0),
+            ("trunc(-2.5)", -2.0),
+            ("trunc(0)", 0.0),
+            ("trunc(2.5)", 2.0),
+        ];
+        for (expression, expected) in inputs {
+            assert_eq!(
+                evaluate(expression).unwrap(),
+                LoxObject::Number(expected),
+                "{}",
+                expression
+            );
+        }
+
+        assert!(evaluate("abs(\"1\")").is_err());
+        assert!(evaluate("sign(nil)").is_err());
+        assert!(evaluate("trunc(true)").is_err());
+    }
+
+    #[test]
+    fn int_truncates_toward_zero_and_displays_without_a_decimal_point() {
+        let inputs = vec![
+            ("int(3.9)", "3"),
+            ("int(-3.9)", "-3"),
+            ("int(-0.5)", "0"),
+            ("int(42)", "42"),
+        ];
+        for (expression, expected) in inputs {
+            let result = evaluate(expression).unwrap();
+            assert_eq!(format!("{}", result), expected, "{}", expression);
+        }
+        assert_eq!(evaluate("int(3.9) == 3").unwrap(), LoxObject::Boolean(true));
+
+        let error = evaluate("int(\"3\")").unwrap_err();
+        assert_eq!(
+            error.message,
+            "int() expects a number argument, received \"3\"."
+        );
+    }
+
+    #[test]
+    fn pad_left_and_pad_right_fill_to_width() {
+        let inputs = vec![
+            ("pad_left(\"7\", 3, \"0\")", "007"),
+            ("pad_right(\"7\", 3, \" \")", "7  "),
+            ("pad_left(\"\", 2, \"-\")", "--"),
+            ("pad_left(\"héllo\", 6, \"é\")", "éhéllo"),
+            ("pad_left(\"1234\", 3, \"0\")", "1234"),
+            ("pad_right(\"123\", 3, \"0\")", "123"),
+            ("pad_right(\"abc\", 0, \" \")", "abc"),
+        ];
+        for (expression, expected) in inputs {
+            assert_eq!(
+                evaluate(expression).unwrap(),
+                str(expected),
+                "{}",
+                expression
+            );
+        }
+
+        let error = evaluate("pad_left(\"7\", 3, \"ab\")").unwrap_err();
+        assert_eq!(
+            error.message,
+            "pad_left() expects a single character fill, received \"ab\"."
+        );
+        assert!(evaluate("pad_right(\"7\", 3, \"\")").is_err());
+        assert!(evaluate("pad_right(\"7\", -1, \" \")").is_err());
+        assert!(evaluate("pad_right(\"7\", 1.5, \" \")").is_err());
+        assert!(evaluate("pad_left(7, 3, \"0\")").is_err());
+        assert!(evaluate("pad_left(\"ab\", 100000000000000, \" \")").is_err());
+        assert!(evaluate("pad_right(\"ab\", 100000000000000, \" \")").is_err());
+    }
+
+    #[test]
+    fn count_and_index_of_list_search_by_equality() {
+        let interpreter = execute(
+            r#"
+            var list = to_list("abcab");
+            list_push(list, 1);
+            var a_count = count(list, "a");
+            var one_count = count(list, 1);
+            var z_count = count(list, "z");
+            var b_index = index_of_list(list, "b");
+            var one_index = index_of_list(list, 1);
+            var z_index = index_of_list(list, "z");
+            var empty_index = index_of_list(to_list(""), "a");
+            "#,
+        );
+        assert_eq!(global(&interpreter, "a_count"), LoxObject::Number(2.0));
+        assert_eq!(global(&interpreter, "one_count"), LoxObject::Number(1.0));
+        assert_eq!(global(&interpreter, "z_count"), LoxObject::Number(0.0));
+        assert_eq!(global(&interpreter, "b_index"), LoxObject::Number(1.0));
+        assert_eq!(global(&interpreter, "one_index"), LoxObject::Number(5.0));
+        assert_eq!(global(&interpreter, "z_index"), LoxObject::Number(-1.0));
+        assert_eq!(global(&interpreter, "empty_index"), LoxObject::Number(-1.0));
+
+        let error = evaluate("count(\"aa\", \"a\")").unwrap_err();
+        assert_eq!(
+            error.message,
+            "count() expects a list argument, received \"aa\"."
+        );
+        assert!(evaluate("index_of_list(nil, 1)").is_err());
+    }
+
+    #[test]
+    fn entries_returns_pairs_sorted_by_key() {
+        let mut entries = HashMap::new();
+        entries.insert(String::from("c"), LoxObject::Number(3.0));
+        entries.insert(String::from("a"), LoxObject::Number(1.0));
+        entries.insert(String::from("b"), str("two"));
+        assert_eq!(
+            evaluate_with(vec![("m", LoxObject::new_map(entries))], "entries(m)").unwrap(),
+            LoxObject::new_list(vec![
+                LoxObject::new_list(vec![str("a"), LoxObject::Number(1.0)]),
+                LoxObject::new_list(vec![str("b"), str("two")]),
+                LoxObject::new_list(vec![str("c"), LoxObject::Number(3.0)]),
+            ])
+        );
+        assert_eq!(
+            evaluate_with(
+                vec![("m", LoxObject::new_map(HashMap::new()))],
+                "entries(m)"
+            )
+            .unwrap(),
+            LoxObject::new_list(vec![])
+        );
+
+        let error = evaluate("entries(\"ab\")").unwrap_err();
+        assert_eq!(
+            error.message,
+            "entries() expects a map argument, received \"ab\"."
+        );
+        assert!(evaluate("entries(to_list(\"ab\"))").is_err());
+    }
+
+    #[test]
+    fn flatten_removes_one_level_of_nesting() {
+        let n = LoxObject::Number;
+        let nested = LoxObject::new_list(vec![
+            LoxObject::new_list(vec![n(1.0), n(2.0)]),
+            LoxObject::new_list(vec![n(3.0)]),
+            LoxObject::new_list(vec![]),
+            LoxObject::new_list(vec![n(4.0), n(5.0)]),
+        ]);
+        assert_eq!(
+            evaluate_with(vec![("l", nested)], "flatten(l)").unwrap(),
+            LoxObject::new_list(vec![n(1.0), n(2.0), n(3.0), n(4.0), n(5.0)])
+        );
+
+        let mixed = LoxObject::new_list(vec![
+            n(1.0),
+            LoxObject::new_list(vec![str("a"), LoxObject::new_list(vec![n(2.0)])]),
+            LoxObject::Nil,
+        ]);
+        assert_eq!(
+            evaluate_with(vec![("l", mixed)], "flatten(l)").unwrap(),
+            LoxObject::new_list(vec![
+                n(1.0),
+                str("a"),
+                LoxObject::new_list(vec![n(2.0)]),
+                LoxObject::Nil,
+            ])
+        );
+
+        let error = evaluate("flatten(1)").unwrap_err();
+        assert_eq!(
+            error.message,
+            "flatten() expects a list argument, received \"1\"."
+        );
+    }
+
+    #[test]
+    fn group_by_collects_elements_by_key() {
+        let interpreter = execute(
+            r#"
+            var by_parity = group_by(range(1, 6), fun (n) { return floor_mod(n, 2) == 0 ? "even" : "odd"; });
+            var by_size = group_by(range(1, 6), fun (n) { return n > 3; });
+            var empty = group_by(range(0, 0), fun (n) { return n; });
+            "#,
+        );
+        let mut expected = HashMap::new();
+        expected.insert(String::from("odd"), numbers(vec![1.0, 3.0, 5.0]));
+        expected.insert(String::from("even"), numbers(vec![2.0, 4.0]));
+        assert_eq!(
+            global(&interpreter, "by_parity"),
+            LoxObject::new_map(expected)
+        );
+
+        // keys which aren't strings are converted to strings
+        let mut expected = HashMap::new();
+        expected.insert(String::from("true"), numbers(vec![4.0, 5.0]));
+        expected.insert(String::from("false"), numbers(vec![1.0, 2.0, 3.0]));
+        assert_eq!(
+            global(&interpreter, "by_size"),
+            LoxObject::new_map(expected)
+        );
+        assert_eq!(
+            global(&interpreter, "empty"),
+            LoxObject::new_map(HashMap::new())
+        );
+
+        let error = evaluate("group_by(to_list(\"\"), 1)").unwrap_err();
+        assert_eq!(
+            error.message,
+            "group_by() expects a function argument, received \"1\"."
+        );
+        assert!(evaluate("group_by(\"ab\", clock)").is_err());
+    }
+
+    #[test]
+    fn slice_copies_clamped_ranges() {
+        let list = numbers(vec![10.0, 20.0, 30.0, 40.0]);
+        let inputs = vec![
+            ("slice(l, 1, 3)", vec![20.0, 30.0]),
+            ("slice(l, 0, 4)", vec![10.0, 20.0, 30.0, 40.0]),
+            ("slice(l, 2, 2)", vec![]),
+            ("slice(l, -2, 4)", vec![30.0, 40.0]),
+            ("slice(l, 0, -1)", vec![10.0, 20.0, 30.0]),
+            ("slice(l, -1, -3)", vec![]),
+            ("slice(l, -10, 2)", vec![10.0, 20.0]),
+            ("slice(l, 2, 100)", vec![30.0, 40.0]),
+            ("slice(l, 5, 6)", vec![]),
+        ];
+        for (expression, expected) in inputs {
+            assert_eq!(
+                evaluate_with(vec![("l", list.clone())], expression).unwrap(),
+                numbers(expected),
+                "{}",
+                expression
+            );
+        }
+
+        // the slice is a copy, not a view of the original
+        let interpreter = execute(
+            r#"
+            var original = range(0, 3);
+            var copy = slice(original, 0, 3);
+            list_push(copy, 3);
+            var original_last = list_pop(original);
+            "#,
+        );
+        assert_eq!(
+            global(&interpreter, "original_last"),
+            LoxObject::Number(2.0)
+        );
+
+        assert!(evaluate_with(vec![("l", list.clone())], "slice(l, 0.5, 2)").is_err());
+        let error = evaluate("slice(\"abc\", 0, 1)").unwrap_err();
+        assert_eq!(
+            error.message,
+            "slice() expects a list argument, received \"abc\"."
+        );
+    }
+
+    #[test]
+    fn enumerate_pairs_indices_with_values() {
+        assert_eq!(
+            evaluate("enumerate(to_list(\"abc\"))").unwrap(),
+            LoxObject::new_list(vec![
+                LoxObject::new_list(vec![LoxObject::Number(0.0), str("a")]),
+                LoxObject::new_list(vec![LoxObject::Number(1.0), str("b")]),
+                LoxObject::new_list(vec![LoxObject::Number(2.0), str("c")]),
+            ])
+        );
+        assert_eq!(
+            evaluate("enumerate(to_list(\"\"))").unwrap(),
+            LoxObject::new_list(vec![])
+        );
+
+        let error = evaluate("enumerate(\"abc\")").unwrap_err();
+        assert_eq!(
+            error.message,
+            "enumerate() expects a list argument, received \"abc\"."
+        );
+    }
+
+    #[test]
+    fn reverse_returns_a_reversed_copy() {
+        let interpreter = execute(
+            r#"
+            var original = range(1, 4);
+            var reversed = reverse(original);
+            list_push(reversed, 0);
+            var empty = reverse(range(0, 0));
+            "#,
+        );
+        assert_eq!(
+            global(&interpreter, "original"),
+            numbers(vec![1.0, 2.0, 3.0])
+        );
+        assert_eq!(
+            global(&interpreter, "reversed"),
+            numbers(vec![3.0, 2.0, 1.0, 0.0])
+        );
+        assert_eq!(global(&interpreter, "empty"), numbers(vec![]));
+
+        let error = evaluate("reverse(\"abc\")").unwrap_err();
+        assert_eq!(
+            error.message,
+            "reverse() expects a list argument, received \"abc\"."
+        );
+    }
+
+    #[test]
+    fn merge_prefers_the_second_map() {
+        let map = |entries: Vec<(&str, f64)>| {
+            LoxObject::new_map(
+                entries
+                    .into_iter()
+                    .map(|(key, value)| (String::from(key), LoxObject::Number(value)))
+                    .collect(),
+            )
+        };
+        let a = map(vec![("x", 1.0), ("y", 2.0)]);
+        let b = map(vec![("y", 3.0), ("z", 4.0)]);
+        let globals = || vec![("a", a.clone()), ("b", b.clone())];
+        assert_eq!(
+            evaluate_with(globals(), "merge(a, b)").unwrap(),
+            map(vec![("x", 1.0), ("y", 3.0), ("z", 4.0)])
+        );
+        assert_eq!(
+            evaluate_with(globals(), "merge(b, a)").unwrap(),
+            map(vec![("x", 1.0), ("y", 2.0), ("z", 4.0)])
+        );
+        assert_eq!(a, map(vec![("x", 1.0), ("y", 2.0)]));
+        assert_eq!(b, map(vec![("y", 3.0), ("z", 4.0)]));
+
+        // the result is a new map
+        let merged = evaluate_with(globals(), "merge(a, a)").unwrap();
+        assert_eq!(merged, a);
+        assert!(!merged.is_identical(&a));
+
+        let error = evaluate_with(globals(), "merge(a, 1)").unwrap_err();
+        assert_eq!(
+            error.message,
+            "merge() expects a map argument, received \"1\"."
+        );
+        assert!(evaluate_with(globals(), "merge(nil, b)").is_err());
+    }
+
+    #[test]
+    fn abs_time_never_runs_backwards() {
+        let first = evaluate("abs_time()").unwrap();
+        let second = evaluate("abs_time()").unwrap();
+        match (first, second) {
+            (LoxObject::Number(first), LoxObject::Number(second)) => {
+                assert!(first >= 0.0);
+                assert!(second >= first);
+            }
+            results => panic!("Expected numbers, got {:?}", results),
+        }
+    }
+
+    #[test]
+    fn try_call_captures_errors() {
+        let interpreter = execute(
+            r#"
+            var succeeded = try_call(fun () { return 42; });
+            var no_value = try_call(fun () {});
+            var failed = try_call(fun () { return 1 / 0; });
+            var panicked = try_call(fun () { panic("oops"); });
+            "#,
+        );
+        assert_eq!(
+            global(&interpreter, "succeeded"),
+            LoxObject::new_list(vec![LoxObject::Boolean(true), LoxObject::Number(42.0)])
+        );
+        assert_eq!(
+            global(&interpreter, "no_value"),
+            LoxObject::new_list(vec![LoxObject::Boolean(true), LoxObject::Nil])
+        );
+        assert_eq!(
+            global(&interpreter, "failed"),
+            LoxObject::new_list(vec![
+                LoxObject::Boolean(false),
+                str("Attempt to divide by zero.")
+            ])
+        );
+        assert_eq!(
+            global(&interpreter, "panicked"),
+            LoxObject::new_list(vec![LoxObject::Boolean(false), str("panic: oops")])
+        );
+
+        let error = evaluate("try_call(1)").unwrap_err();
+        assert_eq!(
+            error.message,
+            "try_call() expects a function argument, received \"1\"."
+        );
+        assert!(evaluate("try_call(abs)").is_err());
+    }
+
+    #[test]
+    fn sorted_returns_a_sorted_copy() {
+        let original = numbers(vec![3.0, 1.0, 2.0]);
+        assert_eq!(
+            evaluate_with(vec![("l", original.clone())], "sorted(l)").unwrap(),
+            numbers(vec![1.0, 2.0, 3.0])
+        );
+        assert_eq!(original, numbers(vec![3.0, 1.0, 2.0]));
+        assert_eq!(
+            evaluate_with(vec![("l", numbers(vec![]))], "sorted(l)").unwrap(),
+            numbers(vec![])
+        );
+
+        let words = LoxObject::new_list(vec![str("pear"), str("apple"), str("fig")]);
+        assert_eq!(
+            evaluate_with(vec![("l", words)], "sorted(l)").unwrap(),
+            LoxObject::new_list(vec![str("apple"), str("fig"), str("pear")])
+        );
+
+        let mixed = LoxObject::new_list(vec![LoxObject::Number(1.0), str("a")]);
+        let error = evaluate_with(vec![("l", mixed)], "sorted(l)").unwrap_err();
+        assert_eq!(error.message, "sorted() can't compare \"1\" with \"a\".");
+
+        let nils = LoxObject::new_list(vec![LoxObject::Nil]);
+        let error = evaluate_with(vec![("l", nils)], "sorted(l)").unwrap_err();
+        assert_eq!(
+            error.message,
+            "sorted() expects a list of numbers or strings, received \"nil\"."
+        );
+    }
+
+    #[test]
+    fn string_join_map_joins_sorted_pairs() {
+        let mut entries = HashMap::new();
+        entries.insert(String::from("b"), LoxObject::Number(2.0));
+        entries.insert(String::from("a"), LoxObject::Number(1.0));
+        entries.insert(String::from("c"), str("three"));
+        let map = LoxObject::new_map(entries);
+
+        assert_eq!(
+            evaluate_with(vec![("m", map.clone())], "string_join_map(m, \";\", \"=\")").unwrap(),
+            str("a=1;b=2;c=three")
+        );
+        let empty = LoxObject::new_map(HashMap::new());
+        assert_eq!(
+            evaluate_with(vec![("m", empty)], "string_join_map(m, \";\", \"=\")").unwrap(),
+            str("")
+        );
+
+        let error = evaluate("string_join_map(\"abc\", \";\", \"=\")").unwrap_err();
+        assert_eq!(
+            error.message,
+            "string_join_map() expects a map argument, received \"abc\"."
+        );
+        let error = evaluate_with(vec![("m", map)], "string_join_map(m, 1, \"=\")").unwrap_err();
+        assert_eq!(
+            error.message,
+            "string_join_map() expects a string argument, received \"1\"."
+        );
+    }
+
+    #[test]
+    fn number_format_groups_thousands() {
+        let inputs = vec![
+            ("number_format(1234567)", "1,234,567"),
+            ("number_format(123)", "123"),
+            ("number_format(1000)", "1,000"),
+            ("number_format(0)", "0"),
+            ("number_format(-9876543)", "-9,876,543"),
+            ("number_format(-100)", "-100"),
+            ("number_format(1234567.891)", "1,234,567.891"),
+            ("number_format(-1234.5)", "-1,234.5"),
+            ("number_format(0.25)", "0.25"),
+        ];
+        for (expr, expected) in inputs {
+            assert_eq!(evaluate(expr).unwrap(), str(expected), "{}", expr);
+        }
+
+        let error = evaluate("number_format(\"1000\")").unwrap_err();
+        assert_eq!(
+            error.message,
+            "number_format() expects a number argument, received \"1000\"."
+        );
+    }
+
+    #[test]
+    fn deep_equals_compares_structurally() {
+        let nested = || {
+            LoxObject::new_list(vec![
+                LoxObject::Number(1.0),
+                LoxObject::new_list(vec![LoxObject::Number(2.0), str("x")]),
+            ])
+        };
+        let map = |value: LoxObject| {
+            let mut entries = HashMap::new();
+            entries.insert(String::from("a"), LoxObject::Number(1.0));
+            entries.insert(String::from("b"), value);
+            LoxObject::new_map(entries)
+        };
+        let equal = |a: LoxObject, b: LoxObject| {
+            evaluate_with(vec![("a", a), ("b", b)], "deep_equals(a, b)").unwrap()
+        };
+
+        assert_eq!(equal(nested(), nested()), LoxObject::Boolean(true));
+        assert_eq!(
+            equal(map(nested()), map(nested())),
+            LoxObject::Boolean(true)
+        );
+        assert_eq!(equal(str("x"), str("x")), LoxObject::Boolean(true));
+        assert_eq!(
+            equal(LoxObject::Nil, LoxObject::Nil),
+            LoxObject::Boolean(true)
+        );
+
+        let unequal = vec![
+            (nested(), numbers(vec![1.0, 2.0])),
+            (numbers(vec![1.0, 2.0]), numbers(vec![1.0, 2.0, 3.0])),
+            (map(nested()), map(numbers(vec![1.0]))),
+            (map(LoxObject::Nil), LoxObject::new_map(HashMap::new())),
+            (numbers(vec![1.0]), LoxObject::Number(1.0)),
+            (str("1"), LoxObject::Number(1.0)),
+            (LoxObject::Nil, LoxObject::Boolean(false)),
+        ];
+        for (a, b) in unequal {
+            assert_eq!(
+                equal(a.clone(), b.clone()),
+                LoxObject::Boolean(false),
+                "{} {}",
+                a,
+                b
+            );
+        }
+
+        let interpreter = execute(
+            r#"
+            class Point {
+                init(x, y) {
+                    this.x = x;
+                    this.y = y;
+                }
+            }
+            class Other {
+                init(x, y) {
+                    this.x = x;
+                    this.y = y;
+                }
+            }
+            var same = deep_equals(Point(1, range(0, 2)), Point(1, range(0, 2)));
+            var different_fields = deep_equals(Point(1, 2), Point(1, 3));
+            var different_classes = deep_equals(Point(1, 2), Other(1, 2));
+
+            var a = range(0, 2);
+            list_push(a, a);
+            var b = range(0, 2);
+            list_push(b, b);
+            var cyclic = deep_equals(a, b);
+            list_push(b, 3);
+            var cyclic_different = deep_equals(a, b);
+            "#,
+        );
+        assert_eq!(global(&interpreter, "same"), LoxObject::Boolean(true));
+        assert_eq!(
+            global(&interpreter, "different_fields"),
+            LoxObject::Boolean(false)
+        );
+        assert_eq!(
+            global(&interpreter, "different_classes"),
+            LoxObject::Boolean(false)
+        );
+        assert_eq!(global(&interpreter, "cyclic"), LoxObject::Boolean(true));
+        assert_eq!(
+            global(&interpreter, "cyclic_different"),
+            LoxObject::Boolean(false)
+        );
+    }
+}
@@ -1,9 +1,11 @@
+use std::collections::HashMap;
 use std::fmt;
 use std::{cell::RefCell, rc::Rc};
 
 use crate::callable::LoxCallable;
 use crate::class::{LoxClass, LoxInstance};
 use crate::scanner::Literal;
+use crate::set::LoxSet;
 
 #[derive(Debug, Clone)]
 pub enum LoxObject {
@@ -11,8 +13,11 @@ pub enum LoxObject {
     Callable(Rc<RefCell<dyn LoxCallable>>),
     Class(LoxClass),
     Instance(LoxInstance),
+    List(Rc<RefCell<Vec<LoxObject>>>),
+    Map(Rc<RefCell<HashMap<String, LoxObject>>>),
     Nil,
     Number(f64),
+    Set(Rc<RefCell<LoxSet>>),
     Str(String),
     Undefined,
 }
@@ -28,6 +33,14 @@ impl LoxObject {
         }
     }
 
+    pub fn new_list(elements: Vec<LoxObject>) -> Self {
+        LoxObject::List(Rc::new(RefCell::new(elements)))
+    }
+
+    pub fn new_map(entries: HashMap<String, LoxObject>) -> Self {
+        LoxObject::Map(Rc::new(RefCell::new(entries)))
+    }
+
     pub fn is_truthy(&self) -> bool {
         match self {
             LoxObject::Nil => false,     // nil is falsey
@@ -35,6 +48,92 @@ impl LoxObject {
             _ => true,                   // everything else is *something*, which is truthy
         }
     }
+
+    /// Returns true if `self` and `other` are the same underlying object. Reference types
+    /// (callables, classes, instances) compare by identity, primitives by value.
+    pub fn is_identical(&self, other: &LoxObject) -> bool {
+        use LoxObject::*;
+        match (self, other) {
+            (Callable(c1), Callable(c2)) => Rc::ptr_eq(c1, c2),
+            (Class(c1), Class(c2)) => c1.ptr_eq(c2),
+            (Instance(i1), Instance(i2)) => i1.ptr_eq(i2),
+            (List(l1), List(l2)) => Rc::ptr_eq(l1, l2),
+            (Map(m1), Map(m2)) => Rc::ptr_eq(m1, m2),
+            (Set(s1), Set(s2)) => Rc::ptr_eq(s1, s2),
+            (Boolean(b1), Boolean(b2)) => b1 == b2,
+            (Nil, Nil) => true,
+            (Number(n1), Number(n2)) => n1 == n2,
+            (Str(s1), Str(s2)) => s1 == s2,
+            (Undefined, Undefined) => true,
+            _ => false,
+        }
+    }
+
+    /// Returns the name of this value's type, e.g. "number" or "string".
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            LoxObject::Boolean(_) => "boolean",
+            LoxObject::Callable(_) => "function",
+            LoxObject::Class(_) => "class",
+            LoxObject::Instance(_) => "instance",
+            LoxObject::List(_) => "list",
+            LoxObject::Map(_) => "map",
+            LoxObject::Nil => "nil",
+            LoxObject::Number(_) => "number",
+            LoxObject::Set(_) => "set",
+            LoxObject::Str(_) => "string",
+            LoxObject::Undefined => "undefined",
+        }
+    }
+
+    // Strings nested in collections are quoted so "[1, 2]" and ["1, 2"] display differently.
+    pub(crate) fn to_element_string(&self) -> String {
+        self.element_string(&mut vec![])
+    }
+
+    fn element_string(&self, showing: &mut Vec<LoxObject>) -> String {
+        match self {
+            LoxObject::Str(s) => format!("\"{}\"", s),
+            LoxObject::List(_) | LoxObject::Map(_) => self.container_string(showing),
+            _ => self.to_string(),
+        }
+    }
+
+    // Formats a list or map. `showing` holds the containers currently being formatted,
+    // so that one containing itself is shown as "[...]" or "{...}" rather than
+    // recursing forever.
+    fn container_string(&self, showing: &mut Vec<LoxObject>) -> String {
+        if showing.iter().any(|v| v.is_identical(self)) {
+            return match self {
+                LoxObject::Map(_) => String::from("{...}"),
+                _ => String::from("[...]"),
+            };
+        }
+        showing.push(self.clone());
+        let result = match self {
+            LoxObject::List(l) => {
+                let elements: Vec<String> = l
+                    .borrow()
+                    .iter()
+                    .map(|e| e.element_string(showing))
+                    .collect();
+                format!("[{}]", elements.join(", "))
+            }
+            LoxObject::Map(m) => {
+                let m = m.borrow();
+                let mut keys: Vec<&String> = m.keys().collect();
+                keys.sort();
+                let entries: Vec<String> = keys
+                    .iter()
+                    .map(|k| format!("\"{}\": {}", k, m[*k].element_string(showing)))
+                    .collect();
+                format!("{{{}}}", entries.join(", "))
+            }
+            _ => self.to_string(),
+        };
+        showing.pop();
+        result
+    }
 }
 
 impl PartialEq<LoxObject> for LoxObject {
@@ -45,8 +144,11 @@ impl PartialEq<LoxObject> for LoxObject {
             (Callable(c1), Callable(c2)) => c1 == c2,
             (Class(c1), Class(c2)) => c1 == c2,
             (Instance(i1), Instance(i2)) => i1 == i2,
+            (List(l1), List(l2)) => *l1.borrow() == *l2.borrow(),
+            (Map(m1), Map(m2)) => *m1.borrow() == *m2.borrow(),
             (Nil, Nil) => true,
             (Number(n1), Number(n2)) => n1 == n2,
+            (Set(s1), Set(s2)) => *s1.borrow() == *s2.borrow(),
             (Str(s1), Str(s2)) => s1 == s2,
             (Undefined, Undefined) => true,
             _ => false,
@@ -56,6 +158,22 @@ impl PartialEq<LoxObject> for LoxObject {
 
 impl Eq for LoxObject {}
 
+/// Formats a number the way Lox displays it; infinities and NaN are spelled
+/// `Infinity`, `-Infinity` and `NaN` rather than Rust's `inf` and `-inf`.
+pub fn format_number(n: f64) -> String {
+    if n.is_nan() {
+        String::from("NaN")
+    } else if n.is_infinite() {
+        if n > 0.0 {
+            String::from("Infinity")
+        } else {
+            String::from("-Infinity")
+        }
+    } else {
+        n.to_string()
+    }
+}
+
 impl fmt::Display for LoxObject {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
@@ -69,10 +187,128 @@ impl fmt::Display for LoxObject {
             LoxObject::Callable(c) => write!(f, "{}", c.borrow()),
             LoxObject::Class(c) => write!(f, "{}", c),
             LoxObject::Instance(i) => write!(f, "{}", i),
+            LoxObject::List(_) | LoxObject::Map(_) => {
+                write!(f, "{}", self.container_string(&mut vec![]))
+            }
             LoxObject::Nil => write!(f, "nil"),
-            LoxObject::Number(n) => write!(f, "{}", n),
+            LoxObject::Number(n) => write!(f, "{}", format_number(*n)),
+            LoxObject::Set(s) => write!(f, "{}", s.borrow()),
             LoxObject::Str(s) => write!(f, "{}", s),
-            LoxObject::Undefined => write!(f, "<undefined>"),
+            LoxObject::Undefined => write!(f, "<uninitialized>"),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interpreter::Interpreter;
+    use crate::parser;
+    use crate::scanner;
+
+    fn display(expression: &str) -> String {
+        let mut scanner = scanner::Scanner::new(expression);
+        let tokens = scanner.scan_tokens();
+        let mut parser = parser::Parser::new(tokens);
+        let expr = parser.parse_expression().unwrap();
+        let mut interpreter = Interpreter::new();
+        interpreter.evaluate(&expr).unwrap().to_string()
+    }
+
+    #[test]
+    fn large_whole_numbers_display_without_wrapping() {
+        let inputs = vec![
+            ("3000000000", "3000000000"),
+            ("-3000000000", "-3000000000"),
+            ("1500000000 * 2", "3000000000"),
+            ("12", "12"),
+            ("1.5", "1.5"),
+        ];
+        for (expression, expected) in inputs {
+            assert_eq!(display(expression), expected);
+        }
+    }
+
+    #[test]
+    fn lists_and_maps_display_their_contents() {
+        let list = LoxObject::new_list(vec![
+            LoxObject::Number(1.0),
+            LoxObject::Str(String::from("two")),
+            LoxObject::Nil,
+        ]);
+        assert_eq!(list.to_string(), "[1, \"two\", nil]");
+
+        let mut entries = HashMap::new();
+        entries.insert(String::from("b"), LoxObject::Boolean(true));
+        entries.insert(String::from("a"), list);
+        assert_eq!(
+            LoxObject::new_map(entries).to_string(),
+            "{\"a\": [1, \"two\", nil], \"b\": true}"
+        );
+    }
+
+    // 1e200; the scanner doesn't support exponents
+    fn huge() -> String {
+        format!("1{}", "0".repeat(200))
+    }
+
+    #[test]
+    fn infinities_and_nan_display_consistently() {
+        let inputs = vec![
+            (format!("{0} * {0}", huge()), "Infinity"),
+            (format!("-{0} * {0}", huge()), "-Infinity"),
+            (format!("{0} * {0} - {0} * {0}", huge()), "NaN"),
+            (format!("\"x\" + {0} * {0}", huge()), "xInfinity"),
+            (String::from("-0 == 0"), "true"),
+        ];
+        for (expression, expected) in inputs {
+            assert_eq!(display(&expression), expected);
+        }
+        assert_eq!(LoxObject::Number(f64::INFINITY).to_string(), "Infinity");
+        assert_eq!(LoxObject::Number(f64::NAN).to_string(), "NaN");
+    }
+
+    #[test]
+    fn comparisons_follow_ieee_semantics() {
+        let nan = format!("({0} * {0} - {0} * {0})", huge());
+        let inputs = vec![
+            (format!("{0} == {0}", nan), "false"),
+            (format!("{0} != {0}", nan), "true"),
+            (format!("{0} < 1", nan), "false"),
+            (format!("{0} >= 1", nan), "false"),
+            (format!("{0} * {0} > {0}", huge()), "true"),
+            (String::from("-0 < 0"), "false"),
+            (String::from("-0 <= 0"), "true"),
+        ];
+        for (expression, expected) in inputs {
+            assert_eq!(display(&expression), expected, "{}", expression);
+        }
+        assert_ne!(LoxObject::Number(f64::NAN), LoxObject::Number(f64::NAN));
+        assert_eq!(LoxObject::Number(-0.0), LoxObject::Number(0.0));
+    }
+
+    #[test]
+    fn containers_holding_themselves_display_a_placeholder() {
+        let list = LoxObject::new_list(vec![LoxObject::Number(1.0)]);
+        if let LoxObject::List(elements) = &list {
+            elements.borrow_mut().push(list.clone());
+        }
+        assert_eq!(list.to_string(), "[1, [...]]");
+
+        let map = LoxObject::new_map(HashMap::new());
+        if let LoxObject::Map(entries) = &map {
+            entries
+                .borrow_mut()
+                .insert(String::from("self"), map.clone());
+            entries
+                .borrow_mut()
+                .insert(String::from("list"), list.clone());
+        }
+        assert_eq!(map.to_string(), "{\"list\": [1, [...]], \"self\": {...}}");
+
+        // a container appearing twice without a cycle is shown in full both times
+        let inner = LoxObject::new_list(vec![LoxObject::Number(2.0)]);
+        let outer = LoxObject::new_list(vec![inner.clone(), inner]);
+        assert_eq!(outer.to_string(), "[[2], [2]]");
+    }
+}
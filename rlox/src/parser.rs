@@ -4,9 +4,18 @@ use crate::scanner::*;
 
 pub type Result<T> = std::result::Result<T, error::ParseError>;
 
+/// The number of errors parse_all collects before giving up, by default.
+pub const DEFAULT_MAX_ERRORS: usize = 20;
+
+/// The most decimal places `print :n` accepts.
+pub const MAX_PRINT_PRECISION: usize = 100;
+
 pub struct Parser {
     tokens: Vec<Token>,
     current: usize,
+    allow_parenless_conditions: bool,
+    optional_semicolons: bool,
+    max_errors: usize,
 }
 
 impl Parser {
@@ -14,9 +23,33 @@ impl Parser {
         Self {
             tokens: tokens,
             current: 0,
+            allow_parenless_conditions: false,
+            optional_semicolons: false,
+            max_errors: DEFAULT_MAX_ERRORS,
         }
     }
 
+    /// Sets the number of errors parse_all collects before it stops parsing. Zero
+    /// means no limit.
+    pub fn set_max_errors(&mut self, max_errors: usize) {
+        self.max_errors = max_errors;
+    }
+
+    /// When set, `if`, `while` and `for` accept conditions without surrounding
+    /// parentheses, e.g. `if x > 1 { ... }`. Parenless forms require a block body.
+    pub fn set_allow_parenless_conditions(&mut self, allow: bool) {
+        self.allow_parenless_conditions = allow;
+    }
+
+    /// When set, the ";" ending a statement may be omitted if the statement is
+    /// followed by a line break, a closing "}" or the end of input, e.g. `print 1`.
+    /// A "(" starting a line then begins a new statement, rather than calling the
+    /// expression ending the line before.
+    pub fn set_optional_semicolons(&mut self, optional: bool) {
+        self.optional_semicolons = optional;
+    }
+
+    #[allow(dead_code)]
     pub fn parse(&mut self) -> Result<Vec<Box<Stmt>>> {
         let mut statements: Vec<Box<Stmt>> = vec![];
         while !self.is_at_end() {
@@ -25,11 +58,43 @@ impl Parser {
         Ok(statements)
     }
 
+    /// Parses the tokens as a program like parse(), but rather than stopping at the
+    /// first error, skips to the next statement and continues, collecting every error.
+    /// Once the maximum number of errors is reached parsing stops, and a final error
+    /// saying so is added.
+    pub fn parse_all(&mut self) -> (Vec<Box<Stmt>>, Vec<error::ParseError>) {
+        let mut statements: Vec<Box<Stmt>> = vec![];
+        let mut errors = vec![];
+        while !self.is_at_end() {
+            match self.declaration_stmt() {
+                Ok(statement) => statements.push(statement),
+                Err(e) => {
+                    errors.push(e);
+                    if errors.len() == self.max_errors && !self.is_at_end() {
+                        errors.push(error::ParseError::new(
+                            self.peek().clone(),
+                            &format!("Too many errors, stopped after {}.", errors.len()),
+                        ));
+                        break;
+                    }
+                }
+            }
+        }
+        (statements, errors)
+    }
+
     /// Parse the tokens as an expression, returning the computed expression tree.
-    /// This is for testing, not running whole programs.
-    #[allow(dead_code)]
+    /// This is for evaluating standalone expressions, not running whole programs, so
+    /// it's an error for anything to follow the expression.
     pub fn parse_expression(&mut self) -> Result<Box<Expr>> {
-        self.expression_expr()
+        let expr = self.expression_expr()?;
+        if !self.is_at_end() {
+            return Err(error::ParseError::new(
+                self.peek().clone(),
+                "Expect end of expression.",
+            ));
+        }
+        Ok(expr)
     }
 
     // Expressions
@@ -90,14 +155,51 @@ impl Parser {
             return Ok(Box::new(Expr::Grouping { expression: expr }));
         }
 
+        if self.match_token(TokenType::LeftBrace) {
+            return self.block_expr();
+        }
+
         Err(error::ParseError::new(
             self.peek().clone(),
             "Expect expression",
         ))
     }
 
+    // A "{" in expression position begins a block expression, which evaluates
+    // to the expression ending it: `{ var t = 2; t * 2 }`. A "{" beginning a
+    // statement remains a block statement.
+    fn block_expr(&mut self) -> Result<Box<Expr>> {
+        let mut statements = vec![];
+        loop {
+            if self.check(TokenType::RightBrace) || self.is_at_end() {
+                return Err(error::ParseError::new(
+                    self.peek().clone(),
+                    "Expect expression at end of block expression.",
+                ));
+            }
+            if self.check_statement_start() {
+                statements.push(self.declaration_stmt()?);
+                continue;
+            }
+
+            let expression = self.expression_expr()?;
+            if self.match_token(TokenType::Semicolon) {
+                statements.push(Box::new(Stmt::Expression { expression }));
+            } else {
+                self.consume(
+                    TokenType::RightBrace,
+                    "Expect \"}\" after final expression of block expression.",
+                )?;
+                return Ok(Box::new(Expr::Block {
+                    statements,
+                    final_expr: expression,
+                }));
+            }
+        }
+    }
+
     fn unary_expr(&mut self) -> Result<Box<Expr>> {
-        if self.match_tokens(&vec![TokenType::Bang, TokenType::Minus]) {
+        if self.match_tokens(&vec![TokenType::Bang, TokenType::Minus, TokenType::Plus]) {
             let op = self.previous().clone();
             let right = self.unary_expr()?;
             return Ok(Box::new(Expr::Unary {
@@ -111,8 +213,9 @@ impl Parser {
     fn call_expr(&mut self) -> Result<Box<Expr>> {
         let mut expr = self.primary_expr()?;
         loop {
-            if self.match_token(TokenType::LeftParen) {
+            if self.check(TokenType::LeftParen) && !self.starts_new_line() {
                 // parse argument list
+                self.advance();
                 expr = self.finish_call_expr(expr)?;
             } else if self.match_token(TokenType::Dot) {
                 let name =
@@ -121,6 +224,13 @@ impl Parser {
                     object: expr,
                     name: name.clone(),
                 });
+            } else if self.match_token(TokenType::QuestionDot) {
+                let name =
+                    self.consume(TokenType::Identifier, "Expect property name after \"?.\"")?;
+                expr = Box::new(Expr::OptionalGet {
+                    object: expr,
+                    name: name.clone(),
+                });
             } else {
                 break;
             }
@@ -268,6 +378,8 @@ impl Parser {
             if self.check(TokenType::Class) {
                 self.advance();
                 class_methods.push(self.function_stmt(CallableType::ClassMethod)?);
+            } else if self.match_token(TokenType::Abstract) {
+                methods.push(self.abstract_method_stmt()?);
             } else {
                 methods.push(self.function_stmt(CallableType::Method)?);
             }
@@ -284,12 +396,16 @@ impl Parser {
     }
 
     fn statement_stmt(&mut self) -> Result<Box<Stmt>> {
-        if self.match_token(TokenType::For) {
+        if self.match_token(TokenType::Assert) {
+            self.assert_stmt()
+        } else if self.match_token(TokenType::For) {
             self.for_stmt()
         } else if self.match_token(TokenType::If) {
             self.if_stmt()
         } else if self.match_token(TokenType::Print) {
-            self.print_stmt()
+            self.print_stmt(true)
+        } else if self.match_token(TokenType::Write) {
+            self.print_stmt(false)
         } else if self.match_token(TokenType::Return) {
             self.return_stmt()
         } else if self.match_token(TokenType::While) {
@@ -305,19 +421,31 @@ impl Parser {
         }
     }
 
+    fn assert_stmt(&mut self) -> Result<Box<Stmt>> {
+        let keyword = self.previous().clone();
+        let condition = self.expression_expr()?;
+        let mut message = None;
+        if self.match_token(TokenType::Colon) {
+            message = Some(self.expression_expr()?);
+        }
+        self.consume_semicolon("Expect \";\" after assertion.")?;
+        Ok(Box::new(Stmt::Assert {
+            condition,
+            keyword,
+            message,
+        }))
+    }
+
     fn break_stmt(&mut self) -> Result<Box<Stmt>> {
         let break_token = self.peek().clone();
-        self.consume(
-            TokenType::Semicolon,
-            "Expect \";\" after \"break\" statement.",
-        )?;
+        self.consume_semicolon("Expect \";\" after \"break\" statement.")?;
         Ok(Box::new(Stmt::Break {
             keyword: break_token,
         }))
     }
 
     fn for_stmt(&mut self) -> Result<Box<Stmt>> {
-        self.consume(TokenType::LeftParen, "Expect \"(\" after \"for\".")?;
+        let parenthesized = self.open_condition("for")?;
 
         let mut initializer = None;
         if self.match_token(TokenType::Semicolon) {
@@ -338,58 +466,31 @@ impl Parser {
         )?;
 
         let mut increment = None;
-        if !self.check(TokenType::RightParen) {
+        let increment_end = if parenthesized {
+            TokenType::RightParen
+        } else {
+            TokenType::LeftBrace
+        };
+        if !self.check(increment_end) {
             increment = Some(self.expression_expr()?);
         }
 
-        self.consume(TokenType::RightParen, "Expect \")\" after for clauses.")?;
-
-        let mut body = self.statement_stmt()?;
-
-        //
-        //   we're taking a for loop and desugaring it to a while loop
-        //   for (var i = 0; i < 10; i = i + 1) { print i; }
-        //
-        //   becomes:
-        //
-        //   { var i = 0; while(i < 10) { { print i; } i = i + 1; } }
-        //
-
-        if let Some(increment) = increment {
-            body = Box::new(Stmt::Block {
-                statements: vec![
-                    body,
-                    Box::new(Stmt::Expression {
-                        expression: increment,
-                    }),
-                ],
-            });
-        }
+        self.close_condition(parenthesized, "Expect \")\" after for clauses.")?;
 
-        if let Some(condition) = condition {
-            body = Box::new(Stmt::While { condition, body });
-        } else {
-            body = Box::new(Stmt::While {
-                condition: Box::new(Expr::Literal {
-                    value: crate::scanner::Literal::True,
-                }),
-                body: body,
-            });
-        }
-
-        if let Some(initializer) = initializer {
-            body = Box::new(Stmt::Block {
-                statements: vec![initializer, body],
-            });
-        }
+        let body = self.statement_stmt()?;
 
-        Ok(body)
+        Ok(Box::new(Stmt::For {
+            initializer,
+            condition,
+            increment,
+            body,
+        }))
     }
 
     fn if_stmt(&mut self) -> Result<Box<Stmt>> {
-        self.consume(TokenType::LeftParen, "Expect \"(\" after \"if\".")?;
+        let parenthesized = self.open_condition("if")?;
         let condition = self.expression_expr()?;
-        self.consume(TokenType::RightParen, "Expect \")\" after if condition.")?;
+        self.close_condition(parenthesized, "Expect \")\" after if condition.")?;
 
         let then_branch = self.statement_stmt()?;
         let mut else_branch = Option::None;
@@ -403,58 +504,150 @@ impl Parser {
         }))
     }
 
-    fn print_stmt(&mut self) -> Result<Box<Stmt>> {
+    fn print_stmt(&mut self, newline: bool) -> Result<Box<Stmt>> {
+        // `print :2 x;` prints numbers with 2 decimal places
+        let mut precision = None;
+        if self.match_token(TokenType::Colon) {
+            let token = self.consume(TokenType::Number, "Expect precision after \":\".")?;
+            match token.literal {
+                Some(Literal::Number(n)) if n.fract() == 0.0 => {
+                    if n > MAX_PRINT_PRECISION as f64 {
+                        return Err(error::ParseError::new(
+                            token.clone(),
+                            &format!("Precision must be at most {}.", MAX_PRINT_PRECISION),
+                        ));
+                    }
+                    precision = Some(n as usize)
+                }
+                _ => {
+                    return Err(error::ParseError::new(
+                        token.clone(),
+                        "Precision must be a non-negative integer.",
+                    ))
+                }
+            }
+        }
         let value = self.expression_expr()?;
-        self.consume(TokenType::Semicolon, "Expect \";\" after value.")?;
-        Ok(Box::new(Stmt::Print { expression: value }))
+        self.consume_semicolon("Expect \";\" after value.")?;
+        Ok(Box::new(Stmt::Print {
+            expression: value,
+            newline,
+            precision,
+        }))
     }
 
     fn return_stmt(&mut self) -> Result<Box<Stmt>> {
         let keyword = self.previous().clone();
         let mut value = None;
-        if !self.check(TokenType::Semicolon) {
-            value = Some(self.expression_expr()?);
+        if !self.is_at_statement_end() {
+            // `return a, b;` returns the list [a, b]
+            let mut values = vec![self.expression_expr()?];
+            while self.match_token(TokenType::Comma) {
+                values.push(self.expression_expr()?);
+            }
+            value = Some(if values.len() == 1 {
+                values.remove(0)
+            } else {
+                Box::new(Expr::List { elements: values })
+            });
         }
-        self.consume(TokenType::Semicolon, "Expect \";\" after return value.")?;
+        self.consume_semicolon("Expect \";\" after return value.")?;
         Ok(Box::new(Stmt::Return { keyword, value }))
     }
 
+    // Parses `var a = 1, b;`, producing a Stmt::Var, or a Stmt::VarList when
+    // more than one variable is declared.
     fn var_declaration_stmt(&mut self) -> Result<Box<Stmt>> {
-        let name = self
-            .consume(TokenType::Identifier, "Expect variable name.")?
-            .clone();
+        if self.match_token(TokenType::LeftParen) {
+            return self.var_destructure_stmt();
+        }
 
-        let mut initializer: Option<Box<Expr>> = None;
-        if self.match_token(TokenType::Equal) {
-            initializer = Some(self.expression_expr()?);
+        let mut declarations = vec![];
+        loop {
+            let name = self
+                .consume(TokenType::Identifier, "Expect variable name.")?
+                .clone();
+
+            let mut initializer: Option<Box<Expr>> = None;
+            if self.match_token(TokenType::Equal) {
+                initializer = Some(self.expression_expr()?);
+            }
+            declarations.push(Box::new(Stmt::Var { name, initializer }));
+
+            if !self.match_token(TokenType::Comma) {
+                break;
+            }
+        }
+        self.consume_semicolon("Expect \";\" after variable declaration.")?;
+
+        if declarations.len() == 1 {
+            Ok(declarations.remove(0))
+        } else {
+            Ok(Box::new(Stmt::VarList { declarations }))
         }
-        self.consume(
-            TokenType::Semicolon,
-            "Expect \";\" after variable declaration.",
-        )?;
-        Ok(Box::new(Stmt::Var {
-            name: name,
-            initializer: initializer,
-        }))
+    }
+
+    // Parses `var (a, b) = pair;`, which declares a variable for each element of
+    // a list, in order.
+    fn var_destructure_stmt(&mut self) -> Result<Box<Stmt>> {
+        let mut names = vec![];
+        loop {
+            names.push(
+                self.consume(TokenType::Identifier, "Expect variable name.")?
+                    .clone(),
+            );
+            if !self.match_token(TokenType::Comma) {
+                break;
+            }
+        }
+        self.consume(TokenType::RightParen, "Expect \")\" after variable names.")?;
+        self.consume(TokenType::Equal, "Expect \"=\" after variable names.")?;
+        let initializer = self.expression_expr()?;
+        self.consume_semicolon("Expect \";\" after variable declaration.")?;
+        Ok(Box::new(Stmt::Destructure { names, initializer }))
     }
 
     fn while_stmt(&mut self) -> Result<Box<Stmt>> {
-        self.consume(
-            TokenType::LeftParen,
-            "Expect \"(\" after variable \"while\".",
-        )?;
+        let parenthesized = self.open_condition("while")?;
         let condition = self.expression_expr()?;
-        self.consume(
-            TokenType::RightParen,
+        self.close_condition(
+            parenthesized,
             "Expect \")\" after variable \"while\" condition.",
         )?;
         let body = self.statement_stmt()?;
         Ok(Box::new(Stmt::While { condition, body }))
     }
 
+    // Consumes the "(" opening a control flow condition, returning false if it was
+    // omitted and parenless conditions are allowed.
+    fn open_condition(&mut self, keyword: &str) -> Result<bool> {
+        if self.allow_parenless_conditions && !self.check(TokenType::LeftParen) {
+            return Ok(false);
+        }
+        self.consume(
+            TokenType::LeftParen,
+            &format!("Expect \"(\" after \"{}\".", keyword),
+        )?;
+        Ok(true)
+    }
+
+    // Consumes the ")" closing a parenthesized condition; parenless conditions
+    // must instead be followed by a block.
+    fn close_condition(&mut self, parenthesized: bool, on_error_message: &str) -> Result<()> {
+        if parenthesized {
+            self.consume(TokenType::RightParen, on_error_message)?;
+        } else if !self.check(TokenType::LeftBrace) {
+            return Err(error::ParseError::new(
+                self.peek().clone(),
+                "Expect \"{\" after condition without parentheses.",
+            ));
+        }
+        Ok(())
+    }
+
     fn expression_stmt(&mut self) -> Result<Box<Stmt>> {
         let expr = self.expression_expr()?;
-        self.consume(TokenType::Semicolon, "Expect \";\" after expression.")?;
+        self.consume_semicolon("Expect \";\" after expression.")?;
         Ok(Box::new(Stmt::Expression { expression: expr }))
     }
 
@@ -495,25 +688,7 @@ impl Parser {
 
         let mut parameters = vec![];
         if has_arguments {
-            if !self.check(TokenType::RightParen) {
-                loop {
-                    if parameters.len() >= 255 {
-                        error::report::parse_error_at_token(
-                            self.peek(),
-                            "Cannot have more than 255 parameters in function declaration.",
-                        );
-                    }
-
-                    parameters.push(
-                        self.consume(TokenType::Identifier, "Expect parameter name.")?
-                            .clone(),
-                    );
-                    if !self.match_token(TokenType::Comma) {
-                        break;
-                    }
-                }
-            }
-            self.consume(TokenType::RightParen, "Expect \")\" after parameter list.")?;
+            parameters = self.parameter_list()?;
         }
 
         self.consume(
@@ -539,6 +714,51 @@ impl Parser {
         }))
     }
 
+    // Abstract methods have a parameter list but no body; subclasses must override them.
+    fn abstract_method_stmt(&mut self) -> Result<Box<Stmt>> {
+        let name = self
+            .consume(TokenType::Identifier, "Expect abstract method name.")?
+            .clone();
+        self.consume(
+            TokenType::LeftParen,
+            "Expect \"(\" after abstract method name.",
+        )?;
+        let parameters = self.parameter_list()?;
+        self.consume_semicolon("Expect \";\" after abstract method declaration.")?;
+
+        Ok(Box::new(Stmt::Function {
+            name,
+            parameters,
+            body: vec![],
+            fn_type: CallableType::Abstract,
+        }))
+    }
+
+    // Parses comma separated parameter names up to and including the closing paren.
+    fn parameter_list(&mut self) -> Result<Vec<Token>> {
+        let mut parameters = vec![];
+        if !self.check(TokenType::RightParen) {
+            loop {
+                if parameters.len() >= 255 {
+                    error::report::parse_error_at_token(
+                        self.peek(),
+                        "Cannot have more than 255 parameters in function declaration.",
+                    );
+                }
+
+                parameters.push(
+                    self.consume(TokenType::Identifier, "Expect parameter name.")?
+                        .clone(),
+                );
+                if !self.match_token(TokenType::Comma) {
+                    break;
+                }
+            }
+        }
+        self.consume(TokenType::RightParen, "Expect \")\" after parameter list.")?;
+        Ok(parameters)
+    }
+
     fn block_stmt(&mut self) -> Result<Vec<Box<Stmt>>> {
         let mut statements = vec![];
         while !self.check(TokenType::RightBrace) && !self.is_at_end() {
@@ -549,10 +769,7 @@ impl Parser {
     }
 
     fn assignment_expr(&mut self) -> Result<Box<Expr>> {
-        let mut expr = self.ternary_expr()?;
-        if self.match_token(TokenType::Fun) {
-            expr = self.lambda_expr()?;
-        }
+        let expr = self.ternary_expr()?;
         if self.match_token(TokenType::Equal) {
             let equals = self.previous().clone();
             let value = self.assignment_expr()?;
@@ -576,6 +793,65 @@ impl Parser {
                     return Err(error::ParseError::new(equals, "Invalid assignment target."));
                 }
             }
+        } else if self.match_token(TokenType::QuestionQuestionEqual) {
+            // desugar `target ??= value` into `target = target ?? value`
+            let operator = self.previous().clone();
+            let value = self.assignment_expr()?;
+            let coalesce = |left: Box<Expr>| {
+                Box::new(Expr::Logical {
+                    left,
+                    operator: Token::new(
+                        TokenType::QuestionQuestion,
+                        String::from("??"),
+                        None,
+                        operator.line,
+                        operator.id,
+                    ),
+                    right: value,
+                })
+            };
+
+            match *expr {
+                Expr::Variable { name } => {
+                    let value = coalesce(Box::new(Expr::Variable { name: name.clone() }));
+                    return Ok(Box::new(Expr::Assign { name, value }));
+                }
+                Expr::Get { object, name } => {
+                    // `object` is evaluated once, into a variable no identifier can name,
+                    // giving `{ var o = object; o.name = o.name ?? value }`
+                    let target = Token::new(
+                        TokenType::Identifier,
+                        format!("<??= {}>", operator.id),
+                        None,
+                        operator.line,
+                        operator.id,
+                    );
+                    let target_expr = Box::new(Expr::Variable {
+                        name: target.clone(),
+                    });
+                    let current = Box::new(Expr::Get {
+                        object: target_expr.clone(),
+                        name: name.clone(),
+                    });
+                    return Ok(Box::new(Expr::Block {
+                        statements: vec![Box::new(Stmt::Var {
+                            name: target,
+                            initializer: Some(object),
+                        })],
+                        final_expr: Box::new(Expr::Set {
+                            object: target_expr,
+                            name,
+                            value: coalesce(current),
+                        }),
+                    }));
+                }
+                _ => {
+                    return Err(error::ParseError::new(
+                        operator,
+                        "Invalid assignment target.",
+                    ));
+                }
+            }
         }
         Ok(expr)
     }
@@ -617,7 +893,7 @@ impl Parser {
     }
 
     fn ternary_expr(&mut self) -> Result<Box<Expr>> {
-        let expr = self.or_expr()?;
+        let expr = self.coalesce_expr()?;
         if self.match_token(TokenType::QuestionMark) {
             let then_value = self.expression_expr()?;
             self.consume(
@@ -634,6 +910,20 @@ impl Parser {
         Ok(expr)
     }
 
+    fn coalesce_expr(&mut self) -> Result<Box<Expr>> {
+        let mut expr = self.or_expr()?;
+        while self.match_token(TokenType::QuestionQuestion) {
+            let op = self.previous().clone();
+            let right = self.or_expr()?;
+            expr = Box::new(Expr::Logical {
+                left: expr,
+                operator: op,
+                right,
+            });
+        }
+        Ok(expr)
+    }
+
     fn or_expr(&mut self) -> Result<Box<Expr>> {
         let mut expr = self.and_expr()?;
         while self.match_token(TokenType::Or) {
@@ -671,6 +961,25 @@ impl Parser {
         self.previous()
     }
 
+    // Returns true if the next token begins a declaration or a non-expression statement.
+    fn check_statement_start(&self) -> bool {
+        matches!(
+            self.peek().token_type,
+            TokenType::Assert
+                | TokenType::Break
+                | TokenType::Class
+                | TokenType::For
+                | TokenType::Fun
+                | TokenType::If
+                | TokenType::LeftBrace
+                | TokenType::Print
+                | TokenType::Return
+                | TokenType::Var
+                | TokenType::While
+                | TokenType::Write
+        )
+    }
+
     fn check(&self, t: TokenType) -> bool {
         if self.is_at_end() {
             false
@@ -690,6 +999,35 @@ impl Parser {
         ))
     }
 
+    // Returns true if the current token ends a statement: a ";", or when semicolons
+    // are optional, a token on a later line, a closing "}" or the end of input.
+    fn is_at_statement_end(&self) -> bool {
+        self.check(TokenType::Semicolon)
+            || (self.optional_semicolons
+                && (self.check(TokenType::RightBrace)
+                    || self.is_at_end()
+                    || self.starts_new_line()))
+    }
+
+    // Returns true if semicolons are optional and the next token is on a later line
+    // than the previous one, so it may begin a new statement.
+    fn starts_new_line(&self) -> bool {
+        self.optional_semicolons && self.peek().line > self.previous().line
+    }
+
+    // Consumes the ";" ending a statement, which may be implied when semicolons
+    // are optional.
+    fn consume_semicolon(&mut self, on_error_message: &str) -> Result<()> {
+        if !self.is_at_statement_end() {
+            return Err(error::ParseError::new(
+                self.peek().clone(),
+                on_error_message,
+            ));
+        }
+        self.match_token(TokenType::Semicolon);
+        Ok(())
+    }
+
     fn is_at_end(&self) -> bool {
         self.peek().token_type == TokenType::Eof
     }
@@ -729,13 +1067,16 @@ impl Parser {
                 return;
             }
             match self.peek().token_type {
-                TokenType::Class
+                TokenType::Abstract
+                | TokenType::Assert
+                | TokenType::Class
                 | TokenType::Fun
                 | TokenType::Var
                 | TokenType::For
                 | TokenType::If
                 | TokenType::While
                 | TokenType::Print
+                | TokenType::Write
                 | TokenType::Return => {
                     return;
                 }
@@ -763,12 +1104,25 @@ mod tests {
 
     fn zero_stmt_line_and_id(stmt: &mut Stmt) {
         match stmt {
+            Stmt::Assert {
+                condition,
+                keyword,
+                message,
+            } => {
+                zero_expr_line_and_id(condition);
+                zero_token_line_and_id(keyword);
+                if let Some(message) = message {
+                    zero_expr_line_and_id(message);
+                }
+            }
             Stmt::Block { statements } => {
                 for stmt in statements {
                     zero_stmt_line_and_id(stmt);
                 }
             }
-            Stmt::Break { keyword: _ } => {}
+            Stmt::Break { keyword } => {
+                zero_token_line_and_id(keyword);
+            }
             Stmt::Class {
                 name,
                 super_class,
@@ -785,6 +1139,23 @@ mod tests {
             Stmt::Expression { expression } => {
                 zero_expr_line_and_id(expression);
             }
+            Stmt::For {
+                initializer,
+                condition,
+                increment,
+                body,
+            } => {
+                if let Some(initializer) = initializer {
+                    zero_stmt_line_and_id(initializer);
+                }
+                if let Some(condition) = condition {
+                    zero_expr_line_and_id(condition);
+                }
+                if let Some(increment) = increment {
+                    zero_expr_line_and_id(increment);
+                }
+                zero_stmt_line_and_id(body);
+            }
             Stmt::Function {
                 name,
                 parameters,
@@ -810,7 +1181,7 @@ mod tests {
                     zero_stmt_line_and_id(else_branch);
                 }
             }
-            Stmt::Print { expression } => {
+            Stmt::Print { expression, .. } => {
                 zero_expr_line_and_id(expression);
             }
             Stmt::Return { keyword, value } => {
@@ -825,6 +1196,15 @@ mod tests {
                     zero_expr_line_and_id(initializer);
                 }
             }
+            Stmt::VarList { declarations } => {
+                zero_stmts_line_and_id(declarations);
+            }
+            Stmt::Destructure { names, initializer } => {
+                for name in names {
+                    zero_token_line_and_id(name);
+                }
+                zero_expr_line_and_id(initializer);
+            }
             Stmt::While { condition, body } => {
                 zero_expr_line_and_id(condition);
                 zero_stmt_line_and_id(body);
@@ -834,6 +1214,13 @@ mod tests {
 
     fn zero_expr_line_and_id(expr: &mut Expr) {
         match expr {
+            Expr::Block {
+                statements,
+                final_expr,
+            } => {
+                zero_stmts_line_and_id(statements);
+                zero_expr_line_and_id(final_expr);
+            }
             Expr::Assign { name, value } => {
                 zero_token_line_and_id(name);
                 zero_expr_line_and_id(value);
@@ -866,6 +1253,10 @@ mod tests {
             Expr::Grouping { expression } => {
                 zero_expr_line_and_id(expression);
             }
+            Expr::OptionalGet { object, name } => {
+                zero_expr_line_and_id(object);
+                zero_token_line_and_id(name);
+            }
             Expr::Lambda { parameters, body } => {
                 for arg in parameters {
                     zero_token_line_and_id(arg);
@@ -874,6 +1265,11 @@ mod tests {
                     zero_stmt_line_and_id(stmt);
                 }
             }
+            Expr::List { elements } => {
+                for element in elements {
+                    zero_expr_line_and_id(element);
+                }
+            }
             Expr::Literal { value: _ } => {
                 // nothing to do
             }
@@ -995,7 +1391,7 @@ mod tests {
 
     #[test]
     fn fails_to_parse_bad_expressions() {
-        let expressions = vec!["1 + (5/2"];
+        let expressions = vec!["1 + (5/2", "1 2", "1;"];
 
         for expression in expressions {
             let mut scanner = Scanner::new(expression);
@@ -1016,32 +1412,89 @@ mod tests {
     }
 
     #[test]
-    fn desugars_for_loop_as_expected() {
-        let baseline = r#"
-{
-    var i = 0;
-    while (i < 3) {
-        {
-            print i;
-        }
-        i = i + 1;
-    }
-}
-        "#;
-
+    fn preserves_for_loop_structure() {
         let for_loop = r#"
 for (var i = 0; i < 3; i = i + 1) {
     print i;
 }
         "#;
 
-        let mut baseline_stmts = parse(baseline).expect("Baseline code should parse");
-        zero_stmts_line_and_id(&mut baseline_stmts);
+        let for_loop_stmts = parse(for_loop).expect("For-loop code should parse");
+        assert_eq!(for_loop_stmts.len(), 1);
+        match &*for_loop_stmts[0] {
+            Stmt::For {
+                initializer,
+                condition,
+                increment,
+                body,
+            } => {
+                assert!(matches!(
+                    initializer.as_deref(),
+                    Some(Stmt::Var { name, .. }) if name.lexeme == "i"
+                ));
+                assert!(matches!(condition.as_deref(), Some(Expr::Binary { .. })));
+                assert!(matches!(increment.as_deref(), Some(Expr::Assign { .. })));
+                assert!(matches!(&**body, Stmt::Block { .. }));
+            }
+            _ => panic!("Expected a Stmt::For, received {:?}", for_loop_stmts[0]),
+        }
 
-        let mut for_loop_stmts = parse(for_loop).expect("For-loop code should parse");
-        zero_stmts_line_and_id(&mut for_loop_stmts);
+        let empty_for_loop_stmts =
+            parse("for (;;) { break; }").expect("Empty for-loop code should parse");
+        match &*empty_for_loop_stmts[0] {
+            Stmt::For {
+                initializer,
+                condition,
+                increment,
+                body: _,
+            } => {
+                assert!(initializer.is_none());
+                assert!(condition.is_none());
+                assert!(increment.is_none());
+            }
+            _ => panic!("Expected a Stmt::For"),
+        }
+    }
 
-        assert_eq!(baseline_stmts, for_loop_stmts);
+    #[test]
+    fn parenless_conditions_match_parenthesized_conditions() {
+        let inputs = vec![
+            (
+                "if (x > 1) { print x; } else print 0;",
+                "if x > 1 { print x; } else print 0;",
+            ),
+            ("while (x < 3) { x = x + 1; }", "while x < 3 { x = x + 1; }"),
+            (
+                "for (var i = 0; i < 3; i = i + 1) { print i; }",
+                "for var i = 0; i < 3; i = i + 1 { print i; }",
+            ),
+            ("for (;;) { break; }", "for ;; { break; }"),
+        ];
+
+        for (parenthesized, parenless) in inputs {
+            let mut scanner = Scanner::new(parenless);
+            let mut parser = Parser::new(scanner.scan_tokens());
+            parser.set_allow_parenless_conditions(true);
+            let mut parenless_stmts = parser.parse().expect("Parenless form should parse");
+
+            let mut scanner = Scanner::new(parenthesized);
+            let mut parser = Parser::new(scanner.scan_tokens());
+            parser.set_allow_parenless_conditions(true);
+            let mut parenthesized_stmts = parser.parse().expect("Parenthesized form should parse");
+
+            zero_stmts_line_and_id(&mut parenless_stmts);
+            zero_stmts_line_and_id(&mut parenthesized_stmts);
+            assert_eq!(parenless_stmts, parenthesized_stmts);
+
+            // parenless conditions are rejected by default
+            assert!(parse(parenless).is_err());
+        }
+
+        // parenless conditions require a block body
+        let mut scanner = Scanner::new("if x > 1 print x;");
+        let mut parser = Parser::new(scanner.scan_tokens());
+        parser.set_allow_parenless_conditions(true);
+        assert!(parser.parse().is_err());
     }
 
     #[test]
@@ -1074,6 +1527,12 @@ for (var i = 0; i < 3; i = i + 1) {
                 }
             }
             "#,
+            r#"
+            var a = { var b = 1; }; // block expression needs a final expression
+            "#,
+            r#"
+            var a = { 1 2 }; // final expression must end the block expression
+            "#,
         ];
 
         for program in programs {
@@ -1087,4 +1546,269 @@ for (var i = 0; i < 3; i = i + 1) {
             assert!(result.is_err(), "Program should not have parsed.");
         }
     }
+
+    #[test]
+    fn braces_in_expression_position_are_block_expressions() {
+        let statements = parse("{ 1; }").unwrap();
+        assert!(matches!(&*statements[0], Stmt::Block { .. }));
+
+        let statements = parse("var a = { var b = 1; b + 1 };").unwrap();
+        match &*statements[0] {
+            Stmt::Var {
+                initializer: Some(initializer),
+                ..
+            } => match &**initializer {
+                Expr::Block {
+                    statements,
+                    final_expr,
+                } => {
+                    assert_eq!(statements.len(), 1);
+                    assert!(matches!(&**final_expr, Expr::Binary { .. }));
+                }
+                _ => panic!("Expected a block expression"),
+            },
+            _ => panic!("Expected a var statement"),
+        }
+    }
+
+    #[test]
+    fn var_declares_comma_separated_variables() {
+        let statements = parse("var a = 1, b, c = a;").unwrap();
+        assert_eq!(statements.len(), 1);
+        match &*statements[0] {
+            Stmt::VarList { declarations } => {
+                let declared: Vec<(&str, bool)> = declarations
+                    .iter()
+                    .map(|declaration| match &**declaration {
+                        Stmt::Var { name, initializer } => {
+                            (name.lexeme.as_str(), initializer.is_some())
+                        }
+                        _ => panic!("Expected a var statement"),
+                    })
+                    .collect();
+                assert_eq!(declared, vec![("a", true), ("b", false), ("c", true)]);
+            }
+            _ => panic!("Expected a var list statement"),
+        }
+
+        assert!(matches!(
+            &*parse("var a = 1;").unwrap()[0],
+            Stmt::Var { .. }
+        ));
+        assert!(parse("var a = 1,;").is_err());
+    }
+
+    #[test]
+    fn var_destructures_a_list_into_named_variables() {
+        match &*parse("var (x, y) = f();").unwrap()[0] {
+            Stmt::Destructure { names, initializer } => {
+                let names: Vec<&str> = names.iter().map(|name| name.lexeme.as_str()).collect();
+                assert_eq!(names, vec!["x", "y"]);
+                assert!(matches!(**initializer, Expr::Call { .. }));
+            }
+            _ => panic!("Expected a destructure statement"),
+        }
+
+        for program in [
+            "var () = f();",
+            "var (x, y);",
+            "var (x, y = f();",
+            "var (x,) = f();",
+        ] {
+            assert!(parse(program).is_err(), "{}", program);
+        }
+    }
+
+    #[test]
+    fn nested_ternaries_are_right_associative() {
+        let mut scanner = Scanner::new("a ? b : c ? d : e");
+        let mut parser = Parser::new(scanner.scan_tokens());
+        match *parser.parse_expression().unwrap() {
+            Expr::Ternary {
+                then_value,
+                else_value,
+                ..
+            } => {
+                assert!(matches!(*then_value, Expr::Variable { .. }));
+                assert!(matches!(*else_value, Expr::Ternary { .. }));
+            }
+            _ => panic!("Expected a ternary expression"),
+        }
+    }
+
+    #[test]
+    fn control_flow_bodies_may_be_single_statements() {
+        let programs = vec![
+            "if (a) print 1;",
+            "if (a) print 1; else print 2;",
+            "while (a) a = a - 1;",
+            "for (var i = 0; i < 3; i = i + 1) print i;",
+        ];
+        for program in programs {
+            let statements = parse(program).expect(program);
+            let body = match &*statements[0] {
+                Stmt::If { then_branch, .. } => then_branch,
+                Stmt::While { body, .. } => body,
+                Stmt::For { body, .. } => body,
+                _ => panic!("Expected a control flow statement for \"{}\"", program),
+            };
+            assert!(!matches!(**body, Stmt::Block { .. }), "{}", program);
+        }
+
+        match &*parse("if (a) print 1; else print 2;").unwrap()[0] {
+            Stmt::If {
+                then_branch,
+                else_branch: Some(else_branch),
+                ..
+            } => {
+                assert!(matches!(**then_branch, Stmt::Print { .. }));
+                assert!(matches!(**else_branch, Stmt::Print { .. }));
+            }
+            _ => panic!("Expected an if statement with an else branch"),
+        }
+
+        // the dangling else binds to the nearest if
+        match &*parse("if (a) if (b) print 1; else print 2;").unwrap()[0] {
+            Stmt::If {
+                then_branch,
+                else_branch: None,
+                ..
+            } => assert!(matches!(
+                **then_branch,
+                Stmt::If {
+                    else_branch: Some(_),
+                    ..
+                }
+            )),
+            _ => panic!("Expected an outer if statement without an else branch"),
+        }
+    }
+
+    #[test]
+    fn parse_all_stops_after_max_errors() {
+        let program = "var = 1;\n".repeat(50);
+        let mut scanner = Scanner::new(&program);
+        let mut parser = Parser::new(scanner.scan_tokens());
+        let (_, errors) = parser.parse_all();
+        assert_eq!(errors.len(), DEFAULT_MAX_ERRORS + 1);
+
+        let mut scanner = Scanner::new(&program);
+        let mut parser = Parser::new(scanner.scan_tokens());
+        parser.set_max_errors(10);
+        let (_, errors) = parser.parse_all();
+        assert_eq!(errors.len(), 11);
+        assert_eq!(errors[9].token.line, 10);
+        assert_eq!(errors[10].message, "Too many errors, stopped after 10.");
+
+        // reaching the limit at the end of the program isn't reported
+        let program = "var = 1;\n".repeat(10);
+        let mut scanner = Scanner::new(&program);
+        let mut parser = Parser::new(scanner.scan_tokens());
+        parser.set_max_errors(10);
+        let (_, errors) = parser.parse_all();
+        assert_eq!(errors.len(), 10);
+
+        // zero means no limit, and a valid program has no errors to count
+        let program = "var = 1;\n".repeat(50);
+        let mut scanner = Scanner::new(&program);
+        let mut parser = Parser::new(scanner.scan_tokens());
+        parser.set_max_errors(0);
+        let (_, errors) = parser.parse_all();
+        assert_eq!(errors.len(), 50);
+
+        let mut scanner = Scanner::new("var a = 1;");
+        let mut parser = Parser::new(scanner.scan_tokens());
+        parser.set_max_errors(0);
+        let (statements, errors) = parser.parse_all();
+        assert_eq!((statements.len(), errors.len()), (1, 0));
+    }
+
+    #[test]
+    fn print_precision_is_captured() {
+        let inputs = vec![
+            ("print 1;", true, None),
+            ("print :2 3.14159;", true, Some(2)),
+            ("write :0 x;", false, Some(0)),
+        ];
+        for (program, expected_newline, expected_precision) in inputs {
+            match &*parse(program).expect(program)[0] {
+                Stmt::Print {
+                    newline, precision, ..
+                } => {
+                    assert_eq!(*newline, expected_newline, "{}", program);
+                    assert_eq!(*precision, expected_precision, "{}", program);
+                }
+                _ => panic!("Expected a print statement for \"{}\"", program),
+            }
+        }
+
+        assert!(parse("print :1.5 x;").is_err());
+        assert!(parse("print :x x;").is_err());
+        assert!(parse("print : x;").is_err());
+        assert!(parse("print :100 1;").is_ok());
+        assert!(parse("print :101 1;").is_err());
+        assert!(parse("print :100000000000 1;").is_err());
+    }
+
+    #[test]
+    fn semicolons_are_optional_at_line_ends_when_enabled() {
+        let program = r#"
+            var a = 1
+            var b = a +
+                2
+            print a; print b
+            fun f() { return }
+            fun g() {
+                return a
+            }
+            var h = fun () { return 1 }
+            assert a == 1
+            while (a < 3) { a = a + 1 }
+            if (a > 1) print a
+            else print b
+            class C {
+                abstract m()
+            }
+            print "done""#;
+        let parse_relaxed = |program: &str| {
+            let mut scanner = Scanner::new(program);
+            let mut parser = Parser::new(scanner.scan_tokens());
+            parser.set_optional_semicolons(true);
+            parser.parse()
+        };
+
+        let statements = parse_relaxed(program).expect("Relaxed program should parse");
+        assert_eq!(statements.len(), 12);
+        match &*statements[1] {
+            Stmt::Var {
+                initializer: Some(initializer),
+                ..
+            } => assert!(matches!(**initializer, Expr::Binary { .. })),
+            _ => panic!("Expected a multi-line binary initializer"),
+        }
+        match &*statements[4] {
+            Stmt::Function { body, .. } => {
+                assert!(matches!(*body[0], Stmt::Return { value: None, .. }))
+            }
+            _ => panic!("Expected a function"),
+        }
+
+        // a "(" starting a line isn't a call of the line before
+        let statements = parse_relaxed("print a\n(b)").expect("Should parse as two statements");
+        assert_eq!(statements.len(), 2);
+        assert!(matches!(*statements[1], Stmt::Expression { .. }));
+        let statements = parse_relaxed("print a(\nb)").expect("Should parse as a call");
+        assert_eq!(statements.len(), 1);
+        let statements = parse("print a\n(b);").expect("Should parse as a call");
+        assert_eq!(statements.len(), 1);
+
+        // statements on the same line still need a ";"
+        assert!(parse_relaxed("print 1 print 2").is_err());
+        assert!(parse_relaxed("var a = 1 var b = 2").is_err());
+
+        // strict by default
+        assert!(parse(program).is_err());
+        assert!(parse("print 1").is_err());
+        assert!(parse("print 1;").is_ok());
+    }
 }
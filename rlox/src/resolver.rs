@@ -29,18 +29,90 @@ enum VariableState {
     Declared,
     Defined,
     Accessed,
+    Stored, // assigned a value which hasn't been read since
 }
 
+// What a declared name refers to, for naming it in diagnostics.
+#[derive(Copy, Clone, Debug)]
+enum DeclarationKind {
+    Class,
+    Function,
+    Parameter,
+    Variable,
+}
+
+impl DeclarationKind {
+    fn name(&self) -> &'static str {
+        match self {
+            DeclarationKind::Class => "Class",
+            DeclarationKind::Function => "Function",
+            DeclarationKind::Parameter => "Parameter",
+            DeclarationKind::Variable => "Variable",
+        }
+    }
+}
+
+/// How a resolver warning is reported.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Severity {
+    Ignore,
+    Warning,
+    Error, // fails the resolve pass, preventing execution
+}
+
+/// The severity of each kind of resolver warning. The default is lenient: warnings
+/// don't prevent execution, and shadowing isn't reported at all.
+#[derive(Clone, Debug)]
+pub struct ResolverConfig {
+    pub assignment_in_condition: Severity,
+    pub dead_store: Severity,
+    pub read_before_declaration: Severity,
+    pub shadowing: Severity,
+}
+
+impl Default for ResolverConfig {
+    fn default() -> Self {
+        ResolverConfig {
+            assignment_in_condition: Severity::Warning,
+            dead_store: Severity::Warning,
+            read_before_declaration: Severity::Warning,
+            shadowing: Severity::Ignore,
+        }
+    }
+}
+
+impl ResolverConfig {
+    /// Returns a config which makes every warning an error.
+    pub fn strict() -> Self {
+        ResolverConfig {
+            assignment_in_condition: Severity::Error,
+            dead_store: Severity::Error,
+            read_before_declaration: Severity::Error,
+            shadowing: Severity::Error,
+        }
+    }
+}
+
+// Identifies the function and loop nesting a variable was declared in, as
+// (number of enclosing functions, loop depth within the innermost function).
+type LoopPosition = (usize, i32);
+
 struct Variable {
     state: VariableState,
     token: Option<Token>,
+    position: LoopPosition,
+    store_token: Option<Token>,
+    captured: bool, // read by a nested function, which may run after any later store
 }
 
 impl Variable {
-    fn new(token: Option<Token>) -> Self {
+    fn new(token: Option<Token>, position: LoopPosition) -> Self {
         Variable {
             state: VariableState::Declared,
             token: token,
+            position,
+            store_token: None,
+            captured: false,
         }
     }
 
@@ -48,6 +120,9 @@ impl Variable {
         Variable {
             state: VariableState::Accessed,
             token: token,
+            position: (0, 0),
+            store_token: None,
+            captured: false,
         }
     }
 
@@ -59,6 +134,11 @@ impl Variable {
         self.state = VariableState::Accessed;
     }
 
+    fn mark_stored(&mut self, token: &Token) {
+        self.state = VariableState::Stored;
+        self.store_token = Some(token.clone());
+    }
+
     fn is_defined(&self) -> bool {
         match self.state {
             VariableState::Declared => false,
@@ -66,9 +146,11 @@ impl Variable {
         }
     }
 
+    // Stored counts as accessed, since assigning a value is a use of the
+    // variable; end_scope warns about the unread value instead.
     fn is_accessed(&self) -> bool {
         match self.state {
-            VariableState::Accessed => true,
+            VariableState::Accessed | VariableState::Stored => true,
             _ => false,
         }
     }
@@ -77,9 +159,16 @@ impl Variable {
 pub struct Resolver<'a> {
     interpreter: &'a mut Interpreter,
     scopes: Vec<HashMap<String, Variable>>,
+    // for each scope, names read there before any declaration in that scope, with
+    // the first token reading them
+    outer_reads: Vec<HashMap<String, Token>>,
     current_function: FunctionType,
     current_class: ClassType,
     loop_depths: Vec<i32>,
+    warnings: Vec<error::ResolveError>,
+    // warnings promoted to errors by the config
+    errors: Vec<error::ResolveError>,
+    config: ResolverConfig,
 }
 
 impl<'a> Resolver<'a> {
@@ -87,21 +176,83 @@ impl<'a> Resolver<'a> {
         Resolver {
             interpreter,
             scopes: vec![],
+            outer_reads: vec![],
             current_function: FunctionType::NoFunction,
             current_class: ClassType::NoClass,
             loop_depths: vec![0],
+            warnings: vec![],
+            errors: vec![],
+            config: ResolverConfig::default(),
+        }
+    }
+
+    pub fn set_config(&mut self, config: ResolverConfig) {
+        self.config = config;
+    }
+
+    /// Resolves `statements`, failing with the first error found, including any
+    /// warning which the config makes an error.
+    pub fn resolve(&mut self, statements: &[Box<Stmt>]) -> Result<()> {
+        let result = self.resolve_statements(statements);
+        match self.errors.first() {
+            Some(e) => Err(e.clone()),
+            None => result,
+        }
+    }
+
+    /// Returns the warnings emitted while resolving; these don't prevent execution.
+    pub fn warnings(&self) -> &Vec<error::ResolveError> {
+        &self.warnings
+    }
+
+    fn warn(&mut self, severity: Severity, token: &Token, message: &str) {
+        let warning = error::ResolveError::new(Some(token.clone()), message);
+        match severity {
+            Severity::Ignore => {}
+            Severity::Warning => self.warnings.push(warning),
+            Severity::Error => self.errors.push(warning),
         }
     }
 
-    pub fn resolve(&mut self, statements: &Vec<Box<Stmt>>) -> Result<()> {
-        self.resolve_statements(statements)
+    // Flags `if (a = b)`, which is likely a typo for `if (a == b)`. Wrapping the
+    // assignment in an extra set of parentheses marks it as intentional.
+    fn check_condition(&mut self, condition: &Expr) {
+        if let Expr::Assign { name, value: _ } = condition {
+            self.warn(
+                self.config.assignment_in_condition,
+                name,
+                &format!(
+                    "Assignment to \"{}\" used as a condition; did you mean \"==\"? Wrap it in parentheses if intentional.",
+                    name.lexeme
+                ),
+            );
+        }
     }
 
     fn begin_scope(&mut self) {
         self.scopes.push(HashMap::new());
+        self.outer_reads.push(HashMap::new());
     }
 
     fn end_scope(&mut self) -> Result<()> {
+        // look for assigned values which are never read ("dead stores")
+        let mut dead_stores = vec![];
+        if let Some(scope) = self.scopes.last() {
+            for var in scope.values() {
+                if let (VariableState::Stored, Some(token)) = (var.state, &var.store_token) {
+                    dead_stores.push(token.clone());
+                }
+            }
+        }
+        dead_stores.sort_by_key(|token| token.id);
+        for token in dead_stores {
+            self.warn(
+                self.config.dead_store,
+                &token,
+                &format!("Value assigned to \"{}\" is never read.", token.lexeme),
+            );
+        }
+
         if let Some(scope) = self.scopes.last() {
             // look for variable definitions which were never accessed
             for var in scope.values() {
@@ -119,41 +270,116 @@ impl<'a> Resolver<'a> {
             }
         }
         self.scopes.pop();
+        self.outer_reads.pop();
         Ok(())
     }
 
-    fn resolve_statements(&mut self, statements: &Vec<Box<Stmt>>) -> Result<()> {
+    fn resolve_statements(&mut self, statements: &[Box<Stmt>]) -> Result<()> {
+        self.hoist_functions(statements)?;
         for statement in statements {
             self.resolve_statement(statement)?;
         }
         Ok(())
     }
 
-    fn resolve_statement(&mut self, statement: &Box<Stmt>) -> Result<()> {
+    // Declares every function in a block up front, so functions in the same
+    // block may refer to one another regardless of declaration order.
+    fn hoist_functions(&mut self, statements: &[Box<Stmt>]) -> Result<()> {
+        for statement in statements {
+            if let Stmt::Function { name, .. } = &**statement {
+                self.declare(name, DeclarationKind::Function)?;
+                self.define(name);
+            }
+        }
+        Ok(())
+    }
+
+    fn is_hoisted(&self, name: &Token) -> bool {
+        match self.scopes.last().and_then(|scope| scope.get(&name.lexeme)) {
+            Some(Variable {
+                token: Some(token), ..
+            }) => token.id == name.id,
+            _ => false,
+        }
+    }
+
+    fn resolve_statement(&mut self, statement: &Stmt) -> Result<()> {
         statement.accept(self)
     }
 
-    fn resolve_expression(&mut self, expression: &Box<Expr>) -> Result<()> {
+    fn resolve_expression(&mut self, expression: &Expr) -> Result<()> {
         expression.accept(self)
     }
 
-    fn declare(&mut self, name: &Token) -> Result<()> {
+    fn loop_position(&self) -> LoopPosition {
+        (
+            self.loop_depths.len(),
+            *self.loop_depths.last().unwrap_or(&0),
+        )
+    }
+
+    fn declare(&mut self, name: &Token, kind: DeclarationKind) -> Result<()> {
+        if self.config.shadowing != Severity::Ignore && self.is_shadowing(name) {
+            self.warn(
+                self.config.shadowing,
+                name,
+                &format!(
+                    "Variable \"{}\" shadows a variable in an enclosing scope.",
+                    name.lexeme
+                ),
+            );
+        }
+        // declarations aren't hoisted, so a read of the name earlier in this scope
+        // referred to an enclosing scope or a global, which is easily mistaken
+        if let Some(read) = self
+            .outer_reads
+            .last()
+            .and_then(|reads| reads.get(&name.lexeme))
+            .cloned()
+        {
+            self.warn(
+                self.config.read_before_declaration,
+                &read,
+                &format!(
+                    "\"{}\" is read before its declaration in this scope, so it refers to an enclosing or global \"{}\".",
+                    name.lexeme, name.lexeme
+                ),
+            );
+        }
+        let position = self.loop_position();
         if let Some(scope) = self.scopes.last_mut() {
             if scope.contains_key(&name.lexeme) {
                 return Err(error::ResolveError::new(
                     Some(name.clone()),
                     &format!(
-                        "Variable named \"{}\" already defined in this scope.",
+                        "{} named \"{}\" already defined in this scope.",
+                        kind.name(),
                         name.lexeme
                     ),
                 ));
             }
             // establish variable as defined
-            scope.insert(name.lexeme.clone(), Variable::new(Some(name.clone())));
+            scope.insert(
+                name.lexeme.clone(),
+                Variable::new(Some(name.clone()), position),
+            );
         }
         Ok(())
     }
 
+    // Returns true if `name` is declared in a local scope enclosing the current one.
+    fn is_shadowing(&self, name: &Token) -> bool {
+        match self.scopes.split_last() {
+            Some((current, enclosing)) => {
+                !current.contains_key(&name.lexeme)
+                    && enclosing
+                        .iter()
+                        .any(|scope| scope.contains_key(&name.lexeme))
+            }
+            None => false,
+        }
+    }
+
     fn define(&mut self, name: &Token) {
         if let Some(scope) = self.scopes.last_mut() {
             if let Some(variable) = scope.get_mut(&name.lexeme) {
@@ -163,22 +389,81 @@ impl<'a> Resolver<'a> {
     }
 
     fn resolve_local(&mut self, variable: &Expr, name: &Token) -> Result<()> {
+        let function_depth = self.loop_depths.len();
         for i in (0..self.scopes.len()).rev() {
             if let Some(var) = self.scopes[i].get_mut(&name.lexeme) {
                 var.mark_accessed();
+                if var.position.0 != function_depth {
+                    var.captured = true;
+                }
                 self.interpreter
                     .resolve_local(variable, self.scopes.len() - 1 - i);
                 return Ok(());
             }
+            self.outer_reads[i]
+                .entry(name.lexeme.clone())
+                .or_insert_with(|| name.clone());
         }
         // not found, assume var is global.
         Ok(())
     }
 
+    // Records an assignment to `name`. Only the last assignment to a variable in a scope
+    // is checked for being read: a single pass can't tell that two assignments are on
+    // different branches of an if, so an overwritten value isn't flagged. Assignments
+    // from a nested function or loop are treated as read, since the value may be read
+    // by a later call or iteration, as are assignments to variables a closure reads.
+    fn resolve_store(&mut self, name: &Token) {
+        let position = self.loop_position();
+        for scope in self.scopes.iter_mut().rev() {
+            if let Some(var) = scope.get_mut(&name.lexeme) {
+                if var.position == position && !var.captured {
+                    var.mark_stored(name);
+                }
+                return;
+            }
+        }
+    }
+
+    fn resolve_for_loop(
+        &mut self,
+        initializer: &Option<Box<Stmt>>,
+        condition: &Option<Box<Expr>>,
+        increment: &Option<Box<Expr>>,
+        body: &Stmt,
+    ) -> Result<()> {
+        if let Some(initializer) = initializer {
+            self.resolve_statement(initializer)?;
+        }
+
+        if let Some(loop_depth) = self.loop_depths.last_mut() {
+            *loop_depth += 1;
+        }
+
+        let mut r = Ok(());
+        if let Some(condition) = condition {
+            self.check_condition(condition);
+            r = self.resolve_expression(condition);
+        }
+        if r.is_ok() {
+            if let Some(increment) = increment {
+                r = self.resolve_expression(increment);
+            }
+        }
+        if r.is_ok() {
+            r = self.resolve_statement(body);
+        }
+
+        if let Some(loop_depth) = self.loop_depths.last_mut() {
+            *loop_depth -= 1;
+        }
+        r
+    }
+
     fn resolve_function(
         &mut self,
-        parameters: &Vec<Token>,
-        body: &Vec<Box<Stmt>>,
+        parameters: &[Token],
+        body: &[Box<Stmt>],
         function_type: FunctionType,
     ) -> Result<()> {
         let enclosing_function = self.current_function;
@@ -187,7 +472,7 @@ impl<'a> Resolver<'a> {
         self.loop_depths.push(0);
         self.begin_scope();
         for param in parameters {
-            self.declare(param)?;
+            self.declare(param, DeclarationKind::Parameter)?;
             self.define(param);
         }
         self.resolve_statements(body)?;
@@ -202,7 +487,9 @@ impl<'a> Resolver<'a> {
 impl<'a> ExprVisitor<Result<()>> for Resolver<'a> {
     fn visit_assign_expr(&mut self, expr: &Expr, name: &Token, value: &Box<Expr>) -> Result<()> {
         self.resolve_expression(value)?;
-        self.resolve_local(expr, name)
+        self.resolve_local(expr, name)?;
+        self.resolve_store(name);
+        Ok(())
     }
 
     fn visit_binary_expr(
@@ -216,6 +503,18 @@ impl<'a> ExprVisitor<Result<()>> for Resolver<'a> {
         self.resolve_expression(right)
     }
 
+    fn visit_block_expr(
+        &mut self,
+        _expr: &Expr,
+        statements: &[Box<Stmt>],
+        final_expr: &Expr,
+    ) -> Result<()> {
+        self.begin_scope();
+        self.resolve_statements(statements)?;
+        self.resolve_expression(final_expr)?;
+        self.end_scope()
+    }
+
     fn visit_call_expr(
         &mut self,
         _expr: &Expr,
@@ -234,6 +533,15 @@ impl<'a> ExprVisitor<Result<()>> for Resolver<'a> {
         self.resolve_expression(object)
     }
 
+    fn visit_optional_get_expr(
+        &mut self,
+        _expr: &Expr,
+        object: &Expr,
+        _name: &Token,
+    ) -> Result<()> {
+        self.resolve_expression(object)
+    }
+
     fn visit_grouping_expr(&mut self, _expr: &Expr, content: &Box<Expr>) -> Result<()> {
         self.resolve_expression(content)
     }
@@ -247,6 +555,13 @@ impl<'a> ExprVisitor<Result<()>> for Resolver<'a> {
         self.resolve_function(parameters, body, FunctionType::Lambda)
     }
 
+    fn visit_list_expr(&mut self, _expr: &Expr, elements: &[Box<Expr>]) -> Result<()> {
+        for element in elements {
+            self.resolve_expression(element)?;
+        }
+        Ok(())
+    }
+
     fn visit_literal_expr(
         &mut self,
         _expr: &Expr,
@@ -333,11 +648,25 @@ impl<'a> ExprVisitor<Result<()>> for Resolver<'a> {
                 }
             }
         }
-        self.resolve_local(&expr, name)
+        self.resolve_local(expr, name)
     }
 }
 
 impl<'a> StmtVisitor<Result<()>> for Resolver<'a> {
+    fn visit_assert_stmt(
+        &mut self,
+        _stmt: &Stmt,
+        condition: &Expr,
+        _keyword: &Token,
+        message: &Option<Box<Expr>>,
+    ) -> Result<()> {
+        self.resolve_expression(condition)?;
+        if let Some(message) = message {
+            self.resolve_expression(message)?;
+        }
+        Ok(())
+    }
+
     fn visit_block_stmt(&mut self, _stmt: &Stmt, statements: &Vec<Box<Stmt>>) -> Result<()> {
         self.begin_scope();
         self.resolve_statements(statements)?;
@@ -368,7 +697,7 @@ impl<'a> StmtVisitor<Result<()>> for Resolver<'a> {
         let enclosing_class = self.current_class;
         self.current_class = ClassType::Class;
 
-        self.declare(name)?;
+        self.declare(name, DeclarationKind::Class)?;
         self.define(name);
 
         if let Some(super_class) = super_class {
@@ -409,6 +738,14 @@ impl<'a> StmtVisitor<Result<()>> for Resolver<'a> {
 
         for method in methods {
             match &**method {
+                Stmt::Function {
+                    name: _,
+                    parameters: _,
+                    body: _,
+                    fn_type: CallableType::Abstract,
+                } => {
+                    // abstract methods have no body, so their parameters go unused
+                }
                 Stmt::Function {
                     name,
                     parameters,
@@ -422,7 +759,7 @@ impl<'a> StmtVisitor<Result<()>> for Resolver<'a> {
                             FunctionType::Method
                         }
                     };
-                    self.resolve_function(&parameters, &body, declaration)?;
+                    self.resolve_function(parameters, body, declaration)?;
                 }
                 _ => {
                     return Err(error::ResolveError::new(
@@ -448,7 +785,7 @@ impl<'a> StmtVisitor<Result<()>> for Resolver<'a> {
                     body,
                     fn_type: _,
                 } => {
-                    self.resolve_function(&parameters, &body, FunctionType::ClassMethod)?;
+                    self.resolve_function(parameters, body, FunctionType::ClassMethod)?;
                 }
                 _ => {
                     return Err(error::ResolveError::new(
@@ -463,10 +800,42 @@ impl<'a> StmtVisitor<Result<()>> for Resolver<'a> {
         Ok(())
     }
 
+    fn visit_destructure_stmt(
+        &mut self,
+        _stmt: &Stmt,
+        names: &[Token],
+        initializer: &Expr,
+    ) -> Result<()> {
+        for name in names {
+            self.declare(name, DeclarationKind::Variable)?;
+        }
+        self.resolve_expression(initializer)?;
+        for name in names {
+            self.define(name);
+        }
+        Ok(())
+    }
+
     fn visit_expression_stmt(&mut self, _stmt: &Stmt, expression: &Box<Expr>) -> Result<()> {
         self.resolve_expression(expression)
     }
 
+    fn visit_for_stmt(
+        &mut self,
+        _stmt: &Stmt,
+        initializer: &Option<Box<Stmt>>,
+        condition: &Option<Box<Expr>>,
+        increment: &Option<Box<Expr>>,
+        body: &Stmt,
+    ) -> Result<()> {
+        // the initializer is scoped to the loop, matching the interpreter's
+        // environment for the loop.
+        self.begin_scope();
+        let result = self.resolve_for_loop(initializer, condition, increment, body);
+        let end = self.end_scope();
+        result.and(end)
+    }
+
     fn visit_function_stmt(
         &mut self,
         _stmt: &Stmt,
@@ -475,8 +844,10 @@ impl<'a> StmtVisitor<Result<()>> for Resolver<'a> {
         body: &Vec<Box<Stmt>>,
         _fn_type: CallableType,
     ) -> Result<()> {
-        self.declare(name)?;
-        self.define(name);
+        if !self.is_hoisted(name) {
+            self.declare(name, DeclarationKind::Function)?;
+            self.define(name);
+        }
         self.resolve_function(parameters, body, FunctionType::Function)
     }
 
@@ -487,6 +858,7 @@ impl<'a> StmtVisitor<Result<()>> for Resolver<'a> {
         then_branch: &Box<Stmt>,
         else_branch: &Option<Box<Stmt>>,
     ) -> Result<()> {
+        self.check_condition(condition);
         self.resolve_expression(condition)?;
         self.resolve_statement(then_branch)?;
         if let Some(else_branch) = else_branch {
@@ -495,7 +867,13 @@ impl<'a> StmtVisitor<Result<()>> for Resolver<'a> {
         Ok(())
     }
 
-    fn visit_print_stmt(&mut self, _stmt: &Stmt, expression: &Box<Expr>) -> Result<()> {
+    fn visit_print_stmt(
+        &mut self,
+        _stmt: &Stmt,
+        expression: &Expr,
+        _newline: bool,
+        _precision: Option<usize>,
+    ) -> Result<()> {
         self.resolve_expression(expression)
     }
 
@@ -533,7 +911,7 @@ impl<'a> StmtVisitor<Result<()>> for Resolver<'a> {
         name: &Token,
         initializer: &Option<Box<Expr>>,
     ) -> Result<()> {
-        self.declare(name)?;
+        self.declare(name, DeclarationKind::Variable)?;
         if let Some(initializer) = initializer {
             self.resolve_expression(initializer)?;
         }
@@ -541,6 +919,13 @@ impl<'a> StmtVisitor<Result<()>> for Resolver<'a> {
         Ok(())
     }
 
+    fn visit_var_list_stmt(&mut self, _stmt: &Stmt, declarations: &[Box<Stmt>]) -> Result<()> {
+        for declaration in declarations {
+            self.resolve_statement(declaration)?;
+        }
+        Ok(())
+    }
+
     fn visit_while_stmt(
         &mut self,
         _stmt: &Stmt,
@@ -551,6 +936,7 @@ impl<'a> StmtVisitor<Result<()>> for Resolver<'a> {
             *loop_depth += 1;
         }
 
+        self.check_condition(condition);
         let mut r = self.resolve_expression(condition);
         match r {
             Ok(()) => {
@@ -602,6 +988,19 @@ mod tests {
         }
     }
 
+    fn warnings(program: &str) -> Vec<error::ResolveError> {
+        let mut scanner = scanner::Scanner::new(program);
+        let tokens = scanner.scan_tokens();
+        let mut parser = parser::Parser::new(tokens);
+        let ast = parser.parse().unwrap();
+        let mut interpreter = Interpreter::new();
+        let mut resolver = Resolver::new(&mut interpreter);
+        resolver
+            .resolve(&ast)
+            .expect("Expected successful resolve pass");
+        resolver.warnings().clone()
+    }
+
     #[test]
     fn return_outside_function_is_error() {
         let inputs = vec![
@@ -836,6 +1235,26 @@ mod tests {
                 "#,
                 Expectation::Error,
             ),
+            (
+                r#"
+                class Counter {
+                    reader() {
+                        return fun () { return fun () { return this.count; }; };
+                    }
+                    named() {
+                        fun read() { return this.count; }
+                        return read;
+                    }
+                }
+                "#,
+                Expectation::Ok,
+            ),
+            (
+                r#"
+                var f = fun () { return this; };
+                "#,
+                Expectation::Error,
+            ),
         ];
 
         for (program, expectation) in inputs {
@@ -859,6 +1278,15 @@ mod tests {
                 "#,
                 Expectation::Error,
             ),
+            (
+                r#"
+                class A {}
+                {
+                    class A < A {}
+                }
+                "#,
+                Expectation::Error,
+            ),
         ];
 
         for (program, expectation) in inputs {
@@ -906,4 +1334,275 @@ mod tests {
             verify(program, expectation);
         }
     }
+
+    #[test]
+    fn abstract_method_parameters_are_not_unused_variables() {
+        verify(
+            r#"
+            class Shape {
+                abstract scale(factor);
+            }
+            "#,
+            Expectation::Ok,
+        );
+    }
+
+    #[test]
+    fn assignment_as_condition_warns() {
+        let inputs = vec![
+            ("var x = 0; if (x = 1) print x;", 1),
+            ("var x = 0; while (x = nil) print x;", 1),
+            ("var x = 0; for (; x = nil;) print x;", 1),
+            ("var x = 0; if ((x = 1)) print x;", 0),
+            ("var x = 0; while ((x = nil)) print x;", 0),
+            ("var x = 0; if (x == 1) print x;", 0),
+        ];
+
+        for (program, expected_warnings) in inputs {
+            assert_eq!(
+                warnings(program).len(),
+                expected_warnings,
+                "Unexpected warning count for: {}",
+                program
+            );
+        }
+    }
+
+    #[test]
+    fn nested_functions_may_reference_later_functions() {
+        verify(
+            r#"
+            fun outer() {
+                fun is_even(n) { if (n == 0) return true; return is_odd(n - 1); }
+                fun is_odd(n) { if (n == 0) return false; return is_even(n - 1); }
+                return is_even(4);
+            }
+            print outer();
+            "#,
+            Expectation::Ok,
+        );
+
+        verify(
+            r#"
+            {
+                fun a() {}
+                fun a() {}
+                a();
+            }
+            "#,
+            Expectation::Error,
+        );
+    }
+
+    #[test]
+    fn unread_assignments_warn() {
+        // the first value is overwritten unread, but only a value which is never
+        // read before the end of its scope is reported
+        assert!(warnings("{ var a = 1; a = 2; print a; }").is_empty());
+
+        let dead = warnings("{ var a = 1; a = 2; }");
+        assert_eq!(dead.len(), 1);
+        assert_eq!(dead[0].message, "Value assigned to \"a\" is never read.");
+
+        let inputs = vec![
+            // assigned in a loop, read by the next iteration
+            "{ var a = 0; var i = 0; while (i < 3) { print a; a = a + 1; i = i + 1; } }",
+            // assigned by a closure, read by a later call
+            "{ var a = 0; fun f() { print a; a = a + 1; } f(); f(); }",
+            // read by a closure called after the assignment
+            "{ var a = 0; fun f() { print a; } a = 5; f(); }",
+            "{ var a = 0; a = a + 1; print a; }",
+        ];
+        for program in inputs {
+            assert!(warnings(program).is_empty(), "{}", program);
+        }
+    }
+
+    #[test]
+    fn reads_before_a_later_declaration_in_the_same_scope_warn() {
+        // `x` in the print refers to the global, not the block's later `x`
+        let program = "var x = 0; { print x; var x = 1; print x; }";
+        let read_early = warnings(program);
+        assert_eq!(read_early.len(), 1);
+        assert_eq!(
+            read_early[0].message,
+            "\"x\" is read before its declaration in this scope, so it refers to an enclosing or global \"x\"."
+        );
+        assert_eq!(read_early[0].token.as_ref().unwrap().id, 7);
+
+        let inputs = vec![
+            // a read from a nested block or function counts too
+            "{ { print x; } var x = 1; print x; }",
+            "{ fun f() { return x; } var x = 1; print f() + x; }",
+            "{ print C; class C {} print C; }",
+        ];
+        for program in inputs {
+            assert_eq!(warnings(program).len(), 1, "{}", program);
+        }
+
+        let inputs = vec![
+            // globals aren't checked, since they are looked up when executed
+            "print x; var x = 1;",
+            // functions are hoisted, so reads before them refer to them
+            "{ print f(); fun f() { return 1; } }",
+            "{ var x = 1; print x; }",
+            // an enclosing variable may be read before the block declares its own
+            "{ var x = 1; print x; { var y = x; print y; } }",
+        ];
+        for program in inputs {
+            assert!(warnings(program).is_empty(), "{}", program);
+        }
+    }
+
+    #[test]
+    fn shadowing_warns_when_enabled() {
+        let shadowing_warnings = |program: &str| {
+            let mut scanner = scanner::Scanner::new(program);
+            let tokens = scanner.scan_tokens();
+            let mut parser = parser::Parser::new(tokens);
+            let ast = parser.parse().unwrap();
+            let mut interpreter = Interpreter::new();
+            let mut resolver = Resolver::new(&mut interpreter);
+            resolver.set_config(ResolverConfig {
+                shadowing: Severity::Warning,
+                ..ResolverConfig::default()
+            });
+            resolver
+                .resolve(&ast)
+                .expect("Expected successful resolve pass");
+            resolver.warnings().clone()
+        };
+
+        let program = "{ var x = 1; { var x = 2; print x; } print x; }";
+        let shadowed = shadowing_warnings(program);
+        assert_eq!(shadowed.len(), 1);
+        assert_eq!(
+            shadowed[0].message,
+            "Variable \"x\" shadows a variable in an enclosing scope."
+        );
+        // off by default
+        assert!(warnings(program).is_empty());
+
+        let inputs = vec![
+            // sibling scopes
+            "{ { var x = 1; print x; } { var x = 2; print x; } }",
+            // globals are exempt
+            "var x = 1; { var x = 2; print x; } print x;",
+            "var x = 1; fun f(x) { return x; } print f(x);",
+        ];
+        for program in inputs {
+            assert!(shadowing_warnings(program).is_empty(), "{}", program);
+        }
+
+        // parameters shadow too
+        let program = "{ var x = 1; fun f(x) { return x; } print f(x); }";
+        assert_eq!(shadowing_warnings(program).len(), 1);
+    }
+
+    #[test]
+    fn strict_config_makes_warnings_errors() {
+        let resolve = |program: &str, config: ResolverConfig| {
+            let mut scanner = scanner::Scanner::new(program);
+            let tokens = scanner.scan_tokens();
+            let mut parser = parser::Parser::new(tokens);
+            let ast = parser.parse().unwrap();
+            let mut interpreter = Interpreter::new();
+            let mut resolver = Resolver::new(&mut interpreter);
+            resolver.set_config(config);
+            let result = resolver.resolve(&ast);
+            (result, resolver.warnings().clone())
+        };
+
+        let program = "{ var x = 1; { var x = 2; print x; } print x; }";
+        let lenient = ResolverConfig {
+            shadowing: Severity::Warning,
+            ..ResolverConfig::default()
+        };
+        let (result, warnings) = resolve(program, lenient);
+        assert!(result.is_ok());
+        assert_eq!(warnings.len(), 1);
+
+        let (result, warnings) = resolve(program, ResolverConfig::strict());
+        assert_eq!(
+            result.unwrap_err().message,
+            "Variable \"x\" shadows a variable in an enclosing scope."
+        );
+        assert!(warnings.is_empty());
+
+        let inputs = vec![
+            "{ var a = 1; a = 2; }",
+            "var a = 1; if (a = 2) print a;",
+            "{ print a; var a = 1; print a; }",
+        ];
+        for program in inputs {
+            assert!(
+                resolve(program, ResolverConfig::default()).0.is_ok(),
+                "{}",
+                program
+            );
+            assert!(
+                resolve(program, ResolverConfig::strict()).0.is_err(),
+                "{}",
+                program
+            );
+        }
+        assert!(resolve("{ var a = 1; print a; }", ResolverConfig::strict())
+            .0
+            .is_ok());
+    }
+
+    #[test]
+    fn redeclaration_errors_name_the_kind_of_declaration() {
+        let inputs = vec![
+            (
+                "fun f(a, a) {}",
+                "Parameter named \"a\" already defined in this scope.",
+            ),
+            (
+                "{ fun f() {} fun f() {} }",
+                "Function named \"f\" already defined in this scope.",
+            ),
+            (
+                "{ var a = 1; var a = 2; }",
+                "Variable named \"a\" already defined in this scope.",
+            ),
+            (
+                "{ class A {} class A {} }",
+                "Class named \"A\" already defined in this scope.",
+            ),
+            // functions are hoisted, so are declared before the variable
+            (
+                "{ var f = 1; fun f() {} }",
+                "Variable named \"f\" already defined in this scope.",
+            ),
+        ];
+        for (program, message) in inputs {
+            let mut scanner = scanner::Scanner::new(program);
+            let tokens = scanner.scan_tokens();
+            let mut parser = parser::Parser::new(tokens);
+            let ast = parser.parse().unwrap();
+            let mut interpreter = Interpreter::new();
+            let mut resolver = Resolver::new(&mut interpreter);
+            let error = resolver.resolve(&ast).expect_err(program);
+            assert_eq!(error.message, message, "{}", program);
+        }
+    }
+
+    #[test]
+    fn failing_for_loops_leave_scopes_balanced() {
+        let inputs = vec![
+            "for (var i = 0; i < 1; i = i + 1) return;",
+            "for (var i = 0; i < 1; i = i + 1) print this;",
+        ];
+        for program in inputs {
+            let mut scanner = scanner::Scanner::new(program);
+            let mut parser = parser::Parser::new(scanner.scan_tokens());
+            let ast = parser.parse().unwrap();
+            let mut interpreter = Interpreter::new();
+            let mut resolver = Resolver::new(&mut interpreter);
+            assert!(resolver.resolve(&ast).is_err(), "{}", program);
+            assert!(resolver.scopes.is_empty(), "{}", program);
+            assert!(resolver.outer_reads.is_empty(), "{}", program);
+        }
+    }
 }
@@ -30,6 +30,9 @@ pub enum TokenType {
     GreaterEqual,
     Less,
     LessEqual,
+    QuestionDot,
+    QuestionQuestion,
+    QuestionQuestionEqual,
 
     // Literals.
     Identifier,
@@ -37,7 +40,9 @@ pub enum TokenType {
     Number,
 
     // Keywords.
+    Abstract,
     And,
+    Assert,
     Break,
     Class,
     Else,
@@ -54,6 +59,7 @@ pub enum TokenType {
     True,
     Var,
     While,
+    Write,
 
     Eof,
 }
@@ -124,7 +130,7 @@ pub struct Token {
     pub lexeme: String,
     pub literal: Option<Literal>,
     pub line: i32,
-    pub id: i32, // unique id
+    pub id: usize, // unique id
 }
 
 impl Token {
@@ -133,7 +139,7 @@ impl Token {
         lexeme: String,
         literal: Option<Literal>,
         line: i32,
-        id: i32,
+        id: usize,
     ) -> Token {
         Token {
             token_type,
@@ -207,27 +213,38 @@ fn is_alpha_numeric(grapheme: &str) -> bool {
 ///////////////////////////////////////////////////////////////////////////////
 
 pub struct Scanner<'a> {
-    _source: &'a str,
+    source: &'a str,
     current_grapheme: &'a str,
     remainder: &'a str,
     line: i32,
-    current_id: i32,
+    current_id: usize,
     keywords: HashMap<String, TokenType>,
+    errors: Vec<error::ScanError>,
 }
 
 impl Scanner<'_> {
     pub fn new<'a>(source: &'a str) -> Scanner {
         Scanner {
-            _source: source,
+            source,
             current_grapheme: "",
             remainder: source,
             line: 1,
             current_id: 0,
             keywords: Scanner::create_keywords(),
+            errors: vec![],
         }
     }
 
-    fn next_token_id(&mut self) -> i32 {
+    /// Returns the errors found by scan_tokens; scanning skips past them.
+    pub fn errors(&self) -> &Vec<error::ScanError> {
+        &self.errors
+    }
+
+    fn error(&mut self, message: &str) {
+        self.errors.push(error::ScanError::new(self.line, message));
+    }
+
+    fn next_token_id(&mut self) -> usize {
         let id = self.current_id;
         self.current_id += 1;
         id
@@ -235,7 +252,9 @@ impl Scanner<'_> {
 
     fn create_keywords() -> HashMap<String, TokenType> {
         let mut keywords = HashMap::new();
+        keywords.insert(String::from("abstract"), TokenType::Abstract);
         keywords.insert(String::from("and"), TokenType::And);
+        keywords.insert(String::from("assert"), TokenType::Assert);
         keywords.insert(String::from("break"), TokenType::Break);
         keywords.insert(String::from("class"), TokenType::Class);
         keywords.insert(String::from("else"), TokenType::Else);
@@ -252,6 +271,7 @@ impl Scanner<'_> {
         keywords.insert(String::from("true"), TokenType::True);
         keywords.insert(String::from("var"), TokenType::Var);
         keywords.insert(String::from("while"), TokenType::While);
+        keywords.insert(String::from("write"), TokenType::Write);
         keywords
     }
 
@@ -337,7 +357,7 @@ impl Scanner<'_> {
         }
 
         if self.is_at_end() {
-            error::report::parse_error_at_line(self.line, "Unterminated string");
+            self.error("Unterminated string");
             return;
         }
 
@@ -375,9 +395,22 @@ impl Scanner<'_> {
             }
         }
 
+        // an `_i` suffix, e.g. `5_i`, truncates the literal to an integer
+        let is_integer = self.peek() == "_"
+            && self.peek_next() == "i"
+            && !self.remainder.get(2..3).is_some_and(is_alpha_numeric);
+
         // now parse to double
         let d = string_value.parse::<f64>();
         if let Ok(v) = d {
+            let v = if is_integer {
+                self.advance();
+                self.advance();
+                string_value.push_str("_i");
+                v as i64 as f64
+            } else {
+                v
+            };
             tokens.push(Token::new(
                 TokenType::Number,
                 string_value,
@@ -387,7 +420,7 @@ impl Scanner<'_> {
             ));
         } else {
             let error_message = format!("Unable to parse number literal \"{}\"", string_value);
-            error::report::parse_error_at_line(self.line, &error_message);
+            self.error(&error_message);
         }
     }
 
@@ -429,6 +462,14 @@ impl Scanner<'_> {
     pub fn scan_tokens(&mut self) -> Vec<Token> {
         let mut tokens: Vec<Token> = vec![];
 
+        // Skip a shebang line, so scripts can be run directly. The newline is left
+        // for the loop below, so it's counted as usual.
+        if self.remainder.len() == self.source.len() && self.remainder.starts_with("#!") {
+            while self.peek() != "\n" && !self.is_at_end() {
+                self.advance();
+            }
+        }
+
         loop {
             if let Some(g) = self.next_grapheme() {
                 match g.as_str() {
@@ -502,13 +543,43 @@ impl Scanner<'_> {
                         self.line,
                         self.next_token_id(),
                     )),
-                    "?" => tokens.push(Token::new(
-                        TokenType::QuestionMark,
-                        g,
-                        None,
-                        self.line,
-                        self.next_token_id(),
-                    )),
+                    "?" => {
+                        if self.match_next_grapheme(".") {
+                            tokens.push(Token::new(
+                                TokenType::QuestionDot,
+                                "?.".to_string(),
+                                None,
+                                self.line,
+                                self.next_token_id(),
+                            ));
+                        } else if self.match_next_grapheme("?") {
+                            if self.match_next_grapheme("=") {
+                                tokens.push(Token::new(
+                                    TokenType::QuestionQuestionEqual,
+                                    "??=".to_string(),
+                                    None,
+                                    self.line,
+                                    self.next_token_id(),
+                                ));
+                            } else {
+                                tokens.push(Token::new(
+                                    TokenType::QuestionQuestion,
+                                    "??".to_string(),
+                                    None,
+                                    self.line,
+                                    self.next_token_id(),
+                                ));
+                            }
+                        } else {
+                            tokens.push(Token::new(
+                                TokenType::QuestionMark,
+                                g,
+                                None,
+                                self.line,
+                                self.next_token_id(),
+                            ));
+                        }
+                    }
                     ":" => tokens.push(Token::new(
                         TokenType::Colon,
                         g,
@@ -628,7 +699,7 @@ impl Scanner<'_> {
                         } else if is_alpha(&g) {
                             self.identifier(&g, &mut tokens);
                         } else {
-                            panic!("Unexpected character: \"{}\"", g);
+                            self.error(&format!("Unexpected character \"{}\"", g));
                         }
                     }
                 }
@@ -729,7 +800,7 @@ mod tests {
 
     #[test]
     fn produces_expected_tokens() {
-        let mut scanner = Scanner::new("( ) { } < > <= >= = == ! !=");
+        let mut scanner = Scanner::new("( ) { } < > <= >= = == ! != ? ?. ?? ??=");
         let tokens = scanner.scan_tokens();
         let expected_token_types = vec![
             TokenType::LeftParen,
@@ -744,6 +815,10 @@ mod tests {
             TokenType::EqualEqual,
             TokenType::Bang,
             TokenType::BangEqual,
+            TokenType::QuestionMark,
+            TokenType::QuestionDot,
+            TokenType::QuestionQuestion,
+            TokenType::QuestionQuestionEqual,
             TokenType::Eof,
         ];
         let token_types: Vec<TokenType> = tokens.into_iter().map(|t| t.token_type).collect();
@@ -792,6 +867,29 @@ mod tests {
         }
     }
 
+    #[test]
+    fn integer_suffix_truncates_number_literals() {
+        let mut scanner = Scanner::new("5_i 2.75_i -2.5_i 5_index 5");
+        let tokens = scanner.scan_tokens();
+        let expected = vec![
+            (TokenType::Number, "5_i", Some(Literal::Number(5.0))),
+            (TokenType::Number, "2.75_i", Some(Literal::Number(2.0))),
+            (TokenType::Minus, "-", None),
+            (TokenType::Number, "2.5_i", Some(Literal::Number(2.0))),
+            // only a whole `_i` is a suffix
+            (TokenType::Number, "5", Some(Literal::Number(5.0))),
+            (TokenType::Identifier, "_index", None),
+            (TokenType::Number, "5", Some(Literal::Number(5.0))),
+            (TokenType::Eof, "", None),
+        ];
+        assert_eq!(tokens.len(), expected.len());
+        for (token, (token_type, lexeme, literal)) in tokens.iter().zip(expected) {
+            assert_eq!(token.token_type, token_type);
+            assert_eq!(token.lexeme, lexeme);
+            assert_eq!(token.literal, literal);
+        }
+    }
+
     #[test]
     fn produces_expected_f64_literal() {
         let double_val = 12345.6789;
@@ -816,14 +914,16 @@ mod tests {
     #[test]
     fn produces_expected_keywords() {
         let mut scanner = Scanner::new(
-            "and class else false for fun if nil or print return super this true var while",
+            "abstract and assert class else false for fun if nil or print return super this true var while write",
         );
         let tokens = scanner.scan_tokens();
         let token_types: Vec<TokenType> = tokens.into_iter().map(|t| t.token_type).collect();
         assert_eq!(
             token_types,
             vec![
+                TokenType::Abstract,
                 TokenType::And,
+                TokenType::Assert,
                 TokenType::Class,
                 TokenType::Else,
                 TokenType::False,
@@ -839,11 +939,51 @@ mod tests {
                 TokenType::True,
                 TokenType::Var,
                 TokenType::While,
+                TokenType::Write,
                 TokenType::Eof,
             ]
         );
     }
 
+    #[test]
+    fn assigns_strictly_increasing_token_ids() {
+        let mut scanner = Scanner::new("var a = \"foo\";\nprint a + 1.5; // bar\n{}");
+        let tokens = scanner.scan_tokens();
+        assert!(tokens.len() > 1);
+        for pair in tokens.windows(2) {
+            assert!(pair[0].id < pair[1].id, "{} !< {}", pair[0], pair[1]);
+        }
+    }
+
+    #[test]
+    fn skips_a_leading_shebang_line() {
+        let mut scanner = Scanner::new("#!/usr/bin/env rlox\nprint 1;\n#");
+        let tokens = scanner.scan_tokens();
+        let types: Vec<TokenType> = tokens.iter().map(|t| t.token_type).collect();
+        assert_eq!(
+            types,
+            vec![
+                TokenType::Print,
+                TokenType::Number,
+                TokenType::Semicolon,
+                TokenType::Eof
+            ]
+        );
+        assert_eq!(tokens[0].line, 2);
+        assert_eq!(scanner.errors().len(), 1);
+        assert_eq!(scanner.errors()[0].line, 3);
+        assert_eq!(scanner.errors()[0].message, "Unexpected character \"#\"");
+
+        // only on the first line
+        let mut scanner = Scanner::new("print 1;\n#!/usr/bin/env rlox\n");
+        scanner.scan_tokens();
+        assert_eq!(scanner.errors()[0].line, 2);
+
+        let mut scanner = Scanner::new(" #!/usr/bin/env rlox\n");
+        scanner.scan_tokens();
+        assert_eq!(scanner.errors()[0].line, 1);
+    }
+
     #[test]
     fn produces_expected_identifiers() {
         let mut scanner = Scanner::new("{foo bar baz}");
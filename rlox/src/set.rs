@@ -0,0 +1,110 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashSet;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+use crate::object::LoxObject;
+
+// Hashable projection of the primitive LoxObjects which may be set members.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+enum SetKey {
+    Boolean(bool),
+    Nil,
+    Number(u64),
+    Str(String),
+}
+
+impl SetKey {
+    fn from_object(value: &LoxObject) -> Option<SetKey> {
+        match value {
+            LoxObject::Boolean(b) => Some(SetKey::Boolean(*b)),
+            LoxObject::Nil => Some(SetKey::Nil),
+            LoxObject::Number(n) => {
+                // -0.0 == 0.0, so they must hash identically
+                let n = if *n == 0.0 { 0.0 } else { *n };
+                Some(SetKey::Number(n.to_bits()))
+            }
+            LoxObject::Str(s) => Some(SetKey::Str(s.clone())),
+            _ => None,
+        }
+    }
+}
+
+/// An insertion-ordered set of primitive LoxObjects (numbers, strings, booleans and nil).
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LoxSet {
+    keys: HashSet<SetKey>,
+    elements: Vec<LoxObject>,
+}
+
+impl LoxSet {
+    pub fn new() -> Self {
+        LoxSet {
+            keys: HashSet::new(),
+            elements: vec![],
+        }
+    }
+
+    /// Returns a deterministic hash of `value`, or None if it isn't hashable.
+    /// Values which are equal as set members hash equally.
+    pub fn hash(value: &LoxObject) -> Option<u64> {
+        let key = SetKey::from_object(value)?;
+        // DefaultHasher::new() uses fixed keys, unlike the randomly seeded HashMap default
+        let mut hasher = DefaultHasher::new();
+        key.hash(&mut hasher);
+        Some(hasher.finish())
+    }
+
+    /// Returns true if `value` is a type which may be stored in a set.
+    pub fn is_hashable(value: &LoxObject) -> bool {
+        SetKey::from_object(value).is_some()
+    }
+
+    /// Adds `value` to the set, returning true if it wasn't already present.
+    /// Values which aren't hashable are never added.
+    pub fn add(&mut self, value: &LoxObject) -> bool {
+        if let Some(key) = SetKey::from_object(value) {
+            if self.keys.insert(key) {
+                self.elements.push(value.clone());
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Iterates the set's members in insertion order.
+    pub fn iter(&self) -> impl Iterator<Item = &LoxObject> {
+        self.elements.iter()
+    }
+
+    pub fn contains(&self, value: &LoxObject) -> bool {
+        if let Some(key) = SetKey::from_object(value) {
+            self.keys.contains(&key)
+        } else {
+            false
+        }
+    }
+
+    /// Removes `value` from the set, returning true if it was present.
+    pub fn remove(&mut self, value: &LoxObject) -> bool {
+        if let Some(key) = SetKey::from_object(value) {
+            if self.keys.remove(&key) {
+                self.elements
+                    .retain(|e| SetKey::from_object(e).as_ref() != Some(&key));
+                return true;
+            }
+        }
+        false
+    }
+}
+
+impl fmt::Display for LoxSet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let elements: Vec<String> = self
+            .elements
+            .iter()
+            .map(|e| e.to_element_string())
+            .collect();
+        write!(f, "{{{}}}", elements.join(", "))
+    }
+}